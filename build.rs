@@ -0,0 +1,54 @@
+use chrono::{Datelike, NaiveDateTime, Utc};
+use std::env;
+use std::process::Command;
+
+/// Embeds the metadata displayed by `cwver --version --verbose`.
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // SOURCE_DATE_EPOCH keeps reproducible builds reproducible
+    let built = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .and_then(|epoch| NaiveDateTime::from_timestamp_opt(epoch, 0))
+        .unwrap_or_else(|| Utc::now().naive_utc())
+        .date();
+    let iso_week = built.iso_week();
+
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase())
+        })
+        .filter(|feature| feature != "default")
+        .map(|feature| feature.replace('_', "-"))
+        .collect();
+    features.sort();
+
+    println!("cargo:rustc-env=CWVER_BUILD_COMMIT={}", commit);
+    println!(
+        "cargo:rustc-env=CWVER_BUILD_DATE={:02}w{:02}.{} ({})",
+        iso_week.year() % 100,
+        iso_week.week(),
+        built.weekday().number_from_monday(),
+        built
+    );
+    println!(
+        "cargo:rustc-env=CWVER_BUILD_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+    let features = match features.is_empty() {
+        true => "none".to_string(),
+        false => features.join(", "),
+    };
+    println!("cargo:rustc-env=CWVER_BUILD_FEATURES={}", features);
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+}
@@ -0,0 +1,21 @@
+#![no_main]
+use cwver::{parse, CwVersion, Schema};
+use libfuzzer_sys::fuzz_target;
+
+// Any input has to be rejected with an error instead of a panic, by strict and lenient parsing.
+fuzz_target!(|data: &[u8]| {
+    let input = match std::str::from_utf8(data) {
+        Ok(input) => input,
+        Err(_) => return,
+    };
+    let lenient = Schema::default();
+    let strict = Schema {
+        strict: true,
+        ..Schema::default()
+    };
+    for schema in &[strict, lenient] {
+        let _ = CwVersion::parse(input, schema);
+        let _ = parse::validate(input, schema);
+        let _ = parse::range_str(input, schema);
+    }
+});
@@ -0,0 +1,585 @@
+use crate::calendar::{count_workdays, effort, jump_n_workdays, next_workday, Calendar};
+use crate::error::CwverError;
+use crate::range::{self, DateRange};
+use chrono::NaiveDate;
+use tracing::debug;
+
+/// The middle of a regression range, a day or a [`Slot`].
+#[derive(Debug, PartialEq)]
+pub enum Bisection<T = NaiveDate> {
+    /// `from` and `till` are adjacent workdays, there is nothing left to bisect.
+    TooClose,
+    /// The range has an odd number of workdays and a single day in the middle.
+    Single(T),
+    /// The range has an even number of workdays, both days are equally good starting points.
+    Pair(T, T),
+}
+
+impl<T> Bisection<T> {
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> Bisection<U> {
+        match self {
+            Bisection::TooClose => Bisection::TooClose,
+            Bisection::Single(middle) => Bisection::Single(f(middle)),
+            Bisection::Pair(left, right) => Bisection::Pair(f(left), f(right)),
+        }
+    }
+}
+
+impl<T> Bisection<T> {
+    /// A single starting point out of two equivalent ones, unless both are preferred.
+    pub fn preferring(self, prefer: Prefer) -> Bisection<T> {
+        match (self, prefer) {
+            (Bisection::Pair(left, _), Prefer::Earlier) => Bisection::Single(left),
+            (Bisection::Pair(_, right), Prefer::Later) => Bisection::Single(right),
+            (bisection, _) => bisection,
+        }
+    }
+}
+
+/// Which of two equivalent starting points to propose.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prefer {
+    Earlier,
+    Later,
+    Both,
+}
+
+impl std::str::FromStr for Prefer {
+    type Err = CwverError;
+
+    fn from_str(prefer: &str) -> Result<Prefer, CwverError> {
+        match prefer {
+            "earlier" => Ok(Prefer::Earlier),
+            "later" => Ok(Prefer::Later),
+            "both" => Ok(Prefer::Both),
+            _ => Err(CwverError::Parse(format!(
+                "unknown preference {}, expected earlier, later or both",
+                prefer
+            ))),
+        }
+    }
+}
+
+/// The test cycles a bisection takes at most to find the first bad one of `candidates` days or
+/// builds: `ceil(log2(candidates))`.
+pub fn test_cycles(candidates: u32) -> u32 {
+    match candidates {
+        0 | 1 => 0,
+        candidates => u32::BITS - (candidates - 1).leading_zeros(),
+    }
+}
+
+/// The worst-case schedule of a regression hunt: the day or build to test, and the plans after a
+/// good and a bad outcome, down to the first bad one.
+#[derive(Debug, PartialEq)]
+pub enum Plan<T = NaiveDate> {
+    /// Nothing left to test, `till` of the remaining range is the first bad one.
+    Found(T),
+    Test {
+        candidate: T,
+        good: Box<Plan<T>>,
+        bad: Box<Plan<T>>,
+    },
+}
+
+impl<T> Plan<T> {
+    /// The test cycles needed in the worst case.
+    pub fn depth(&self) -> u32 {
+        match self {
+            Plan::Found(_) => 0,
+            Plan::Test { good, bad, .. } => 1 + good.depth().max(bad.depth()),
+        }
+    }
+}
+
+/// Plans the bisection of `from` (good) till `till` (bad) with the given bisection of a range. Of
+/// two equivalent starting points the earlier one is planned.
+pub fn plan<T: Copy>(
+    from: T,
+    till: T,
+    bisect: &impl Fn(&T, &T) -> Result<Bisection<T>, CwverError>,
+) -> Result<Plan<T>, CwverError> {
+    Ok(match bisect(&from, &till)?.preferring(Prefer::Earlier) {
+        Bisection::TooClose => Plan::Found(till),
+        Bisection::Single(candidate) | Bisection::Pair(candidate, _) => Plan::Test {
+            candidate,
+            good: Box::new(plan(candidate, till, bisect)?),
+            bad: Box::new(plan(from, candidate, bisect)?),
+        },
+    })
+}
+
+/// One of several builds of a day, e.g. `21w45.7b` for the second one, which has `index` 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Slot {
+    pub date: NaiveDate,
+    pub index: u32,
+}
+
+pub fn bisect_range(calendar: &Calendar, from: &NaiveDate, till: &NaiveDate) -> Result<Bisection, CwverError> {
+    let regression_range_in_workdays = count_workdays(calendar, from, till)?;
+
+    if regression_range_in_workdays < 2 {
+        return Ok(Bisection::TooClose);
+    }
+    if !calendar.weights.is_empty() {
+        return weighted_middle(calendar, from, till);
+    }
+
+    let (left_offset, right_offset) = (
+        regression_range_in_workdays / 2,
+        regression_range_in_workdays.div_ceil(2),
+    );
+    let left = jump_n_workdays(from, left_offset, calendar)?;
+    let right = jump_n_workdays(from, right_offset, calendar)?;
+    debug!(
+        workdays = regression_range_in_workdays,
+        left_offset,
+        right_offset,
+        %left,
+        %right,
+        "bisected {} ➔ {}",
+        from,
+        till
+    );
+    Ok(match left == right {
+        true => Bisection::Single(left),
+        false => Bisection::Pair(left, right),
+    })
+}
+
+/// The builds from `from` till `till` with `slots` builds per workday, which like
+/// [`count_workdays`] includes `till` but not `from`.
+pub fn count_slots(calendar: &Calendar, from: &Slot, till: &Slot, slots: u32) -> Result<u32, CwverError> {
+    if from > till {
+        return Err(CwverError::InvertedRange {
+            from: from.date,
+            till: till.date,
+        });
+    }
+    if let Some(slot) = [from, till].iter().find(|slot| slot.index >= slots) {
+        return Err(CwverError::OutOfRange(format!(
+            "build slot {} of {} exceeds the {} slot(s) per day",
+            slot.index + 1,
+            slot.date,
+            slots
+        )));
+    }
+    Ok(count_workdays(calendar, &from.date, &till.date)? * slots + till.index - from.index)
+}
+
+/// Like [`bisect_range`] at the granularity of builds, with `slots` builds per workday, so that
+/// the culprit build is found rather than its day. Weights of workdays don't apply.
+pub fn bisect_slots(calendar: &Calendar, from: &Slot, till: &Slot, slots: u32) -> Result<Bisection<Slot>, CwverError> {
+    let builds = count_slots(calendar, from, till, slots)?;
+    if builds < 2 {
+        return Ok(Bisection::TooClose);
+    }
+
+    let (left, right) = (
+        slot_at(calendar, from, slots, builds / 2)?,
+        slot_at(calendar, from, slots, builds.div_ceil(2))?,
+    );
+    debug!(builds, ?left, ?right, "bisected {:?} ➔ {:?}", from, till);
+    Ok(match left == right {
+        true => Bisection::Single(left),
+        false => Bisection::Pair(left, right),
+    })
+}
+
+/// `points` evenly spaced builds splitting a range into `points + 1` parts, e.g. to test them on
+/// several test rigs in parallel. Ranges with fewer builds yield fewer points, none at all if
+/// `from` and `till` are adjacent. Weights of workdays don't apply.
+pub fn split_slots(
+    calendar: &Calendar,
+    from: &Slot,
+    till: &Slot,
+    slots: u32,
+    points: u32,
+) -> Result<Vec<Slot>, CwverError> {
+    let builds = u64::from(count_slots(calendar, from, till, slots)?);
+    let parts = u64::from(points) + 1;
+    let mut offsets: Vec<u32> = (1..parts)
+        // the nearest build, the earlier one of two equally near
+        .map(|part| (2 * part * builds + parts - 1) / (2 * parts))
+        .filter(|offset| (1..builds).contains(offset))
+        .map(|offset| offset as u32)
+        .collect();
+    offsets.dedup();
+    debug!(builds, ?offsets, "split {:?} ➔ {:?} into {} parts", from, till, parts);
+    offsets
+        .into_iter()
+        .map(|offset| slot_at(calendar, from, slots, offset))
+        .collect()
+}
+
+/// The build `offset` builds after `from`.
+fn slot_at(calendar: &Calendar, from: &Slot, slots: u32, offset: u32) -> Result<Slot, CwverError> {
+    let index = from.index + offset;
+    Ok(Slot {
+        date: jump_n_workdays(&from.date, index / slots, calendar)?,
+        index: index % slots,
+    })
+}
+
+/// The working weeks from the first till the last of consecutive `weeks`, which like
+/// [`count_workdays`] includes the last week but not the first one: the weeks with workdays in
+/// between and the last week, whether it has workdays or not.
+pub fn count_weeks(calendar: &Calendar, weeks: &[DateRange]) -> u32 {
+    match weeks.len() {
+        0 | 1 => 0,
+        _ => working_weeks(calendar, weeks).len() as u32 + 1,
+    }
+}
+
+/// The weeks with at least one workday between the first and the last of `weeks`.
+pub fn working_weeks(calendar: &Calendar, weeks: &[DateRange]) -> Vec<DateRange> {
+    weeks
+        .iter()
+        .skip(1)
+        .take(weeks.len().saturating_sub(2))
+        .filter(|week| !range::workdays(calendar, week).is_empty())
+        .copied()
+        .collect()
+}
+
+/// The middle week of consecutive `weeks`, e.g. for weekly release branches. Like days, the first
+/// week is known to be good and the last one known to be bad. Weeks in between without any
+/// workday, e.g. shutdown weeks, are skipped.
+pub fn bisect_weeks(calendar: &Calendar, weeks: &[DateRange]) -> Bisection<DateRange> {
+    let working_weeks = working_weeks(calendar, weeks);
+    let jumps = count_weeks(calendar, weeks) as usize;
+    if jumps < 2 {
+        return Bisection::TooClose;
+    }
+    let (left, right) = (working_weeks[jumps / 2 - 1], working_weeks[jumps.div_ceil(2) - 1]);
+    debug!(working_weeks = working_weeks.len(), ?left, ?right, "bisected weeks");
+    match left == right {
+        true => Bisection::Single(left),
+        false => Bisection::Pair(left, right),
+    }
+}
+
+/// The workday which splits the effort of a range most evenly, or the two workdays doing so equally
+/// well. Half-day Fridays e.g. count half as much as a full day of testing.
+fn weighted_middle(calendar: &Calendar, from: &NaiveDate, till: &NaiveDate) -> Result<Bisection, CwverError> {
+    let half = effort(calendar, from, till)? / 2.0;
+    let (mut middle, mut best) = (vec![], f64::INFINITY);
+    let (mut day, mut sum) = (next_workday(calendar, from)?, 0.0);
+    while &day < till {
+        sum += calendar.weight(&day);
+        let off = (sum - half).abs();
+        if off < best - f64::EPSILON {
+            middle = vec![day];
+            best = off;
+        } else if off <= best + f64::EPSILON {
+            middle.push(day);
+        }
+        day = next_workday(calendar, &day)?;
+    }
+    debug!(effort = half * 2.0, ?middle, "bisected {} ➔ {} by effort", from, till);
+    Ok(match middle[..] {
+        [left, right, ..] => Bisection::Pair(left, right),
+        [single] => Bisection::Single(single),
+        [] => Bisection::TooClose,
+    })
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+    #[cfg(test)]
+    use chrono::naive::MAX_DATE;
+    #[cfg(test)]
+    use chrono::Datelike;
+
+    #[test]
+    fn test_bisect_range() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let max_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5, 6, 7]);
+
+        assert_eq!(
+            bisect_range(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 9)
+            ),
+            Ok(Bisection::TooClose)
+        );
+
+        assert_eq!(
+            bisect_range(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 10)
+            ),
+            Ok(Bisection::Single(NaiveDate::from_ymd(2021, 3, 9)))
+        );
+
+        assert_eq!(
+            bisect_range(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 12)
+            ),
+            Ok(Bisection::Single(NaiveDate::from_ymd(2021, 3, 10)))
+        );
+
+        assert_eq!(
+            bisect_range(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            ),
+            Ok(Bisection::Pair(
+                NaiveDate::from_ymd(2021, 3, 10),
+                NaiveDate::from_ymd(2021, 3, 11)
+            ))
+        );
+
+        assert_eq!(
+            bisect_range(
+                max_workdays,
+                &NaiveDate::from_ymd(2021, 3, 1),
+                &NaiveDate::from_ymd(2021, 3, 7)
+            ),
+            Ok(Bisection::Single(NaiveDate::from_ymd(2021, 3, 4)))
+        );
+
+        assert_eq!(
+            bisect_range(
+                max_workdays,
+                &NaiveDate::from_ymd(2021, 3, 1),
+                &NaiveDate::from_ymd(2021, 3, 8)
+            ),
+            Ok(Bisection::Pair(
+                NaiveDate::from_ymd(2021, 3, 4),
+                NaiveDate::from_ymd(2021, 3, 5)
+            ))
+        );
+
+        let all_days = &Calendar::new(hashset![1, 2, 3, 4, 5, 6, 7]);
+        assert!(bisect_range(all_days, &MAX_DATE.pred().pred(), &MAX_DATE).is_ok());
+    }
+
+    #[test]
+    fn test_bisect_weighted_range() {
+        let half_day_fridays = &Calendar {
+            weights: hashmap! {5 => 0.5},
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            bisect_range(
+                half_day_fridays,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            ),
+            Ok(Bisection::Single(NaiveDate::from_ymd(2021, 3, 10)))
+        );
+        let full_days = &Calendar {
+            weights: hashmap! {5 => 1.0},
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            bisect_range(
+                full_days,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            ),
+            Ok(Bisection::Pair(
+                NaiveDate::from_ymd(2021, 3, 10),
+                NaiveDate::from_ymd(2021, 3, 11)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bisect_slots() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let slot = |day, index| Slot {
+            date: NaiveDate::from_ymd(2021, 3, day),
+            index,
+        };
+        assert_eq!(count_slots(commercial_workdays, &slot(8, 0), &slot(12, 1), 2), Ok(9));
+        assert_eq!(
+            bisect_slots(commercial_workdays, &slot(8, 0), &slot(12, 1), 2),
+            Ok(Bisection::Pair(slot(10, 0), slot(10, 1)))
+        );
+        assert_eq!(
+            bisect_slots(commercial_workdays, &slot(8, 1), &slot(12, 1), 2),
+            Ok(Bisection::Single(slot(10, 1)))
+        );
+        assert_eq!(
+            bisect_slots(commercial_workdays, &slot(8, 0), &slot(8, 1), 2),
+            Ok(Bisection::TooClose)
+        );
+        assert_eq!(
+            bisect_slots(commercial_workdays, &slot(8, 0), &slot(15, 0), 1),
+            bisect_range(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            )
+            .map(|bisection| bisection.map(|date| slot(date.day(), 0)))
+        );
+        assert!(bisect_slots(commercial_workdays, &slot(8, 2), &slot(12, 1), 2).is_err());
+        assert!(bisect_slots(commercial_workdays, &slot(12, 1), &slot(8, 0), 2).is_err());
+    }
+
+    #[test]
+    fn test_bisect_around_blackouts() {
+        let skipped = |day| {
+            let date = NaiveDate::from_ymd(2021, 3, day);
+            (DateRange::new(date, date).unwrap(), Some("skipped".to_string()))
+        };
+        let calendar = &Calendar {
+            blackouts: vec![skipped(10), skipped(11)],
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            bisect_range(
+                calendar,
+                &NaiveDate::from_ymd(2021, 3, 8),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            ),
+            Ok(Bisection::Pair(
+                NaiveDate::from_ymd(2021, 3, 9),
+                NaiveDate::from_ymd(2021, 3, 12)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_preferring() {
+        let (left, right) = (NaiveDate::from_ymd(2021, 3, 10), NaiveDate::from_ymd(2021, 3, 11));
+        assert_eq!(
+            Bisection::Pair(left, right).preferring(Prefer::Earlier),
+            Bisection::Single(left)
+        );
+        assert_eq!(
+            Bisection::Pair(left, right).preferring(Prefer::Later),
+            Bisection::Single(right)
+        );
+        assert_eq!(
+            Bisection::Pair(left, right).preferring(Prefer::Both),
+            Bisection::Pair(left, right)
+        );
+        assert_eq!(
+            Bisection::<NaiveDate>::TooClose.preferring(Prefer::Later),
+            Bisection::TooClose
+        );
+        assert!("latest".parse::<Prefer>().is_err());
+    }
+
+    #[test]
+    fn test_split_slots() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let slot = |day, index| Slot {
+            date: NaiveDate::from_ymd(2021, 3, day),
+            index,
+        };
+        assert_eq!(
+            split_slots(commercial_workdays, &slot(1, 0), &slot(15, 0), 1, 3),
+            Ok(vec![slot(3, 0), slot(8, 0), slot(10, 0)])
+        );
+        assert_eq!(
+            split_slots(commercial_workdays, &slot(8, 0), &slot(15, 0), 1, 1),
+            Ok(vec![slot(10, 0)])
+        );
+        assert_eq!(
+            split_slots(commercial_workdays, &slot(8, 0), &slot(10, 0), 1, 3),
+            Ok(vec![slot(9, 0)])
+        );
+        assert_eq!(
+            split_slots(commercial_workdays, &slot(8, 0), &slot(9, 1), 2, 2),
+            Ok(vec![slot(8, 1), slot(9, 0)])
+        );
+        assert_eq!(
+            split_slots(commercial_workdays, &slot(8, 0), &slot(9, 0), 1, 3),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn test_bisect_weeks() {
+        let week = |day| {
+            let monday = NaiveDate::from_ymd(2024, 1, 1) + chrono::Duration::days(day);
+            DateRange::new(monday, monday + chrono::Duration::days(6)).unwrap()
+        };
+        let weeks: Vec<DateRange> = (0..6).map(|n| week(7 * n)).collect();
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        assert_eq!(
+            bisect_weeks(commercial_workdays, &weeks),
+            Bisection::Pair(week(14), week(21))
+        );
+        assert_eq!(
+            bisect_weeks(commercial_workdays, &weeks[..5]),
+            Bisection::Single(week(14))
+        );
+        assert_eq!(bisect_weeks(commercial_workdays, &weeks[..2]), Bisection::TooClose);
+        let shutdown = &Calendar {
+            blackouts: vec![(week(14), None)],
+            ..commercial_workdays.clone()
+        };
+        assert_eq!(bisect_weeks(shutdown, &weeks[..5]), Bisection::Pair(week(7), week(21)));
+    }
+
+    #[test]
+    fn test_count_weeks() {
+        let week = |day| {
+            let monday = NaiveDate::from_ymd(2024, 1, 1) + chrono::Duration::days(day);
+            DateRange::new(monday, monday + chrono::Duration::days(6)).unwrap()
+        };
+        let weeks: Vec<DateRange> = (0..4).map(|n| week(7 * n)).collect();
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        assert_eq!(count_weeks(commercial_workdays, &weeks), 3);
+        assert_eq!(count_weeks(commercial_workdays, &weeks[..2]), 1);
+        assert_eq!(count_weeks(commercial_workdays, &weeks[..1]), 0);
+        assert_eq!(count_weeks(commercial_workdays, &[]), 0);
+        let shutdown = &Calendar {
+            blackouts: vec![(week(7), None), (week(21), None)],
+            ..commercial_workdays.clone()
+        };
+        // a shutdown week in between isn't counted, the last week always is
+        assert_eq!(count_weeks(shutdown, &weeks), 2);
+    }
+
+    #[test]
+    fn test_plan() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let bisect = |from: &NaiveDate, till: &NaiveDate| bisect_range(commercial_workdays, from, till);
+        let day = |day| NaiveDate::from_ymd(2021, 3, day);
+        assert_eq!(
+            plan(day(8), day(11), &bisect),
+            Ok(Plan::Test {
+                candidate: day(9),
+                good: Box::new(Plan::Test {
+                    candidate: day(10),
+                    good: Box::new(Plan::Found(day(11))),
+                    bad: Box::new(Plan::Found(day(10))),
+                }),
+                bad: Box::new(Plan::Found(day(9))),
+            })
+        );
+        assert_eq!(plan(day(8), day(9), &bisect), Ok(Plan::Found(day(9))));
+        assert_eq!(plan(day(1), day(29), &bisect).map(|plan| plan.depth()), Ok(5));
+    }
+
+    #[test]
+    fn test_test_cycles() {
+        assert_eq!(
+            [0, 1, 2, 3, 4, 5, 7, 8, 9, 1024, 1025].map(test_cycles),
+            [0, 0, 1, 2, 2, 3, 3, 3, 4, 10, 11]
+        );
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let bisect = |from: &NaiveDate, till: &NaiveDate| bisect_range(commercial_workdays, from, till);
+        let from = NaiveDate::from_ymd(2021, 3, 1);
+        for day in 2..=31 {
+            let till = NaiveDate::from_ymd(2021, 3, day);
+            assert_eq!(
+                plan(from, till, &bisect).map(|plan| plan.depth()),
+                count_workdays(commercial_workdays, &from, &till).map(test_cycles)
+            );
+        }
+    }
+}
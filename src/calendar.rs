@@ -0,0 +1,558 @@
+use crate::error::CwverError;
+use crate::range::DateRange;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use im::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use tracing::trace;
+
+/// The days of week worked on and the holidays which are taken off nevertheless, with their
+/// names if known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Calendar {
+    pub workdays: HashSet<u32>,
+    pub holidays: HashMap<NaiveDate, Option<String>>,
+    /// Ranges in which nobody can test although it's a workday, e.g. a tester's vacation, with
+    /// their names if known.
+    pub blackouts: Vec<(DateRange, Option<String>)>,
+    /// The share of a full workday per day of week, e.g. 0.5 for half-day Fridays. Days of week
+    /// without a weight count fully.
+    pub weights: HashMap<u32, f64>,
+}
+
+/// Why a day is worked on or not.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DayKind {
+    Workday,
+    Holiday(Option<String>),
+    /// A day of week not worked on, e.g. a weekend day.
+    DayOff,
+    /// A workday within a blackout range.
+    Blackout(Option<String>),
+}
+
+impl Calendar {
+    pub fn new(workdays: HashSet<u32>) -> Calendar {
+        Calendar {
+            workdays,
+            holidays: HashMap::new(),
+            blackouts: vec![],
+            weights: HashMap::new(),
+        }
+    }
+
+    /// The share of a full workday a day counts with.
+    pub fn weight(&self, date: &NaiveDate) -> f64 {
+        self.weights
+            .get(&date.weekday().number_from_monday())
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    pub fn is_workday(&self, date: &NaiveDate) -> bool {
+        self.workdays.contains(&date.weekday().number_from_monday())
+            && !self.holidays.contains_key(date)
+            && self.blackout(date).is_none()
+    }
+
+    /// The name of the blackout range containing a date, `None` if there is none.
+    fn blackout(&self, date: &NaiveDate) -> Option<&Option<String>> {
+        self.blackouts
+            .iter()
+            .find(|(range, _)| range.from <= *date && *date <= range.till)
+            .map(|(_, name)| name)
+    }
+
+    /// Single workdays squeezed between a holiday and another day off, e.g. the Friday after
+    /// Ascension Day, along with the holiday they bridge. Sorted by date.
+    pub fn bridge_days(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        let mut bridge_days = vec![];
+        for holiday in self.holidays.keys() {
+            for step in [Duration::days(-1), Duration::days(1)] {
+                let day = holiday.checked_add_signed(step);
+                let beyond = day.and_then(|day| day.checked_add_signed(step));
+                if let (Some(day), Some(beyond)) = (day, beyond) {
+                    if self.is_workday(&day) && !self.is_workday(&beyond) {
+                        bridge_days.push((day, *holiday));
+                    }
+                }
+            }
+        }
+        bridge_days.sort();
+        // a day between two holidays bridges the earlier one
+        bridge_days.dedup_by_key(|(day, _)| *day);
+        bridge_days
+    }
+
+    /// The calendar with its bridge days taken off like holidays, e.g. when the build lab is
+    /// closed on them.
+    pub fn without_bridge_days(&self) -> Calendar {
+        let mut calendar = self.clone();
+        for (day, _) in self.bridge_days() {
+            calendar.holidays.insert(day, Some("bridge day".to_string()));
+        }
+        calendar
+    }
+
+    /// Holidays take precedence, even if they fall on a day off anyway. Blackouts only apply to
+    /// workdays.
+    pub fn day_kind(&self, date: &NaiveDate) -> DayKind {
+        match self.holidays.get(date) {
+            Some(name) => DayKind::Holiday(name.clone()),
+            None if !self.workdays.contains(&date.weekday().number_from_monday()) => DayKind::DayOff,
+            None => match self.blackout(date) {
+                Some(name) => DayKind::Blackout(name.clone()),
+                None => DayKind::Workday,
+            },
+        }
+    }
+}
+
+pub fn count_workdays(calendar: &Calendar, from: &NaiveDate, till: &NaiveDate) -> Result<u32, CwverError> {
+    if from > till {
+        return Err(CwverError::InvertedRange {
+            from: *from,
+            till: *till,
+        });
+    }
+    if from == till {
+        return Ok(0);
+    }
+
+    let mut current = *from;
+    let mut count = 1;
+    loop {
+        current = succ(&current)?;
+        if &current == till {
+            trace!(%from, %till, count, "counted workdays");
+            return Ok(count);
+        }
+        if calendar.is_workday(&current) {
+            count += 1;
+        }
+    }
+}
+
+/// The workdays from `from` till `till` like [`count_workdays`], each counting with its weight.
+pub fn effort(calendar: &Calendar, from: &NaiveDate, till: &NaiveDate) -> Result<f64, CwverError> {
+    if from > till {
+        return Err(CwverError::InvertedRange {
+            from: *from,
+            till: *till,
+        });
+    }
+
+    let (mut current, mut effort) = (*from, 0.0);
+    while &current < till {
+        current = succ(&current)?;
+        if &current == till || calendar.is_workday(&current) {
+            effort += calendar.weight(&current);
+        }
+    }
+    trace!(%from, %till, effort, "summed effort");
+    Ok(effort)
+}
+
+pub fn next_workday(calendar: &Calendar, from: &NaiveDate) -> Result<NaiveDate, CwverError> {
+    let mut next = succ(from)?;
+    loop {
+        if calendar.is_workday(&next) {
+            return Ok(next);
+        }
+        next = succ(&next)?;
+    }
+}
+
+pub fn jump_n_workdays(from: &NaiveDate, n: u32, calendar: &Calendar) -> Result<NaiveDate, CwverError> {
+    let (mut i, mut date) = (0, *from);
+    loop {
+        if i >= n {
+            break Ok(date);
+        }
+        date = next_workday(calendar, &date)?;
+        i += 1;
+    }
+}
+
+pub fn previous_workday(calendar: &Calendar, from: &NaiveDate) -> Result<NaiveDate, CwverError> {
+    let mut previous = pred(from)?;
+    loop {
+        if calendar.is_workday(&previous) {
+            return Ok(previous);
+        }
+        previous = pred(&previous)?;
+    }
+}
+
+/// A signed shift of a date, e.g. `+3d`, `-2w` or `+5wd`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Offset {
+    pub amount: i32,
+    pub unit: OffsetUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OffsetUnit {
+    Days,
+    Weeks,
+    Workdays,
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let suffix = match self.unit {
+            OffsetUnit::Days => "d",
+            OffsetUnit::Weeks => "w",
+            OffsetUnit::Workdays => "wd",
+        };
+        write!(f, "{:+}{}", self.amount, suffix)
+    }
+}
+
+/// Workdays are jumped like by `jump_n_workdays`, so that from a weekend `+1wd` is the next
+/// workday and `-1wd` the last one.
+pub fn shift(calendar: &Calendar, date: &NaiveDate, offset: &Offset) -> Result<NaiveDate, CwverError> {
+    let days = |days: i64| {
+        date.checked_add_signed(Duration::days(days))
+            .ok_or_else(|| CwverError::OutOfRange(format!("{} shifted by {} is out of range", date, offset)))
+    };
+    match offset.unit {
+        OffsetUnit::Days => days(offset.amount.into()),
+        OffsetUnit::Weeks => days(i64::from(offset.amount) * 7),
+        OffsetUnit::Workdays => {
+            let step = match offset.amount < 0 {
+                true => previous_workday,
+                false => next_workday,
+            };
+            (0..offset.amount.unsigned_abs()).try_fold(*date, |date, _| step(calendar, &date))
+        }
+    }
+}
+
+/// What distances between dates are measured in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Workdays,
+    Days,
+    Hours,
+}
+
+impl Unit {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Unit::Workdays => "wd",
+            Unit::Days => "d",
+            Unit::Hours => "h",
+        }
+    }
+}
+
+/// The distance between two dates in either order. Dates are taken at the start of the day when
+/// measured in hours, see `hours_between` for points in time. In workdays it is the number of workdays
+/// jumped to get from the earlier date to the later one, see `count_workdays`.
+pub fn distance(calendar: &Calendar, a: &NaiveDate, b: &NaiveDate, unit: Unit) -> Result<u32, CwverError> {
+    let (from, till) = (a.min(b), a.max(b));
+    match unit {
+        Unit::Workdays => count_workdays(calendar, from, till),
+        Unit::Days => u32::try_from(till.signed_duration_since(*from).num_days())
+            .map_err(|_| CwverError::OutOfRange(format!("{} and {} are too far apart", from, till))),
+        Unit::Hours => hours_between(&from.and_hms(0, 0, 0), &till.and_hms(0, 0, 0)),
+    }
+}
+
+/// The full hours between two points in time in either order.
+pub fn hours_between(a: &NaiveDateTime, b: &NaiveDateTime) -> Result<u32, CwverError> {
+    u32::try_from((*b - *a).num_hours().abs())
+        .map_err(|_| CwverError::OutOfRange(format!("{} and {} are too far apart", a, b)))
+}
+
+/// The days between two dates decomposed into full weeks and the workdays and days off of the
+/// remaining days. Like `distance`, the earlier date itself isn't counted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breakdown {
+    pub weeks: u32,
+    pub workdays: u32,
+    pub days_off: u32,
+}
+
+pub fn breakdown(calendar: &Calendar, a: &NaiveDate, b: &NaiveDate) -> Result<Breakdown, CwverError> {
+    let days = distance(calendar, a, b, Unit::Days)?;
+    let (weeks, rest) = (days / 7, days % 7);
+    let till = a.max(b);
+    let workdays = (0..rest)
+        .filter_map(|days_back| till.checked_sub_signed(Duration::days(days_back.into())))
+        .filter(|date| calendar.is_workday(date))
+        .count() as u32;
+    Ok(Breakdown {
+        weeks,
+        workdays,
+        days_off: rest - workdays,
+    })
+}
+
+fn succ(date: &NaiveDate) -> Result<NaiveDate, CwverError> {
+    date.succ_opt()
+        .ok_or_else(|| CwverError::OutOfRange(format!("{} is the last representable date", date)))
+}
+
+fn pred(date: &NaiveDate) -> Result<NaiveDate, CwverError> {
+    date.pred_opt()
+        .ok_or_else(|| CwverError::OutOfRange(format!("{} is the first representable date", date)))
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_count_workdays() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let max_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(
+            count_workdays(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 1, 4),
+                &NaiveDate::from_ymd(2021, 1, 3)
+            ),
+            Err(CwverError::InvertedRange {
+                from: NaiveDate::from_ymd(2021, 1, 4),
+                till: NaiveDate::from_ymd(2021, 1, 3)
+            })
+        );
+        assert_eq!(
+            count_workdays(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 14),
+                &NaiveDate::from_ymd(2021, 3, 14)
+            ),
+            Ok(0)
+        );
+        assert_eq!(
+            count_workdays(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2021, 3, 12),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            ),
+            Ok(1)
+        );
+        assert_eq!(
+            count_workdays(
+                max_workdays,
+                &NaiveDate::from_ymd(2021, 3, 12),
+                &NaiveDate::from_ymd(2021, 3, 15)
+            ),
+            Ok(3)
+        );
+        assert_eq!(
+            count_workdays(
+                max_workdays,
+                &NaiveDate::from_ymd(2021, 3, 11),
+                &NaiveDate::from_ymd(2021, 3, 12)
+            ),
+            Ok(1)
+        );
+
+        let with_holidays = &Calendar {
+            holidays: hashmap! {NaiveDate::from_ymd(2021, 12, 24) => None},
+            ..commercial_workdays.clone()
+        };
+        assert_eq!(
+            count_workdays(
+                with_holidays,
+                &NaiveDate::from_ymd(2021, 12, 23),
+                &NaiveDate::from_ymd(2021, 12, 28)
+            ),
+            Ok(2)
+        );
+        assert_eq!(
+            next_workday(with_holidays, &NaiveDate::from_ymd(2021, 12, 23)),
+            Ok(NaiveDate::from_ymd(2021, 12, 27))
+        );
+    }
+
+    #[test]
+    fn test_effort() {
+        let half_day_fridays = &Calendar {
+            weights: hashmap! {5 => 0.5},
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        let monday = NaiveDate::from_ymd(2021, 11, 1);
+        assert_eq!(effort(half_day_fridays, &monday, &monday), Ok(0.0));
+        assert_eq!(
+            effort(half_day_fridays, &monday, &NaiveDate::from_ymd(2021, 11, 5)),
+            Ok(3.5)
+        );
+        assert_eq!(
+            effort(half_day_fridays, &monday, &NaiveDate::from_ymd(2021, 11, 8)),
+            Ok(4.5)
+        );
+        let full_days = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        assert_eq!(
+            effort(full_days, &monday, &NaiveDate::from_ymd(2021, 11, 14)),
+            count_workdays(full_days, &monday, &NaiveDate::from_ymd(2021, 11, 14)).map(f64::from)
+        );
+        assert!(effort(half_day_fridays, &NaiveDate::from_ymd(2021, 11, 8), &monday).is_err());
+    }
+
+    #[test]
+    fn test_day_kind() {
+        let calendar = &Calendar {
+            holidays: hashmap! {
+                NaiveDate::from_ymd(2021, 12, 24) => Some("Christmas Eve".to_string()),
+                NaiveDate::from_ymd(2021, 12, 25) => None
+            },
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 24)),
+            DayKind::Holiday(Some("Christmas Eve".to_string()))
+        );
+        assert_eq!(
+            calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 25)),
+            DayKind::Holiday(None)
+        );
+        assert_eq!(calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 26)), DayKind::DayOff);
+        assert_eq!(calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 27)), DayKind::Workday);
+    }
+
+    #[test]
+    fn test_blackouts() {
+        let vacation = DateRange::new(NaiveDate::from_ymd(2021, 12, 22), NaiveDate::from_ymd(2021, 12, 28)).unwrap();
+        let calendar = &Calendar {
+            holidays: hashmap! { NaiveDate::from_ymd(2021, 12, 24) => None },
+            blackouts: vec![(vacation, Some("vacation".to_string()))],
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 23)),
+            DayKind::Blackout(Some("vacation".to_string()))
+        );
+        assert_eq!(
+            calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 24)),
+            DayKind::Holiday(None)
+        );
+        assert_eq!(calendar.day_kind(&NaiveDate::from_ymd(2021, 12, 25)), DayKind::DayOff);
+        assert_eq!(
+            next_workday(calendar, &NaiveDate::from_ymd(2021, 12, 21)),
+            Ok(NaiveDate::from_ymd(2021, 12, 29))
+        );
+        assert_eq!(
+            count_workdays(
+                calendar,
+                &NaiveDate::from_ymd(2021, 12, 20),
+                &NaiveDate::from_ymd(2021, 12, 31)
+            ),
+            Ok(4)
+        );
+    }
+
+    #[test]
+    fn test_bridge_days() {
+        let ascension = NaiveDate::from_ymd(2021, 5, 13);
+        let unity = NaiveDate::from_ymd(2021, 10, 3);
+        let all_saints = NaiveDate::from_ymd(2022, 11, 1);
+        let calendar = &Calendar {
+            holidays: hashmap! {
+                ascension => Some("Ascension Day".to_string()),
+                unity => None,
+                all_saints => None,
+                NaiveDate::from_ymd(2022, 12, 27) => None,
+                NaiveDate::from_ymd(2022, 12, 29) => None
+            },
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            calendar.bridge_days(),
+            vec![
+                (NaiveDate::from_ymd(2021, 5, 14), ascension),
+                (NaiveDate::from_ymd(2022, 10, 31), all_saints),
+                (NaiveDate::from_ymd(2022, 12, 26), NaiveDate::from_ymd(2022, 12, 27)),
+                (NaiveDate::from_ymd(2022, 12, 28), NaiveDate::from_ymd(2022, 12, 27)),
+                (NaiveDate::from_ymd(2022, 12, 30), NaiveDate::from_ymd(2022, 12, 29)),
+            ]
+        );
+        let closed = calendar.without_bridge_days();
+        assert!(!closed.is_workday(&NaiveDate::from_ymd(2021, 5, 14)));
+        assert_eq!(next_workday(&closed, &ascension), Ok(NaiveDate::from_ymd(2021, 5, 17)));
+    }
+
+    #[test]
+    fn test_distance() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let (friday, tuesday) = (NaiveDate::from_ymd(2021, 3, 12), NaiveDate::from_ymd(2021, 3, 16));
+        assert_eq!(distance(commercial_workdays, &friday, &tuesday, Unit::Workdays), Ok(2));
+        assert_eq!(distance(commercial_workdays, &tuesday, &friday, Unit::Workdays), Ok(2));
+        assert_eq!(distance(commercial_workdays, &tuesday, &friday, Unit::Days), Ok(4));
+        assert_eq!(distance(commercial_workdays, &friday, &friday, Unit::Workdays), Ok(0));
+        assert_eq!(distance(commercial_workdays, &tuesday, &friday, Unit::Hours), Ok(96));
+        assert_eq!(
+            hours_between(&tuesday.and_hms(14, 30, 0), &friday.and_hms(9, 0, 0)),
+            Ok(101)
+        );
+    }
+
+    #[test]
+    fn test_shift() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let (saturday, tuesday) = (NaiveDate::from_ymd(2021, 3, 13), NaiveDate::from_ymd(2021, 3, 16));
+        let offset = |amount, unit| Offset { amount, unit };
+        assert_eq!(
+            shift(commercial_workdays, &tuesday, &offset(3, OffsetUnit::Days)),
+            Ok(NaiveDate::from_ymd(2021, 3, 19))
+        );
+        assert_eq!(
+            shift(commercial_workdays, &tuesday, &offset(-2, OffsetUnit::Weeks)),
+            Ok(NaiveDate::from_ymd(2021, 3, 2))
+        );
+        assert_eq!(
+            shift(commercial_workdays, &tuesday, &offset(-3, OffsetUnit::Workdays)),
+            Ok(NaiveDate::from_ymd(2021, 3, 11))
+        );
+        assert_eq!(
+            shift(commercial_workdays, &saturday, &offset(1, OffsetUnit::Workdays)),
+            Ok(NaiveDate::from_ymd(2021, 3, 15))
+        );
+        assert_eq!(
+            shift(commercial_workdays, &saturday, &offset(-1, OffsetUnit::Workdays)),
+            Ok(NaiveDate::from_ymd(2021, 3, 12))
+        );
+        assert_eq!(offset(-5, OffsetUnit::Workdays).to_string(), "-5wd".to_string());
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let monday = NaiveDate::from_ymd(2021, 10, 4);
+        // 30 days, 4 weeks till monday and then tuesday and wednesday
+        assert_eq!(
+            breakdown(commercial_workdays, &NaiveDate::from_ymd(2021, 11, 3), &monday),
+            Ok(Breakdown {
+                weeks: 4,
+                workdays: 2,
+                days_off: 0
+            })
+        );
+        assert_eq!(
+            breakdown(commercial_workdays, &monday, &NaiveDate::from_ymd(2021, 10, 10)),
+            Ok(Breakdown {
+                weeks: 0,
+                workdays: 4,
+                days_off: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_date_limits() {
+        use chrono::naive::MAX_DATE;
+        let all_days = &Calendar::new(hashset![1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(
+            next_workday(all_days, &MAX_DATE),
+            Err(CwverError::OutOfRange(format!(
+                "{} is the last representable date",
+                MAX_DATE
+            )))
+        );
+        assert!(count_workdays(all_days, &MAX_DATE.pred(), &MAX_DATE).is_ok());
+    }
+}
@@ -0,0 +1,3207 @@
+use crate::bisect::{self, bisect_range, bisect_slots, bisect_weeks, count_weeks, split_slots, Prefer, Slot};
+use crate::calendar::{
+    breakdown, count_workdays, distance, effort, hours_between, next_workday, previous_workday, shift, Calendar,
+    DayKind, Offset, Unit,
+};
+use crate::clock::{self, Timezone};
+use crate::config::{self, Config};
+use crate::countries;
+use crate::csv;
+use crate::error::CwverError;
+use crate::files;
+use crate::holidays;
+use crate::locale::Locale;
+use crate::milestone;
+#[cfg(feature = "online")]
+use crate::online;
+use crate::output::{self, Deviation, Format, Issue, Style};
+use crate::parse::{
+    self, at, date_str, datetime_str, month_str, named_range_str, offset_str, period_str, range_str, tolerance_str,
+    verify, weeks_in_year, weights_str, workdays_to_hashset, year_range, year_range_str, Schema, Tolerance,
+};
+use crate::pattern::Pattern;
+use crate::range::{self, free_days, nth_workday, split, union, DateRange};
+use crate::selftest::selftest;
+use crate::set;
+use crate::template::Template;
+use crate::version::CwVersion;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::parser::{ValueSource, ValuesRef};
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, Id};
+use im::HashSet;
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn, Level};
+
+pub fn app(aliases: &[(String, String)], custom_pattern: bool) -> Command {
+    Command::new("cwver")
+        .version(crate_version!())
+        .author("Florian Bramer <elektronenhirn@gmail.com>")
+        .about("Command line tool to work with calendar week version strings (e.g. 21w45.7).")
+        .arg(
+            switch(Arg::new("strict"))
+                .help("Reject input with leading or trailing characters around the cw version string")
+                .long("strict")
+                .env("CWVER_STRICT")
+                .global(true)
+        )
+        .arg(
+            switch(Arg::new("long_year"))
+                .help("Write four-digit years like 2021w45.7 instead of 21w45.7")
+                .long("long-year")
+                .env("CWVER_LONG_YEAR")
+                .global(true)
+        )
+        .arg(
+            Arg::new("pivot")
+                .help("First of the 100 years two-digit years refer to, e.g. 1970 for 1970 till 2069 [default: 2000]")
+                .long("pivot")
+                .value_parser(number::<i32>)
+                .env("CWVER_PIVOT")
+                .global(true)
+        )
+        .arg(
+            Arg::new("pattern")
+                .help("Naming scheme of cw version strings, e.g. B{yy}W{ww}-{d} for B21W45-7 [default: {yy}w{ww}.{d}]")
+                .long("pattern")
+                .value_parser(Pattern::from_str)
+                .env("CWVER_PATTERN")
+                .global(true)
+        )
+        .arg(
+            switch(Arg::new("utc"))
+                .help("Determine today's date in UTC, short for --tz UTC")
+                .long("utc")
+                .env("CWVER_UTC")
+                .global(true)
+        )
+        .arg(
+            Arg::new("tz")
+                .help("Timezone today's date is determined in, local, UTC or a name like Europe/Berlin [default: local]")
+                .long("tz")
+                .value_parser(Timezone::parse)
+                .env("CWVER_TIMEZONE")
+                .global(true)
+        )
+        .arg(
+            switch(Arg::new("deterministic"))
+                .help("Fail instead of depending on the wall clock, locale or timezone, for reproducible output")
+                .long("deterministic")
+                .env("CWVER_DETERMINISTIC")
+                .global(true)
+        )
+        .arg(
+            Arg::new("holidays")
+                .help("Skip the holidays of the given file like weekends, with one ISO date or cw version per line, instead of the configured holiday sources")
+                .long("holidays")
+                .value_name("source")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .env("CWVER_HOLIDAYS")
+                .global(true)
+        )
+        .arg(
+            Arg::new("holiday_calendar")
+                .help("Skip the public holidays of the built-in calendar of a country or region like DE, DE-BY, GB-SCT or US, or of one fetched from the holidays_endpoint like online:DE-BY, instead of the configured holiday sources")
+                .long("holiday-calendar")
+                .value_name("code")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(|code: &str| holiday_calendar(code.trim()))
+                .env("CWVER_HOLIDAY_CALENDAR")
+                .global(true)
+        )
+        .arg(
+            Arg::new("blackout")
+                .help("Skip the ranges of the given file like holidays, e.g. a tester's vacation, with one range like 21w51.3..22w01.5, date or week per line")
+                .long("blackout")
+                .value_name("file")
+                .action(ArgAction::Append)
+                .env("CWVER_BLACKOUT")
+                .global(true)
+        )
+        .arg(
+            Arg::new("workday_weights")
+                .help("Count days of week with the given share of a full workday in diff, countdown and bisect, e.g. 5=0.5 for half-day fridays")
+                .long("workday-weights")
+                .value_name("weights")
+                .value_parser(weights_str)
+                .env("CWVER_WORKDAY_WEIGHTS")
+                .global(true)
+        )
+        .arg(
+            Arg::new("config")
+                .help("Read the settings from the given configuration file instead of the user and project configuration files")
+                .long("config")
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .env("CWVER_CONFIG")
+                .global(true)
+        )
+        .arg(
+            Arg::new("profile")
+                .help("Use the settings of the given [profile.<name>] section of the configuration files")
+                .long("profile")
+                .env("CWVER_PROFILE")
+                .global(true)
+        )
+        .arg(
+            Arg::new("output")
+                .help("Display results as prose (plain), as JSON (json) or YAML (yaml) documents, or as JSON Lines (jsonl) with one document per result of batch operations [default: plain]")
+                .long("output")
+                .value_parser(PossibleValuesParser::new(Format::NAMES.iter().copied()).try_map(|name: String| Format::parse(&name)))
+                .env("CWVER_OUTPUT")
+                .global(true)
+        )
+        .arg(
+            switch(Arg::new("quiet"))
+                .help("Print only the essential values without banners and labels, e.g. for embedding in other tools' output")
+                .short('q')
+                .long("quiet")
+                .env("CWVER_QUIET")
+                .global(true)
+        )
+        .arg(
+            Arg::new("relative_to")
+                .help("Reference date of time-relative commands instead of today, as cw version string or ISO date")
+                .long("relative-to")
+                .value_name("anchor")
+                .value_parser(date_arg(custom_pattern))
+                .env("CWVER_RELATIVE_TO")
+                .global(true)
+        )
+        .arg(
+            Arg::new("locale")
+                .help("Language of messages and dates in prose, en or de, detected from LC_ALL, LC_TIME or LANG by default")
+                .long("locale")
+                .value_parser(PossibleValuesParser::new(Locale::NAMES.iter().copied()).try_map(|name: String| Locale::parse(&name)))
+                .env("CWVER_LOCALE")
+                .global(true)
+        )
+        .arg(
+            Arg::new("verbose")
+                .help("Explain how inputs were parsed and results were calculated on stderr, repeat for more detail")
+                .short('v')
+                .long("verbose")
+                .action(ArgAction::Count)
+                .global(true)
+        )
+        .arg(
+            Arg::new("verbosity")
+                .help("Explain on stderr in the given detail from 0 to 3, like repeating --verbose as often")
+                .long("verbosity")
+                .value_name("level")
+                .value_parser(number::<u64>)
+                .env("CWVER_VERBOSE")
+                .global(true)
+        )
+        .subcommands(subcommands(custom_pattern).into_iter().map(|subcommand| {
+            let name = subcommand.get_name().to_string();
+            aliases
+                .iter()
+                .filter(|(_, target)| target == &name)
+                .fold(subcommand, |subcommand, (alias, _)| subcommand.visible_alias(alias))
+        }))
+}
+
+fn subcommands(custom_pattern: bool) -> Vec<Command> {
+    vec![
+        Command::new("today")
+            .about("Display today's date as cw version string.")
+            .arg(
+                Arg::new("offset")
+                    .help("shift today by calendar days, weeks or workdays first, e.g. +3d, -2w or +5wd")
+                    .long("offset")
+                    .allow_hyphen_values(true)
+                    .value_parser(offset_str)
+            )
+            .arg(workdays_arg())
+            .arg(template_arg()),
+        Command::new("convert")
+            .about("Convert cw version string (e.g. 21w45.7) into ISO date (2021-11-14) or vice versa.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version string or ISO date, - for one per line from stdin")
+                    .index(1)
+                    .value_parser(move |value: &str| match value {
+                        "-" => Ok(value.to_string()),
+                        _ => date_arg(custom_pattern)(value),
+                    })
+                    .required_unless_present_any(["stdin", "csv"]),
+            )
+            .arg(
+                Arg::new("stdin")
+                    .help("read one cw version string or ISO date per line from stdin")
+                    .long("stdin")
+                    .conflicts_with("data_str")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("csv")
+                    .help("CSV file with a header line to convert a column of, - for stdin")
+                    .long("csv")
+                    .conflicts_with_all(["data_str", "stdin"])
+                    .requires("column")
+            )
+            .arg(
+                Arg::new("column")
+                    .help("name of the CSV column to convert")
+                    .long("column")
+                    .requires("csv")
+            )
+            .arg(
+                Arg::new("add_column")
+                    .help("name of the CSV column to write the conversions to, added unless it exists [default: the converted column]")
+                    .long("add-column")
+                    .requires("csv")
+            )
+            .arg(template_arg()),
+        Command::new("bisect")
+            .about("Calculates the workday(s) in the middle of two given cw versions or ISO dates spanning a regression range. Saturdays and sundays are ignored. Use --workdays to override.")
+            .arg(
+                Arg::new("from")
+                    .help("left side of the regression range as cw version or ISO date, or a named range like @release-2021-4")
+                    .index(1)
+                    .required(true),
+            )
+            .arg(
+                Arg::new("till")
+                    .help("right side of the regression range as cw version or ISO date, with --slots optionally followed by the letter of a build like 21w45.7b [default: today]")
+                    .index(2)
+                    .value_parser(slot_arg(custom_pattern)),
+            )
+            .arg(
+                Arg::new("skip_bridge_days")
+                    .help("Treat bridge days, single workdays between a holiday and another day off, as days off")
+                    .long("skip-bridge-days")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("skip")
+                    .help("Skip the given comma separated dates without a usable build like git bisect skip, e.g. 24w05.3,24w05.4")
+                    .long("skip")
+                    .value_name("dates")
+                    .action(ArgAction::Append)
+                    .value_delimiter(',')
+                    .value_parser(date_arg(custom_pattern))
+            )
+            .arg(
+                Arg::new("exclude_weeks")
+                    .help("Skip the given comma separated weeks entirely, e.g. plant shutdowns like 24w52,25w01")
+                    .long("exclude-weeks")
+                    .value_name("weeks")
+                    .action(ArgAction::Append)
+                    .value_delimiter(',')
+                    .value_parser(week_syntax)
+            )
+            .arg(
+                Arg::new("prefer")
+                    .help("Which of two equivalent starting points to propose")
+                    .long("prefer")
+                    .value_parser(PossibleValuesParser::new(["earlier", "later", "both"]).try_map(|prefer: String| Prefer::from_str(&prefer)))
+                    .default_value("earlier")
+            )
+            .arg(
+                Arg::new("points")
+                    .help("Propose the given number of evenly spaced test points instead of the middle, e.g. one per test rig for testing in parallel")
+                    .long("points")
+                    .value_name("n")
+                    .value_parser(points_number)
+            )
+            .arg(
+                Arg::new("granularity")
+                    .help("Bisect whole weeks with workdays rather than days, e.g. for weekly release branches [default: day]")
+                    .long("granularity")
+                    .value_parser(["day", "week"])
+                    .conflicts_with_all(["slots", "points"])
+            )
+            .arg(
+                Arg::new("plan")
+                    .help("Print the worst-case decision tree down to the first bad day up front, testing the earlier of two equivalent days")
+                    .long("plan")
+                    .conflicts_with_all(["points", "granularity"])
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("slots")
+                    .help("Bisect builds rather than days with the given number of builds per workday, labelled a, b, ... like 21w45.7a [default: 1]")
+                    .long("slots")
+                    .value_name("n")
+                    .value_parser(slots_number)
+            )
+            .arg(workdays_arg()),
+        Command::new("compare")
+            .about("Succeeds if two cw versions or ISO dates are within the given distance of each other, e.g. in the same release window.")
+            .arg(Arg::new("a").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(Arg::new("b").index(2).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(
+                Arg::new("within")
+                    .help("maximum distance in workdays (3wd), calendar days (3d) or hours (3h)")
+                    .long("within")
+                    .value_parser(tolerance_str)
+                    .default_value("0d")
+            )
+            .arg(workdays_arg()),
+        Command::new("cmp")
+            .about("Orders two cw versions or ISO dates as lt, eq or gt. Exits with 0 if equal, 2 if the first is earlier and 3 if it is later, or with --before, --after or --same with 0 if that holds.")
+            .arg(Arg::new("a").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(Arg::new("b").index(2).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(Arg::new("before").help("succeed if a is earlier than b").long("before").action(ArgAction::SetTrue))
+            .arg(Arg::new("after").help("succeed if a is later than b").long("after").action(ArgAction::SetTrue))
+            .arg(Arg::new("same").help("succeed if a and b are the same").long("same").action(ArgAction::SetTrue))
+            .group(ArgGroup::new("expected").args(["before", "after", "same"])),
+        Command::new("timeline")
+            .about("Renders named ranges on a shared ASCII axis, e.g. to compare test campaigns and regression windows. Milestones within the axis are marked.")
+            .arg(
+                Arg::new("input")
+                    .help("file with a name and a range like 21w40.1..21w42.3 per line, - for stdin")
+                    .long("input")
+                    .required(true)
+            )
+            .arg(workdays_arg()),
+        Command::new("union")
+            .about("Merges ranges like 21w40.1..21w42.3 into the smallest set of ranges covering them, e.g. regression windows of several test suites.")
+            .arg(
+                Arg::new("ranges")
+                    .help("ranges of cw versions or ISO dates, read one per line from stdin if none are given")
+                    .index(1)
+                    .num_args(1..)
+            )
+            .arg(workdays_arg()),
+        Command::new("split")
+            .about("Divides a range like 21w40.1..21w50.5 into chunks of nearly equal workday count, e.g. to distribute retest work.")
+            .arg(Arg::new("range").help("range of cw versions or ISO dates").index(1).required(true))
+            .arg(
+                Arg::new("parts")
+                    .help("number of chunks")
+                    .long("parts")
+                    .value_parser(number::<u32>)
+                    .required(true)
+            )
+            .arg(workdays_arg()),
+        Command::new("nth-workday")
+            .about("Displays the n-th workday of a week or month, e.g. an invoicing deadline. Negative n count back from the last workday.")
+            .arg(
+                Arg::new("n")
+                    .help("1 for the first workday, -1 for the last one")
+                    .index(1)
+                    .allow_negative_numbers(true)
+                    .value_parser(number::<i32>)
+                    .required(true)
+            )
+            .arg(
+                Arg::new("of")
+                    .help("week like 21w45 or month like 2021-11")
+                    .long("of")
+                    .required(true)
+            )
+            .arg(workdays_arg()),
+        Command::new("next")
+            .about("Displays the workday following a cw version or ISO date, e.g. the build to grab when a day had none.")
+            .arg(Arg::new("date_str").help("cw version or ISO date").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(workdays_arg()),
+        Command::new("prev")
+            .about("Displays the workday preceding a cw version or ISO date.")
+            .arg(Arg::new("date_str").help("cw version or ISO date").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(workdays_arg()),
+        Command::new("add")
+            .about("Shifts a cw version or ISO date by calendar days, weeks or workdays, e.g. +5wd, -2w or +10d.")
+            .arg(Arg::new("date_str").help("cw version or ISO date").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(
+                Arg::new("offset")
+                    .help("signed amount of days (d), weeks (w) or workdays (wd)")
+                    .index(2)
+                    .required(true)
+                    .allow_hyphen_values(true)
+                    .value_parser(offset_str)
+            )
+            .arg(workdays_arg()),
+        Command::new("badge")
+            .about("Emits shields.io endpoint JSON displaying today's cw version, or the one of --relative-to, e.g. for READMEs and dashboards.")
+            .arg(
+                Arg::new("label")
+                    .help("text on the left side of the badge")
+                    .long("label")
+                    .default_value("cw version")
+            )
+            .arg(
+                Arg::new("color")
+                    .help("color of the right side of the badge, a shields.io color name or hex code")
+                    .long("color")
+                    .default_value("blue")
+            ),
+        Command::new("countdown")
+            .about("Displays the workdays, days and hours left till a cw version, ISO date or milestone, e.g. on a war-room screen with --watch.")
+            .arg(Arg::new("target").help("cw version, ISO date or milestone name").index(1).required(true))
+            .arg(
+                Arg::new("watch")
+                    .help("Stay open and refresh the countdown")
+                    .long("watch")
+                    .conflicts_with("relative_to")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("interval")
+                    .help("seconds between refreshes with --watch [default: 60]")
+                    .long("interval")
+                    .value_parser(number::<u64>)
+                    .requires("watch")
+            )
+            .arg(workdays_arg()),
+        Command::new("seq")
+            .about("Lists the cw versions from one cw version or ISO date till another, every day or every --step, one per line.")
+            .arg(Arg::new("from").help("first cw version or ISO date").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(Arg::new("till").help("last cw version or ISO date, a week-only version includes the whole week").index(2).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(
+                Arg::new("step")
+                    .help("distance in days (1d), weeks (1w) or workdays (1wd)")
+                    .long("step")
+                    .value_parser(offset_str)
+                    .default_value("1d")
+            )
+            .arg(workdays_arg())
+            .arg(template_arg()),
+        Command::new("age")
+            .about("Displays the calendar days and workdays passed since a build, failing if it is older than --max-workdays, e.g. to reject stale baselines in CI.")
+            .arg(Arg::new("date_str").help("cw version or ISO date of the build").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(
+                Arg::new("max_workdays")
+                    .help("fail if more workdays than this have passed")
+                    .long("max-workdays")
+                    .value_parser(number::<i64>)
+            )
+            .arg(workdays_arg()),
+        Command::new("diff")
+            .about("Displays the calendar days and workdays between two cw versions or ISO dates.")
+            .arg(Arg::new("a").help("cw version or ISO date").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(Arg::new("b").help("cw version or ISO date").index(2).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(
+                Arg::new("breakdown")
+                    .help("Decompose the days into full weeks and the workdays and days off of the remaining days")
+                    .long("breakdown")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(workdays_arg()),
+        Command::new("free-days")
+            .about("Lists the days off and holidays within a range like 21w50.1..22w02.5, e.g. to see how many candidate builds a regression range can contain.")
+            .arg(Arg::new("range").help("range of cw versions or ISO dates").index(1).required(true))
+            .arg(workdays_arg()),
+        Command::new("bridge-days")
+            .about("Lists the bridge days of a year, single workdays squeezed between a holiday and another day off like the Friday after Ascension Day, which the holiday sources make likely to be taken off.")
+            .arg(Arg::new("year").help("four-digit year [default: the year of --relative-to or today]").index(1).value_parser(number::<i32>))
+            .arg(workdays_arg()),
+        Command::new("is-holiday")
+            .about("Succeeds if a cw version or ISO date is a holiday of the configured holiday sources, and tells its name if known.")
+            .arg(Arg::new("date_str").help("cw version or ISO date").index(1).required(true).value_parser(date_arg(custom_pattern)))
+            .arg(workdays_arg()),
+        Command::new("milestone")
+            .about("Lists the milestones defined in the [milestones] tables of the configuration files, or counts the workdays till one of them.")
+            .subcommand_required(true)
+            .arg_required_else_help(true)
+            .subcommand(Command::new("list").about("Display all milestones by date."))
+            .subcommand(
+                Command::new("until")
+                    .about("Display the workdays left till a milestone, counted from --relative-to or today.")
+                    .arg(Arg::new("name").index(1).required(true))
+                    .arg(workdays_arg())
+            ),
+        Command::new("month-edge")
+            .about("Displays the first or last workday of a month, e.g. the day a month-end release cut has to land on.")
+            .arg(Arg::new("month").help("month like 2021-11").index(1).required(true).value_parser(month_str))
+            .arg(
+                Arg::new("first_workday")
+                    .help("Display the first workday of the month")
+                    .long("first-workday")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("last_workday")
+                    .help("Display the last workday of the month")
+                    .long("last-workday")
+                    .action(ArgAction::SetTrue)
+            )
+            .group(ArgGroup::new("edge").args(["first_workday", "last_workday"]).required(true))
+            .arg(workdays_arg()),
+        Command::new("remaining")
+            .about("Displays the weeks and workdays left of a week-based year, counted from --relative-to or today.")
+            .arg(
+                Arg::new("in")
+                    .help("year like 2021 [default: the current one]")
+                    .long("in")
+                    .value_parser(number::<i32>)
+            )
+            .arg(workdays_arg()),
+        Command::new("sort")
+            .about("Sorts cw versions or ISO dates chronologically and reprints them, unlike a lexicographic sort also across year boundaries.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings or ISO dates [default: one per line from stdin]")
+                    .index(1)
+                    .num_args(1..)
+            )
+            .arg(Arg::new("reverse").help("sort the latest first").long("reverse").short('r').action(ArgAction::SetTrue))
+            .arg(Arg::new("unique").help("keep only the first of inputs referring to the same date").long("unique").short('u').action(ArgAction::SetTrue)),
+        Command::new("latest")
+            .about("Prints the chronologically latest of cw versions or ISO dates, e.g. to pick the most recent nightly from a directory listing.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings or ISO dates [default: one per line from stdin]")
+                    .index(1)
+                    .num_args(1..)
+            ),
+        Command::new("earliest")
+            .about("Prints the chronologically earliest of cw versions or ISO dates.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings or ISO dates [default: one per line from stdin]")
+                    .index(1)
+                    .num_args(1..)
+            ),
+        Command::new("dedup")
+            .about("Normalizes cw versions or ISO dates and drops those referring to the same date as an earlier one, reporting which inputs were merged.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings like 24w07.1, 24w7.1 or 24W07-1, or ISO dates [default: one per line from stdin]")
+                    .index(1)
+                    .num_args(1..)
+            ),
+        Command::new("set")
+            .about("Combines two lists of cw versions or ISO dates, e.g. to find the nightly builds tested by one suite but not by another.")
+            .subcommand_required(true)
+            .arg_required_else_help(true)
+            .subcommands(vec![
+                ("diff", "Lists the versions of list_a missing in list_b."),
+                ("intersect", "Lists the versions contained in both lists."),
+                ("union", "Lists the versions contained in either list."),
+            ]
+            .into_iter()
+            .map(|(name, about)| {
+                Command::new(name)
+                    .about(about)
+                    .arg(Arg::new("list_a").help("file with one version per line, - for stdin").index(1).required(true))
+                    .arg(Arg::new("list_b").help("file with one version per line, - for stdin").index(2).required(true))
+            })),
+        Command::new("selftest")
+            .about("Round-trips every date of the given years through format ➔ parse ➔ format and checks the workday arithmetic for consistency.")
+            .arg(
+                Arg::new("years")
+                    .help("ISO week-based year or range of years, e.g. 2021 or 2000..2099 [default: the 100 years of the pivot]")
+                    .long("years")
+                    .value_parser(year_range_str)
+            )
+            .arg(workdays_arg()),
+        Command::new("verify")
+            .about("Checks that every cw version string parses and round-trips to the identical canonical string, reporting each deviation.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings")
+                    .index(1)
+                    .num_args(1..)
+                    .required_unless_present("stdin"),
+            )
+            .arg(
+                Arg::new("stdin")
+                    .help("read one cw version string per line from stdin")
+                    .long("stdin")
+                    .conflicts_with("data_str")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("suggest")
+                    .help("Pair each deviating input with the most likely intended valid cw version")
+                    .long("suggest")
+                    .action(ArgAction::SetTrue)
+            ),
+        Command::new("grep")
+            .about("Finds the cw version strings in a text like a log file or changelog and prints each with its line number and ISO date.")
+            .arg(
+                Arg::new("file")
+                    .help("file to scan, - for stdin")
+                    .index(1)
+                    .default_value("-")
+            ),
+        Command::new("annotate")
+            .about("Appends the ISO date to each cw version string in a text like release notes, or replaces it with --replace.")
+            .arg(
+                Arg::new("file")
+                    .help("file to annotate, - for stdin")
+                    .index(1)
+                    .default_value("-")
+            )
+            .arg(
+                Arg::new("inline")
+                    .help("append the date like 21w45.7 (2021-11-14) [default]")
+                    .long("inline")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("replace")
+                    .help("replace the cw version string with the date")
+                    .long("replace")
+                    .conflicts_with("inline")
+                    .action(ArgAction::SetTrue)
+            ),
+        Command::new("scan")
+            .about("Lists the files below a directory whose names contain cw versions sorted by date, with the oldest and newest one and the workdays without any.")
+            .arg(Arg::new("dir").help("directory of build artifacts").index(1).required(true))
+            .arg(
+                Arg::new("glob")
+                    .help("only files whose name matches a glob like *.tar.gz")
+                    .long("glob")
+                    .default_value("*")
+            )
+            .arg(workdays_arg()),
+        Command::new("rename")
+            .about("Renames the files below a directory by rewriting the cw versions in their names into ISO dates, or vice versa, e.g. to migrate an artifact naming convention.")
+            .arg(Arg::new("dir").help("directory of files to rename").index(1).required(true))
+            .arg(
+                Arg::new("from_cwver")
+                    .help("rewrite cw versions into ISO dates, same as --to-iso")
+                    .long("from-cwver")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("to_iso")
+                    .help("rewrite cw versions into ISO dates")
+                    .long("to-iso")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(
+                Arg::new("from_iso")
+                    .help("rewrite ISO dates into cw versions, same as --to-cwver")
+                    .long("from-iso")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(Arg::new("to_cwver").help("rewrite ISO dates into cw versions").long("to-cwver").action(ArgAction::SetTrue))
+            .group(
+                ArgGroup::new("direction")
+                    .args(["from_cwver", "to_iso", "from_iso", "to_cwver"])
+                    .multiple(true)
+                    .required(true)
+            )
+            .arg(
+                Arg::new("dry_run")
+                    .help("only print what would be renamed")
+                    .long("dry-run")
+                    .action(ArgAction::SetTrue)
+            ),
+        Command::new("lint")
+            .about("Flags malformed or impossible cw versions in the names and content of the files below a directory, e.g. to gate CI on them. Fails if there are any.")
+            .arg(Arg::new("dir").help("directory to lint").index(1).default_value("."))
+            .arg(
+                Arg::new("ignore")
+                    .help("skip files and directories whose name matches a glob, on top of .gitignore and .cwverignore")
+                    .long("ignore")
+                    .action(ArgAction::Append)
+            ),
+        Command::new("fmt")
+            .about("Rewrites loosely written cw versions like 24w7.1, 24W07-1 or 2024w07.1 to the canonical 24w07.1, keeping the text around them.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings [default: filter stdin line by line]")
+                    .index(1)
+                    .num_args(1..)
+            ),
+        Command::new("validate")
+            .about("Explains precisely why cw version strings are invalid, e.g. that their week doesn't exist in their year. With --strict weeks must be zero-padded.")
+            .arg(
+                Arg::new("data_str")
+                    .help("cw version strings [default: one per line from stdin]")
+                    .index(1)
+                    .num_args(1..)
+            ),
+        Command::new("week53")
+            .about("Lists the years with 53 ISO weeks, e.g. to check a week-based numbering scheme survives leap weeks.")
+            .arg(
+                Arg::new("years")
+                    .help("year (2021) or inclusive range of years (2000..2099) [default: the 100 years of the pivot]")
+                    .index(1)
+                    .value_parser(year_range_str)
+            )
+            .arg(
+                Arg::new("stats")
+                    .help("Summarize the intervals between the years with 53 weeks as well")
+                    .long("stats")
+                    .action(ArgAction::SetTrue)
+            ),
+        Command::new("week")
+            .about("Lists the days of a week like 24w07 with their cw versions, e.g. to see which daily builds belong to a sprint week.")
+            .arg(Arg::new("week").help("week-only version like 24w07").index(1).required(true).value_parser(week_syntax))
+            .arg(
+                Arg::new("workdays_only")
+                    .help("list the workdays only")
+                    .long("workdays-only")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(workdays_arg()),
+        Command::new("week-bounds")
+            .about("Displays the Monday and Sunday of a week like 24w07 as ISO dates, e.g. for scripts generating weekly reports.")
+            .arg(Arg::new("week").help("week-only version like 24w07").index(1).required(true).value_parser(week_syntax))
+            .arg(
+                Arg::new("with_workdays")
+                    .help("display the first and last workday of the week as well")
+                    .long("with-workdays")
+                    .action(ArgAction::SetTrue)
+            )
+            .arg(workdays_arg()),
+        Command::new("workdays-in")
+            .about("Counts the workdays within a range like 21w40.1..21w48.5 or a named range like @release-2021-4.")
+            .arg(Arg::new("range").help("range of cw versions or ISO dates, or a named range").index(1).required(true))
+            .arg(workdays_arg()),
+        Command::new("config")
+            .about("Inspect and change the configuration.")
+            .subcommand_required(true)
+            .arg_required_else_help(true)
+            .infer_subcommands(true)
+            .subcommand(
+                Command::new("get")
+                    .about("Display the effective value of a configuration key.")
+                    .arg(Arg::new("key").index(1).required(true))
+            )
+            .subcommand(
+                Command::new("set")
+                    .about("Set a configuration key in the user configuration file.")
+                    .arg(Arg::new("key").index(1).required(true))
+                    .arg(Arg::new("value").help("TOML value, plain strings don't need to be quoted").index(2).required(true))
+                    .arg(
+                        Arg::new("project")
+                            .help("Change the project configuration file instead")
+                            .long("project")
+                            .action(ArgAction::SetTrue)
+                    )
+            )
+            .subcommand(
+                Command::new("list")
+                    .about("Display all settings of all configuration files.")
+                    .arg(
+                        Arg::new("effective")
+                            .help("Display the effective value of every configuration key and where it comes from instead")
+                            .long("effective")
+                            .action(ArgAction::SetTrue)
+                    )
+            )
+            .subcommand(
+                Command::new("path")
+                    .about("Display the locations of the configuration files and the cache.")
+            ),
+    ]
+}
+
+fn template_arg() -> Arg {
+    Arg::new("format")
+        .help("print each date as the given template instead, e.g. \"{yy}w{ww}.{d} built on {iso} ({weekday})\", placeholders are {cwver}, {yy}, {yyyy}, {ww}, {d}, {weekday}, {iso} and {epoch}")
+        .long("format")
+        .value_parser(Template::parse)
+}
+
+fn workdays_arg() -> Arg {
+    Arg::new("workdays")
+        .help("workdays as comma separated days of week, 1 = monday till 7 = sunday [default: 1,2,3,4,5]")
+        .short('w')
+        .long("workdays")
+        .value_parser(workdays_to_hashset)
+        .env("CWVER_WORKDAYS")
+        .required(false)
+}
+
+/// A boolean global flag like `--strict`, which is true if given bare. It takes an optional value
+/// like `--strict=false` to turn off its `CWVER_*` variable, which has to be attached with `=` to
+/// not be mistaken for the subcommand.
+fn switch(arg: Arg) -> Arg {
+    arg.value_name("bool")
+        .num_args(0..=1)
+        .require_equals(true)
+        .default_missing_value("true")
+        .value_parser(boolean)
+}
+
+/// Typed access to the arguments clap has parsed, as the types their value parsers produce.
+trait Arguments {
+    /// The value of an argument clap requires or has a default for.
+    fn required<T: Any + Clone + Send + Sync>(&self, name: &str) -> Result<&T, String>;
+
+    /// The value of an optional argument.
+    fn optional<T: Any + Clone + Send + Sync>(&self, name: &str) -> Result<Option<&T>, String>;
+
+    /// The values of an argument taking several, `None` if there are none.
+    fn values<T: Any + Clone + Send + Sync>(&self, name: &str) -> Result<Option<ValuesRef<'_, T>>, String>;
+
+    /// Whether a flag or a [`switch`] is on.
+    fn flag(&self, name: &str) -> Result<bool, String>;
+}
+
+impl Arguments for ArgMatches {
+    fn required<T: Any + Clone + Send + Sync>(&self, name: &str) -> Result<&T, String> {
+        self.optional(name)?
+            .ok_or_else(|| format!("{} is neither required nor has a default", name))
+    }
+
+    fn optional<T: Any + Clone + Send + Sync>(&self, name: &str) -> Result<Option<&T>, String> {
+        self.try_get_one(name).map_err(|e| format!("argument {}: {}", name, e))
+    }
+
+    fn values<T: Any + Clone + Send + Sync>(&self, name: &str) -> Result<Option<ValuesRef<'_, T>>, String> {
+        self.try_get_many(name).map_err(|e| format!("argument {}: {}", name, e))
+    }
+
+    fn flag(&self, name: &str) -> Result<bool, String> {
+        Ok(self.optional::<bool>(name)?.copied().unwrap_or(false))
+    }
+}
+
+/// Whether an argument is given on the command line rather than taken from its `CWVER_*` variable
+/// or its default.
+fn on_command_line(matches: &ArgMatches, name: &str) -> bool {
+    matches.value_source(name) == Some(ValueSource::CommandLine)
+}
+
+fn number<T: FromStr>(number: &str) -> Result<T, String> {
+    number.parse().map_err(|_| format!("{} is not a valid number", number))
+}
+
+/// The builds per day to bisect, as many as there are letters to label them with.
+fn slots_number(slots: &str) -> Result<u32, String> {
+    match number(slots)? {
+        slots @ 1..=26 => Ok(slots),
+        _ => Err(format!("{} slots per day are not supported, expected 1 to 26", slots)),
+    }
+}
+
+fn points_number(points: &str) -> Result<u32, String> {
+    match number(points)? {
+        0 => Err("bisect requires at least 1 test point".to_string()),
+        points => Ok(points),
+    }
+}
+
+/// Like `date_syntax`, optionally followed by the letter of a build slot like `21w45.7b`.
+fn slot_syntax(slot_str: &str) -> Result<(), CwverError> {
+    date_syntax(parse::split_slot(slot_str).0)
+}
+
+/// A cw version or ISO date with an optional time of day. Only the syntax is checked, whether the
+/// week exists depends on the schema of the configuration files.
+fn date_syntax(date_str: &str) -> Result<(), CwverError> {
+    let schema = Schema::default();
+    match date_str.contains('w') {
+        true if parse::is_week(date_str) => Ok(()),
+        true => CwVersion::parse(date_str, &schema).map(|_| ()),
+        false => datetime_str(date_str, &schema).map(|_| ()),
+    }
+}
+
+fn week_syntax(week: &str) -> Result<String, String> {
+    match parse::is_week(week) {
+        true => Ok(week.to_string()),
+        false => Err(format!("{} is not a week like 24w07", week)),
+    }
+}
+
+/// Dates stay strings to be parsed with the schema, which is only known once the arguments are
+/// parsed. Versions in a custom pattern can't even be checked before.
+fn date_arg(custom_pattern: bool) -> impl Fn(&str) -> Result<String, CwverError> + Clone + Send + Sync + 'static {
+    move |value: &str| match custom_pattern {
+        true => Ok(value.to_string()),
+        false => date_syntax(value).map(|_| value.to_string()),
+    }
+}
+
+fn slot_arg(custom_pattern: bool) -> impl Fn(&str) -> Result<String, CwverError> + Clone + Send + Sync + 'static {
+    move |value: &str| match custom_pattern {
+        true => Ok(value.to_string()),
+        false => slot_syntax(value).map(|_| value.to_string()),
+    }
+}
+
+/// Whether a `--pattern` may be in effect, given on the command line, in the environment or in
+/// any configuration file or profile.
+pub fn custom_pattern(args: &[OsString], config: &Config) -> bool {
+    args.iter().any(|arg| arg.to_string_lossy().starts_with("--pattern"))
+        || env::var_os(env_var("pattern")).is_some()
+        || config
+            .settings()
+            .iter()
+            .any(|(name, ..)| name == "pattern" || (name.starts_with("profile.") && name.ends_with(".pattern")))
+}
+
+/// The configuration file given with `--config`, which has to be known before the command line can
+/// be parsed with the aliases of the configuration.
+pub fn config_path(args: &[OsString]) -> Option<PathBuf> {
+    let given = args.iter().enumerate().find_map(|(index, arg)| {
+        let arg = arg.to_string_lossy();
+        match arg.strip_prefix("--config") {
+            Some("") => args.get(index + 1).cloned(),
+            Some(value) => value.strip_prefix('=').map(OsString::from),
+            None => None,
+        }
+    });
+    given.or_else(|| env::var_os("CWVER_CONFIG")).map(PathBuf::from)
+}
+
+/// Aliases which are always available, the `[aliases]` table of the configuration files adds more.
+const BUILTIN_ALIASES: &[(&str, &str)] = &[("b", "bisect"), ("c", "convert"), ("t", "today")];
+
+/// Global options which take a value, their value must not be mistaken for the subcommand.
+const GLOBAL_OPTIONS_WITH_VALUE: &[&str] = &[
+    "--blackout",
+    "--config",
+    "--holiday-calendar",
+    "--holidays",
+    "--locale",
+    "--output",
+    "--pattern",
+    "--pivot",
+    "--profile",
+    "--relative-to",
+    "--tz",
+    "--verbosity",
+    "--workday-weights",
+];
+
+/// The built-in aliases followed by the ones of the configuration files, which may override them.
+pub fn aliases(config: &Config) -> Result<Vec<(String, String)>, String> {
+    let names = subcommand_names();
+    let mut aliases: Vec<(String, String)> = BUILTIN_ALIASES
+        .iter()
+        .map(|(alias, subcommand)| (alias.to_string(), subcommand.to_string()))
+        .collect();
+    for (alias, subcommand) in config.aliases() {
+        if names.contains(&alias) {
+            return Err(format!("alias {} shadows the subcommand of the same name", alias));
+        }
+        if !names.contains(&subcommand) {
+            return Err(format!("alias {} refers to unknown subcommand {}", alias, subcommand));
+        }
+        aliases.retain(|(defined, _)| defined != &alias);
+        aliases.push((alias, subcommand));
+    }
+    Ok(aliases)
+}
+
+fn subcommand_names() -> Vec<String> {
+    subcommands(false)
+        .iter()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .chain(Some("help".to_string()))
+        .collect()
+}
+
+/// `--version` is handled by clap before the other arguments are looked at, so its verbose form
+/// is detected on the raw arguments.
+pub fn verbose_version_requested(args: &[OsString]) -> bool {
+    let args: Vec<String> = args
+        .iter()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().to_string())
+        .take_while(|arg| arg != "--")
+        .collect();
+    let verbose = args
+        .iter()
+        .any(|arg| arg == "--verbose" || (arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v')));
+    verbose && args.iter().any(|arg| arg == "--version" || arg == "-V")
+}
+
+/// Replaces an alias or an unambiguous abbreviation of a subcommand in `args` by the name of
+/// the subcommand. This is done up front rather than by clap, so that an ambiguous abbreviation
+/// is reported along with the subcommands it could stand for.
+pub fn resolve_subcommand(mut args: Vec<OsString>, aliases: &[(String, String)]) -> Result<Vec<OsString>, String> {
+    let mut index = 1;
+    while let Some(arg) = args.get(index).and_then(|arg| arg.to_str()) {
+        if arg == "--" {
+            return Ok(args);
+        }
+        if !arg.starts_with('-') {
+            break;
+        }
+        index += match GLOBAL_OPTIONS_WITH_VALUE.contains(&arg) {
+            true => 2,
+            false => 1,
+        };
+    }
+    let arg = match args.get(index).and_then(|arg| arg.to_str()) {
+        Some(arg) => arg.to_string(),
+        None => return Ok(args),
+    };
+    let names = subcommand_names();
+    if names.contains(&arg) {
+        return Ok(args);
+    }
+    if let Some((_, subcommand)) = aliases.iter().find(|(alias, _)| alias == &arg) {
+        args[index] = subcommand.into();
+        return Ok(args);
+    }
+    let mut candidates: Vec<&String> = names
+        .iter()
+        .filter(|name| name.starts_with(&arg))
+        .chain(
+            aliases
+                .iter()
+                .filter(|(alias, _)| alias.starts_with(&arg))
+                .map(|(_, subcommand)| subcommand),
+        )
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    match candidates.as_slice() {
+        [] => Ok(args),
+        [subcommand] => {
+            args[index] = subcommand.into();
+            Ok(args)
+        }
+        _ => Err(format!(
+            "ambiguous subcommand {}, could be {}",
+            arg,
+            candidates
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn env_var(name: &str) -> String {
+    format!("CWVER_{}", name.to_uppercase().replace('-', "_"))
+}
+
+fn truthy(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "" | "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// The value of a [`switch`], given on the command line or in its `CWVER_*` variable.
+fn boolean(value: &str) -> Result<bool, String> {
+    truthy(value).ok_or_else(|| format!("{} is neither true nor false", value))
+}
+
+/// Appends the configured `default` command to `args`, which don't contain a subcommand.
+pub fn with_default_command(
+    mut args: Vec<OsString>,
+    config: &Config,
+    aliases: &[(String, String)],
+) -> Result<Vec<OsString>, String> {
+    let command = config.default_command()?;
+    info!(?command, "running the default command");
+    args.extend(command.into_iter().map(OsString::from));
+    resolve_subcommand(args, aliases)
+}
+
+/// Parses `args`, printing the help, the version or the usage error clap stops at instead. Usage
+/// errors exit with 1 like every other failure.
+pub fn parse(aliases: &[(String, String)], custom_pattern: bool, args: Vec<OsString>) -> Result<ArgMatches, ExitCode> {
+    app(aliases, custom_pattern)
+        .try_get_matches_from(args)
+        .map_err(|error| {
+            // like clap's own exit, which leaves a failure to print the error unreported as well
+            let _ = error.print();
+            match error.use_stderr() {
+                true => ExitCode::FAILURE,
+                false => ExitCode::SUCCESS,
+            }
+        })
+}
+
+/// The `--profile` to select the settings of.
+pub fn profile(matches: &ArgMatches) -> Result<Option<&str>, String> {
+    Ok(matches.optional::<String>("profile")?.map(String::as_str))
+}
+
+pub fn init_tracing(matches: &ArgMatches) -> Result<(), String> {
+    let verbosity = match (
+        matches.optional::<u8>("verbose")?.copied(),
+        matches.optional::<u64>("verbosity")?,
+    ) {
+        (None | Some(0), Some(level)) => *level,
+        (occurrences, _) => occurrences.unwrap_or(0).into(),
+    };
+    let level = match verbosity {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(io::stderr)
+        .without_time()
+        .with_target(false)
+        .init();
+    Ok(())
+}
+
+/// Runs the subcommand, writing its results to `out`. Failures are errors, except for the
+/// orderings of `cmp`, which have exit statuses of their own.
+pub fn run(matches: &ArgMatches, config: &Config, out: &mut impl Write) -> Result<ExitCode, String> {
+    let schema = schema(matches, config)?;
+    let deterministic = matches.flag("deterministic")?;
+    let style = Style {
+        format: match matches.optional::<Format>("output")? {
+            Some(format) => *format,
+            None => config.format()?.unwrap_or_default(),
+        },
+        quiet: match matches.optional::<bool>("quiet")? {
+            Some(quiet) => *quiet,
+            None => config.quiet()?.unwrap_or(false),
+        },
+        locale: locale(matches, config, deterministic)?,
+        schema: schema.clone(),
+        template: None,
+    };
+
+    match matches.subcommand() {
+        Some(("today", today_matches)) => {
+            let style = with_template(today_matches, style)?;
+            let mut date = anchor(matches, config, &schema, deterministic)?;
+            if let Some(offset) = today_matches.optional::<Offset>("offset")? {
+                let years = shifted_years(today_matches, config, &date, offset)?;
+                date = shift(&calendar(today_matches, config, years)?, &date, offset)?;
+                info!(%date, %offset, "shifted today");
+            }
+            print(out, output::today(&date, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("convert", matches)) => {
+            let style = with_template(matches, style)?;
+            if let Some(path) = matches.optional::<String>("csv")? {
+                let column = matches.required::<String>("column")?;
+                let target = matches.optional::<String>("add_column")?.unwrap_or(column);
+                let (records, errors) = convert_csv(&read_text(path)?, column, target, &style)?;
+                for record in records {
+                    print(out, csv::record(&record))?;
+                }
+                for error in &errors {
+                    eprintln!("{}", error);
+                }
+                return match errors.len() {
+                    0 => Ok(ExitCode::SUCCESS),
+                    n => Err(format!("{} value(s) of column {} failed to convert", n, column)),
+                };
+            }
+            if matches.flag("stdin")? || matches.optional::<String>("data_str")?.map(String::as_str) == Some("-") {
+                convert_lines(io::stdin().lock(), &mut *out, io::stderr().lock(), &style)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            print(out, convert(matches.required::<String>("data_str")?, &style)?)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("bisect", matches)) => {
+            let slots = matches.optional::<u32>("slots")?.copied().unwrap_or(1);
+            let (from, till) = match (matches.required::<String>("from")?, matches.optional::<String>("till")?) {
+                (from_str, Some(till_str)) => (
+                    bisect_endpoint(from_str, false, slots, &schema)?,
+                    bisect_endpoint(till_str, true, slots, &schema)?,
+                ),
+                (range_str, None) if range_str.starts_with('@') => {
+                    let range = named_range(range_str, config, &schema)?;
+                    (
+                        (DateRange::new(range.from, range.from)?, 0),
+                        (DateRange::new(range.till, range.till)?, slots - 1),
+                    )
+                }
+                (from_str, None) => {
+                    let anchor = anchor(matches, config, &schema, deterministic)?;
+                    (
+                        bisect_endpoint(from_str, false, slots, &schema)?,
+                        (DateRange::new(anchor, anchor)?, slots - 1),
+                    )
+                }
+            };
+            let years = years_around(&[from.0.from, till.0.till]);
+            let mut calendar = match matches.flag("skip_bridge_days")? {
+                true => calendar(matches, config, years)?.without_bridge_days(),
+                false => calendar(matches, config, years)?,
+            };
+            for skipped in matches.values::<String>("skip")?.into_iter().flatten() {
+                let date = date_str(skipped, &schema).map_err(|e| format!("--skip {}: {}", skipped, e))?;
+                calendar
+                    .blackouts
+                    .push((DateRange::new(date, date)?, Some("skipped".to_string())));
+            }
+            for excluded in matches.values::<String>("exclude_weeks")?.into_iter().flatten() {
+                let week =
+                    parse::week_str(excluded, &schema).map_err(|e| format!("--exclude-weeks {}: {}", excluded, e))?;
+                calendar.blackouts.push((week, Some("excluded week".to_string())));
+            }
+            let (from, till) = (bisect_slot(&calendar, from, false), bisect_slot(&calendar, till, true));
+            let prefer = *matches.required::<Prefer>("prefer")?;
+            if matches.optional::<String>("granularity")?.map(String::as_str) == Some("week") {
+                let weeks = parse::weeks_between(&from.date, &till.date, &schema)?;
+                let middle_of_range = bisect_weeks(&calendar, &weeks).preferring(prefer);
+                let working_weeks = count_weeks(&calendar, &weeks);
+                let bisection = output::bisect_weeks(
+                    &weeks[0],
+                    &weeks[weeks.len() - 1],
+                    working_weeks,
+                    &middle_of_range,
+                    &style,
+                );
+                print_unless_empty(out, bisection)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let regression_range_in_workdays = count_workdays(&calendar, &from.date, &till.date)?;
+            if let Some(points) = matches.optional::<u32>("points")? {
+                let points = split_slots(&calendar, &from, &till, slots, *points)?;
+                let points = output::split_points(&from, &till, regression_range_in_workdays, &points, slots, &style);
+                print_unless_empty(out, points)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let bisect_slots = |from: &Slot, till: &Slot| match slots {
+                1 => bisect_range(&calendar, &from.date, &till.date)
+                    .map(|bisection| bisection.map(|date| Slot { date, index: 0 })),
+                _ => bisect_slots(&calendar, from, till, slots),
+            };
+            if matches.flag("plan")? {
+                let plan = bisect::plan(from, till, &bisect_slots)?;
+                print(
+                    out,
+                    output::plan(&from, &till, regression_range_in_workdays, &plan, slots, &style),
+                )?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let middle_of_range = bisect_slots(&from, &till)?.preferring(prefer);
+            let bisection = output::bisect(
+                &from,
+                &till,
+                regression_range_in_workdays,
+                &middle_of_range,
+                slots,
+                &style,
+            );
+            print_unless_empty(out, bisection)?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("compare", matches)) => {
+            let tolerance = *matches.required::<Tolerance>("within")?;
+            let (a_str, b_str) = (matches.required::<String>("a")?, matches.required::<String>("b")?);
+            let ((a, a_time), (b, b_time)) = (datetime_str(a_str, &schema)?, datetime_str(b_str, &schema)?);
+            let calendar = calendar(matches, config, years_around(&[a, b]))?;
+            let distance = match tolerance.unit {
+                Unit::Hours => hours_between(&at(&a, a_time.as_ref()), &at(&b, b_time.as_ref()))?,
+                unit => distance(&calendar, &a, &b, unit)?,
+            };
+            print(
+                out,
+                output::comparison((a_str, &a), (b_str, &b), distance, &tolerance, &style),
+            )?;
+            match distance <= tolerance.amount {
+                true => Ok(ExitCode::SUCCESS),
+                false => Err(format!(
+                    "{} and {} are {}{} apart, more than {}",
+                    a_str,
+                    b_str,
+                    distance,
+                    tolerance.unit.suffix(),
+                    tolerance
+                )),
+            }
+        }
+        Some(("cmp", matches)) => {
+            let (a_str, b_str) = (matches.required::<String>("a")?, matches.required::<String>("b")?);
+            let ((a, a_time), (b, b_time)) = (datetime_str(a_str, &schema)?, datetime_str(b_str, &schema)?);
+            let ordering = at(&a, a_time.as_ref()).cmp(&at(&b, b_time.as_ref()));
+            print(out, output::ordering((a_str, &a), (b_str, &b), ordering, &style))?;
+            let given = matches.optional::<Id>("expected")?.map(Id::as_str);
+            let expected = [
+                ("before", Ordering::Less, "earlier than"),
+                ("after", Ordering::Greater, "later than"),
+                ("same", Ordering::Equal, "the same as"),
+            ]
+            .iter()
+            .find(|(name, _, _)| given == Some(*name));
+            match expected {
+                Some((_, expected, _)) if *expected == ordering => Ok(ExitCode::SUCCESS),
+                Some((_, _, relation)) => Err(format!("{} is not {} {}", a_str, relation, b_str)),
+                // Errors exit with 1, so the orderings get codes of their own.
+                None => match ordering {
+                    Ordering::Equal => Ok(ExitCode::SUCCESS),
+                    Ordering::Less => Ok(ExitCode::from(2)),
+                    Ordering::Greater => Ok(ExitCode::from(3)),
+                },
+            }
+        }
+        Some(("timeline", matches)) => {
+            let ranges = read_lines(matches.required::<String>("input")?, |line| {
+                named_range_str(line, &schema)
+            })?;
+            let milestones = milestone::resolve(&config.milestones(), &schema)?;
+            let dates: Vec<NaiveDate> = ranges
+                .iter()
+                .flat_map(|(_, range)| [range.from, range.till])
+                .chain(milestones.iter().map(|milestone| milestone.date))
+                .collect();
+            let calendar = calendar(matches, config, years_around(&dates))?;
+            print(out, output::timeline(&ranges, &milestones, &calendar, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("union", matches)) => {
+            let ranges = inputs(matches.values("ranges")?)?
+                .iter()
+                .map(|range| named_range(range, config, &schema))
+                .collect::<Result<Vec<_>, _>>()?;
+            let dates: Vec<NaiveDate> = ranges.iter().flat_map(|range| [range.from, range.till]).collect();
+            let calendar = calendar(matches, config, years_around(&dates))?;
+            let merged = union(&calendar, &ranges)?;
+            if !merged.is_empty() || style.format.is_structured() {
+                print(out, output::ranges(&merged, &style))?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("split", matches)) => {
+            let parts = *matches.required::<u32>("parts")?;
+            let range = named_range(matches.required::<String>("range")?, config, &schema)?;
+            let chunks = split(
+                &calendar(matches, config, years_around(&[range.from, range.till]))?,
+                &range,
+                parts,
+            )?;
+            print(out, output::chunks(&chunks, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some((name @ ("next" | "prev"), matches)) => {
+            let input = matches.required::<String>("date_str")?;
+            let from = date_str(input, &schema)?;
+            let calendar = calendar(matches, config, years_around(&[from]))?;
+            let date = match name {
+                "next" => next_workday(&calendar, &from)?,
+                _ => previous_workday(&calendar, &from)?,
+            };
+            print(out, output::adjacent_workday(input, &date, name == "next", &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("add", matches)) => {
+            let input = matches.required::<String>("date_str")?;
+            let offset = matches.required::<Offset>("offset")?;
+            let from = date_str(input, &schema)?;
+            let years = shifted_years(matches, config, &from, offset)?;
+            let date = shift(&calendar(matches, config, years)?, &from, offset)?;
+            print(out, output::shifted(input, offset, &date, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("nth-workday", matches)) => {
+            let n = *matches.required::<i32>("n")?;
+            let period = matches.required::<String>("of")?;
+            let period_range = period_str(period, &schema)?;
+            let years = years_around(&[period_range.from, period_range.till]);
+            let date = nth_workday(&calendar(matches, config, years)?, &period_range, n)?;
+            print(out, output::nth_workday(n, period, &date, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("badge", matches)) => {
+            let date = anchor(matches, config, &schema, deterministic)?;
+            print(
+                out,
+                output::badge(
+                    matches.required::<String>("label")?,
+                    matches.required::<String>("color")?,
+                    &date,
+                    &style,
+                ),
+            )?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("countdown", matches)) => {
+            let target_input = matches.required::<String>("target")?;
+            let milestones = milestone::resolve(&config.milestones(), &schema)?;
+            let target = match milestones.iter().find(|milestone| &milestone.name == target_input) {
+                Some(milestone) => milestone.date,
+                None => date_str(target_input, &schema)?,
+            };
+            let today = anchor(matches, config, &schema, deterministic)?;
+            let calendar = calendar(matches, config, years_around(&[today, target]))?;
+            if !matches.flag("watch")? {
+                let now = match matches.optional::<String>("relative_to")? {
+                    Some(_) => today.and_hms(0, 0, 0),
+                    None => clock::now(deterministic, timezone(matches, config)?)?,
+                };
+                let countdown = milestone::countdown(&calendar, &now, &target)?;
+                if countdown.days < 0 {
+                    warn!("{} ({}) is in the past", target_input, target);
+                }
+                print(out, output::countdown(target_input, &target, &countdown, &style))?;
+                return Ok(ExitCode::SUCCESS);
+            }
+            let interval = matches.optional::<u64>("interval")?.copied().unwrap_or(60);
+            loop {
+                let countdown = milestone::countdown(
+                    &calendar,
+                    &clock::now(deterministic, timezone(matches, config)?)?,
+                    &target,
+                )?;
+                if style.format == Format::Plain {
+                    // clear the screen and move the cursor home
+                    write!(out, "\x1b[2J\x1b[H").map_err(write_error)?;
+                }
+                print(out, output::countdown(target_input, &target, &countdown, &style))?;
+                out.flush().map_err(write_error)?;
+                thread::sleep(Duration::from_secs(interval));
+            }
+        }
+        Some(("seq", matches)) => {
+            let style = with_template(matches, style)?;
+            let (from, till) = (matches.required::<String>("from")?, matches.required::<String>("till")?);
+            let range = range_str(&format!("{}..{}", from, till), &schema)?;
+            let step = matches.required::<Offset>("step")?;
+            let calendar = calendar(matches, config, years_around(&[range.from, range.till]))?;
+            let dates = range::seq(&calendar, &range, step)?;
+            print(out, output::seq(&dates, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("age", matches)) => {
+            let input = matches.required::<String>("date_str")?;
+            let (build, today) = (
+                date_str(input, &schema)?,
+                anchor(matches, config, &schema, deterministic)?,
+            );
+            let days = today.signed_duration_since(build).num_days();
+            let calendar = calendar(matches, config, years_around(&[build, today]))?;
+            let workdays = milestone::workdays_until(&calendar, &build, &today)?;
+            print(out, output::age(input, &build, days, workdays, &style))?;
+            match matches.optional::<i64>("max_workdays")? {
+                Some(max) if workdays > *max => Err(format!(
+                    "{} is {} workday(s) old, more than --max-workdays {}",
+                    input, workdays, max
+                )),
+                _ => Ok(ExitCode::SUCCESS),
+            }
+        }
+        Some(("diff", matches)) => {
+            let (a_str, b_str) = (matches.required::<String>("a")?, matches.required::<String>("b")?);
+            let ((a, a_time), (b, b_time)) = (datetime_str(a_str, &schema)?, datetime_str(b_str, &schema)?);
+            let calendar = calendar(matches, config, years_around(&[a, b]))?;
+            let days = distance(&calendar, &a, &b, Unit::Days)?;
+            let workdays = effort(&calendar, &a.min(b), &a.max(b))?;
+            let hours = match a_time.is_some() || b_time.is_some() {
+                true => Some(hours_between(&at(&a, a_time.as_ref()), &at(&b, b_time.as_ref()))?),
+                false => None,
+            };
+            let breakdown = match matches.flag("breakdown")? {
+                true => Some(breakdown(&calendar, &a, &b)?),
+                false => None,
+            };
+            print(
+                out,
+                output::diff(
+                    (a_str, &a),
+                    (b_str, &b),
+                    days,
+                    workdays,
+                    hours,
+                    breakdown.as_ref(),
+                    &style,
+                ),
+            )?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("week", matches)) => {
+            let week = parse::week_str(matches.required::<String>("week")?, &schema)?;
+            let calendar = calendar(matches, config, years_around(&[week.from, week.till]))?;
+            let workdays_only = matches.flag("workdays_only")?;
+            let days: Vec<(NaiveDate, DayKind)> = week
+                .from
+                .iter_days()
+                .take_while(|date| date <= &week.till)
+                .map(|date| (date, calendar.day_kind(&date)))
+                .filter(|(_, kind)| !workdays_only || kind == &DayKind::Workday)
+                .collect();
+            print(out, output::week(&days, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("week-bounds", matches)) => {
+            let week_input = matches.required::<String>("week")?;
+            let week = parse::week_str(week_input, &schema)?;
+            let workdays = match matches.flag("with_workdays")? {
+                true => {
+                    let calendar = calendar(matches, config, years_around(&[week.from, week.till]))?;
+                    Some((nth_workday(&calendar, &week, 1)?, nth_workday(&calendar, &week, -1)?))
+                }
+                false => None,
+            };
+            print(out, output::week_bounds(week_input, &week, workdays.as_ref(), &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("free-days", matches)) => {
+            let range = named_range(matches.required::<String>("range")?, config, &schema)?;
+            let calendar = calendar(matches, config, years_around(&[range.from, range.till]))?;
+            let free_days = free_days(&calendar, &range);
+            let workdays = range::workdays(&calendar, &range).len();
+            print(out, output::free_days(&free_days, workdays, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("bridge-days", matches)) => {
+            let year = match matches.optional::<i32>("year")? {
+                Some(year) => *year,
+                None => anchor(matches, config, &schema, deterministic)?.year(),
+            };
+            let day =
+                |month, day| NaiveDate::from_ymd_opt(year, month, day).ok_or(format!("year {} is out of range", year));
+            let calendar = calendar(matches, config, years_around(&[day(1, 1)?, day(12, 31)?]))?;
+            if calendar.holidays.is_empty() {
+                return Err(
+                    "bridge days require holidays, pass --holidays or --holiday-calendar or configure holiday sources"
+                        .to_string(),
+                );
+            }
+            // bridge days of a year without known holidays would silently be missing
+            if !calendar.holidays.keys().any(|holiday| holiday.year() == year) {
+                return Err(format!(
+                    "bridge days require holidays, but the holiday sources have none in {}",
+                    year
+                ));
+            }
+            let bridge_days: Vec<_> = calendar
+                .bridge_days()
+                .into_iter()
+                .filter(|(day, _)| day.year() == year)
+                .map(|(day, holiday)| (day, holiday, calendar.holidays.get(&holiday).cloned().flatten()))
+                .collect();
+            print(out, output::bridge_days(year, &bridge_days, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("is-holiday", matches)) => {
+            let input = matches.required::<String>("date_str")?;
+            let date = date_str(input, &schema)?;
+            let kind = calendar(matches, config, years_around(&[date]))?.day_kind(&date);
+            print(out, output::day_kind(&date, &kind, &style))?;
+            match kind {
+                DayKind::Holiday(_) => Ok(ExitCode::SUCCESS),
+                _ => Err(format!("{} is not a holiday", input)),
+            }
+        }
+        Some(("milestone", matches)) => {
+            let milestones = milestone::resolve(&config.milestones(), &schema)?;
+            match matches.subcommand() {
+                Some(("list", _)) => {
+                    if !milestones.is_empty() || style.format.is_structured() {
+                        print(out, output::milestones(&milestones, &style))?;
+                    }
+                    Ok(ExitCode::SUCCESS)
+                }
+                Some(("until", matches)) => {
+                    let milestone = milestone::find(&milestones, matches.required::<String>("name")?)?;
+                    let from = anchor(matches, config, &schema, deterministic)?;
+                    let calendar = calendar(matches, config, years_around(&[from, milestone.date]))?;
+                    let workdays = milestone::workdays_until(&calendar, &from, &milestone.date)?;
+                    print(out, output::milestone_until(milestone, &from, workdays, &style))?;
+                    Ok(ExitCode::SUCCESS)
+                }
+                _ => Err("Unknown milestone subcommand".to_string()),
+            }
+        }
+        Some(("month-edge", matches)) => {
+            let month_range = matches.required::<DateRange>("month")?;
+            let last = matches.flag("last_workday")?;
+            let n = if last { -1 } else { 1 };
+            let years = years_around(&[month_range.from, month_range.till]);
+            let date = nth_workday(&calendar(matches, config, years)?, month_range, n)?;
+            print(
+                out,
+                output::month_edge(last, &month_range.from.format("%Y-%m").to_string(), &date, &style),
+            )?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("remaining", matches)) => {
+            let from = anchor(matches, config, &schema, deterministic)?;
+            let year = match matches.optional::<i32>("in")? {
+                Some(year) => *year,
+                None => schema.week_year(&from),
+            };
+            let range = year_range(year, &schema)?;
+            let calendar = calendar(matches, config, years_around(&[range.from, range.till, from]))?;
+            let remaining = range::remaining(&calendar, &range, &from);
+            print(out, output::remaining(year, &from, &remaining, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("sort", matches)) => {
+            let inputs = inputs(matches.values("data_str")?)?;
+            let sorted = sort(&inputs, &schema, matches.flag("reverse")?, matches.flag("unique")?)?;
+            if !sorted.is_empty() || style.format.is_structured() {
+                print(out, output::inputs(&sorted, &style))?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some((name @ ("latest" | "earliest"), matches)) => {
+            let inputs = inputs(matches.values("data_str")?)?;
+            let dated = dated(&inputs, &schema)?;
+            let selected = match name {
+                "latest" => dated.iter().max_by_key(|(_, at)| *at),
+                _ => dated.iter().min_by_key(|(_, at)| *at),
+            };
+            let (input, at) = selected.ok_or("no cw versions or ISO dates given")?;
+            print(out, output::selected(input, &at.date(), &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("dedup", matches)) => {
+            let inputs = inputs(matches.values("data_str")?)?;
+            let groups = dedup(&inputs, &schema)?;
+            if !groups.is_empty() || style.format.is_structured() {
+                print(out, output::dedup(&groups, &style))?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("set", matches)) => {
+            let (name, matches) = matches.subcommand().ok_or("Unknown set subcommand")?;
+            let operation = set::Operation::parse(name)?;
+            let versions = operation.apply(
+                &read_list(matches.required::<String>("list_a")?, &schema)?,
+                &read_list(matches.required::<String>("list_b")?, &schema)?,
+            );
+            if !versions.is_empty() || style.format.is_structured() {
+                print(out, output::versions(&versions, &style))?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("selftest", matches)) => {
+            let years = match matches.optional::<RangeInclusive<i32>>("years")? {
+                Some(years) => years.clone(),
+                None => schema.pivot..=schema.pivot + 99,
+            };
+            let calendar = calendar(matches, config, years.start() - 1..=years.end() + 1)?;
+            let report = selftest(&calendar, &years, &schema)?;
+            print_unless_empty(out, output::selftest(&report, &style))?;
+            match report.failures() {
+                0 => Ok(ExitCode::SUCCESS),
+                failures => Err(format!("self-test found {} failure(s)", failures)),
+            }
+        }
+        Some(("verify", matches)) => {
+            let inputs = inputs(matches.values("data_str")?)?;
+            let suggest = matches.flag("suggest")?;
+            let deviations: Vec<Deviation> = inputs
+                .iter()
+                .filter_map(|input| {
+                    let reason = verify(input, &schema).err()?;
+                    Some(Deviation {
+                        input,
+                        reason: reason.to_string(),
+                        suggestion: match suggest {
+                            true => parse::suggest(input, &schema),
+                            false => None,
+                        },
+                    })
+                })
+                .collect();
+            let report = output::verification(inputs.len(), &deviations, &style);
+            if !report.is_empty() {
+                print(out, report)?;
+            }
+            match deviations.len() {
+                0 => Ok(ExitCode::SUCCESS),
+                n => Err(format!("{} of {} input(s) deviate", n, inputs.len())),
+            }
+        }
+        Some(("grep", matches)) => {
+            let path = matches.required::<String>("file")?;
+            let text = read_text(path)?;
+            let found = grep(&text, &schema);
+            if found.is_empty() && !style.format.is_structured() {
+                let source = if path == "-" { "stdin" } else { path };
+                return Err(format!("no cw versions found in {}", source));
+            }
+            print(out, output::grep(&found, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("annotate", matches)) => {
+            let replace = matches.flag("replace")?;
+            let rewrite = |line: &str| Ok(annotate(line, &schema, replace));
+            match matches.required::<String>("file")?.as_str() {
+                "-" => rewrite_lines(io::stdin().lock(), &mut *out, rewrite)?,
+                path => {
+                    let file = fs::File::open(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+                    rewrite_lines(io::BufReader::new(file), &mut *out, rewrite)?
+                }
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("scan", matches)) => {
+            let dir = matches.required::<String>("dir")?;
+            let artifacts = scan(Path::new(dir), matches.required::<String>("glob")?, &schema)?;
+            if artifacts.is_empty() && !style.format.is_structured() {
+                return Err(format!("no files with cw versions found in {}", dir));
+            }
+            let dates: Vec<NaiveDate> = artifacts.iter().map(|(_, date)| *date).collect();
+            let gaps = range::gaps(&calendar(matches, config, years_around(&dates))?, &dates)?;
+            print(out, output::scan(&artifacts, &gaps, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("rename", matches)) => {
+            let to_iso = matches.flag("from_cwver")? || matches.flag("to_iso")?;
+            if to_iso && (matches.flag("from_iso")? || matches.flag("to_cwver")?) {
+                return Err("rename either --from-cwver --to-iso or --from-iso --to-cwver".to_string());
+            }
+            let renames = renames(Path::new(matches.required::<String>("dir")?), &schema, to_iso)?;
+            let dry_run = matches.flag("dry_run")?;
+            if !dry_run {
+                for (from, to) in &renames {
+                    fs::rename(from, to)
+                        .map_err(|e| format!("failed to rename {} to {}: {}", from.display(), to.display(), e))?;
+                    info!(from = %from.display(), to = %to.display(), "renamed");
+                }
+            }
+            if !renames.is_empty() || style.format.is_structured() {
+                print(out, output::renames(&renames, dry_run, &style))?;
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("lint", matches)) => {
+            let dir = Path::new(matches.required::<String>("dir")?);
+            let mut ignore = vec![".git".to_string()];
+            ignore.extend(files::ignore_patterns(dir));
+            ignore.extend(matches.values::<String>("ignore")?.into_iter().flatten().cloned());
+            let paths = files::walk(dir, &ignore)?;
+            let issues: Vec<Issue> = paths.iter().flat_map(|path| lint(path, &schema)).collect();
+            print(out, output::lint(paths.len(), &issues, &style))?;
+            match issues.len() {
+                0 => Ok(ExitCode::SUCCESS),
+                n => Err(format!("{} issue(s) in {} file(s)", n, paths.len())),
+            }
+        }
+        Some(("fmt", matches)) => {
+            match matches.values::<String>("data_str")? {
+                Some(inputs) => {
+                    for input in inputs {
+                        print(out, normalize(input, &schema)?)?;
+                    }
+                }
+                None => rewrite_lines(io::stdin().lock(), &mut *out, |line| normalize(line, &schema))?,
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("validate", matches)) => {
+            let inputs = inputs(matches.values("data_str")?)?;
+            let invalid: Vec<Deviation> = inputs
+                .iter()
+                .filter_map(|input| {
+                    Some(Deviation {
+                        input,
+                        reason: parse::validate(input, &schema).err()?.to_string(),
+                        suggestion: None,
+                    })
+                })
+                .collect();
+            let report = output::verification(inputs.len(), &invalid, &style);
+            if !report.is_empty() {
+                print(out, report)?;
+            }
+            match invalid.len() {
+                0 => Ok(ExitCode::SUCCESS),
+                n => Err(format!("{} of {} input(s) are invalid", n, inputs.len())),
+            }
+        }
+        Some(("workdays-in", matches)) => {
+            let range = named_range(matches.required::<String>("range")?, config, &schema)?;
+            let calendar = calendar(matches, config, years_around(&[range.from, range.till]))?;
+            let workdays = range::workdays(&calendar, &range).len();
+            print(out, output::workdays_in(&range, workdays, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("week53", matches)) => {
+            let years = match matches.optional::<RangeInclusive<i32>>("years")? {
+                Some(years) => years.clone(),
+                None => schema.pivot..=schema.pivot + 99,
+            };
+            let long_years: Vec<i32> = years.clone().filter(|year| weeks_in_year(*year) == 53).collect();
+            print(out, output::week53(&years, &long_years, matches.flag("stats")?, &style))?;
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(("config", matches)) => match matches.subcommand() {
+            Some(("get", matches)) => {
+                let key = config::key(matches.required::<String>("key")?)?;
+                let (value, _) = config
+                    .effective(key)
+                    .ok_or_else(|| format!("{} is not set", key.name))?;
+                print(out, output::config_value(&value))?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Some(("set", matches)) => {
+                let path = match (matches.optional::<PathBuf>("config")?, matches.flag("project")?) {
+                    (Some(path), _) => Some(path.clone()),
+                    (None, true) => config::project_config_path()
+                        .or_else(|| env::current_dir().ok().map(|dir| dir.join(config::PROJECT_CONFIG))),
+                    (None, false) => config::user_config_path(),
+                }
+                .ok_or("failed to locate the configuration file")?;
+                config::set(
+                    &path,
+                    matches.required::<String>("key")?,
+                    matches.required::<String>("value")?,
+                )?;
+                Ok(ExitCode::SUCCESS)
+            }
+            Some(("list", matches)) if matches.flag("effective")? => {
+                for key in config::KEYS {
+                    if let Some((value, source)) = config.effective(key) {
+                        print(out, output::config_setting(key.name, &value, &source))?;
+                    }
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Some(("list", _)) => {
+                for (name, value, origin) in config.settings() {
+                    print(out, output::config_setting(&name, value, &origin))?;
+                }
+                Ok(ExitCode::SUCCESS)
+            }
+            Some(("path", matches)) => {
+                let (user_config, project_config) = match matches.optional::<PathBuf>("config")? {
+                    Some(path) => (Some(path.clone()), None),
+                    None => (config::user_config_path(), config::project_config_path()),
+                };
+                print(
+                    out,
+                    output::config_paths(
+                        user_config.as_deref(),
+                        project_config.as_deref(),
+                        config::cache_dir().as_deref(),
+                        &style,
+                    ),
+                )?;
+                Ok(ExitCode::SUCCESS)
+            }
+            _ => Err("Unknown config subcommand".to_string()),
+        },
+        None => {
+            print(
+                out,
+                output::today(&anchor(matches, config, &schema, deterministic)?, &style),
+            )?;
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err("Unknown subcommand".to_string()),
+    }
+}
+
+/// Writes a result like `println!`, to stdout or the output of a test.
+fn print(out: &mut impl Write, result: impl fmt::Display) -> Result<(), String> {
+    writeln!(out, "{}", result).map_err(write_error)
+}
+
+/// Quiet output without any values is no output at all, not an empty line.
+fn print_unless_empty(out: &mut impl Write, result: String) -> Result<(), String> {
+    match result.is_empty() {
+        true => Ok(()),
+        false => print(out, result),
+    }
+}
+
+fn write_error(error: io::Error) -> String {
+    format!("failed to write to stdout: {}", error)
+}
+
+/// The date time-relative commands refer to, `--relative-to` or today.
+fn anchor(matches: &ArgMatches, config: &Config, schema: &Schema, deterministic: bool) -> Result<NaiveDate, String> {
+    match matches.optional::<String>("relative_to")? {
+        Some(anchor_str) => {
+            let anchor = date_str(anchor_str, schema)?;
+            info!(%anchor, "relative to");
+            Ok(anchor)
+        }
+        None => clock::today(deterministic, timezone(matches, config)?),
+    }
+}
+
+/// How cw version strings are parsed and formatted.
+fn schema(matches: &ArgMatches, config: &Config) -> Result<Schema, String> {
+    Ok(Schema {
+        strict: matches.flag("strict")?,
+        first_weekday: config.first_weekday()?,
+        pivot: match matches.optional::<i32>("pivot")? {
+            Some(pivot) => *pivot,
+            None => config.pivot()?,
+        },
+        long_year: matches.flag("long_year")?,
+        pattern: match matches.optional::<Pattern>("pattern")? {
+            Some(pattern) => Some(pattern.clone()),
+            None => config.pattern()?,
+        },
+    })
+}
+
+/// `--tz` and `--utc`, which conflict on the command line, win over the `timezone` setting. `CWVER_UTC`
+/// yields to `--tz` and wins over `CWVER_TIMEZONE`.
+fn timezone(matches: &ArgMatches, config: &Config) -> Result<Timezone, String> {
+    let timezone = match (on_command_line(matches, "tz"), on_command_line(matches, "utc")) {
+        (false, _) if matches.flag("utc")? => Timezone::Utc,
+        (true, true) => return Err("--utc cannot be used with --tz".to_string()),
+        _ => match matches.optional::<Timezone>("tz")? {
+            Some(timezone) => *timezone,
+            None => config.timezone()?,
+        },
+    };
+    info!(?timezone, "resolved timezone");
+    Ok(timezone)
+}
+
+/// The style with the `--format` template of a subcommand displaying a single date.
+fn with_template(matches: &ArgMatches, style: Style) -> Result<Style, String> {
+    Ok(Style {
+        template: matches.optional::<Template>("format")?.cloned(),
+        ..style
+    })
+}
+
+/// `--locale` wins over the configuration files, which win over the locale of the environment.
+/// In deterministic mode the environment is ignored.
+fn locale(matches: &ArgMatches, config: &Config, deterministic: bool) -> Result<Locale, String> {
+    let locale = match (matches.optional::<Locale>("locale")?, config.locale()?) {
+        (Some(locale), _) => *locale,
+        (None, Some(locale)) => locale,
+        (None, None) if deterministic => Locale::default(),
+        (None, None) => Locale::detect(),
+    };
+    info!(?locale, "resolved locale");
+    Ok(locale)
+}
+
+/// A built-in holiday calendar like `DE-BY`, or one fetched from the holidays endpoint like
+/// `online:DE-BY`.
+fn holiday_calendar(code: &str) -> Result<holidays::Source, String> {
+    match code.strip_prefix("online:") {
+        Some(_) => Ok(holidays::Source::parse(code, None)?),
+        None => {
+            countries::holidays(code, 2000)?;
+            Ok(holidays::Source::Country(code.to_string()))
+        }
+    }
+}
+
+/// The workdays, the holidays of the configured holiday sources and the `--blackout` ranges.
+/// `--holidays` and `--holiday-calendar` replace the sources of the environment and the
+/// configuration files, `CWVER_HOLIDAYS` and `CWVER_HOLIDAY_CALENDAR` the ones of the
+/// configuration files. `--blackout` replaces `CWVER_BLACKOUT` likewise. Online holiday calendars
+/// are fetched for `years`, see [`years_around`].
+fn calendar(matches: &ArgMatches, config: &Config, years: RangeInclusive<i32>) -> Result<Calendar, String> {
+    // the variables only count if neither option is given on the command line
+    let from_env = !on_command_line(matches, "holidays") && !on_command_line(matches, "holiday_calendar");
+    let given = |name| from_env || on_command_line(matches, name);
+    let mut sources = vec![];
+    if given("holidays") {
+        for source in matches.values::<String>("holidays")?.into_iter().flatten() {
+            if !source.trim().is_empty() {
+                sources.push(holidays::Source::parse(source.trim(), None)?);
+            }
+        }
+    }
+    if given("holiday_calendar") {
+        sources.extend(matches.values("holiday_calendar")?.into_iter().flatten().cloned());
+    }
+    if sources.is_empty() {
+        sources = config.holidays()?;
+    }
+    let (mut online, mut offline) = (vec![], vec![]);
+    for source in sources {
+        match source {
+            holidays::Source::Online(country) => online.push(country),
+            source => offline.push(source),
+        }
+    }
+    let schema = schema(matches, config)?;
+    let mut blackouts = vec![];
+    for path in matches.values::<String>("blackout")?.into_iter().flatten() {
+        blackouts.extend(holidays::load_blackouts(Path::new(path), &schema)?);
+    }
+    let mut calendar = Calendar {
+        holidays: holidays::load_cached(&offline, &schema, config::cache_dir().as_deref())?,
+        blackouts,
+        weights: matches.optional("workday_weights")?.cloned().unwrap_or_default(),
+        ..Calendar::new(workdays(matches, config)?)
+    };
+    if !online.is_empty() {
+        // online calendars are fetched year by year, so only the years the command deals with are,
+        // and a year which can neither be fetched nor is cached fails the command. The year of
+        // `--relative-to` counts as current, so that pinning it doesn't need the wall clock.
+        let current_year = anchor(matches, config, &schema, matches.flag("deterministic")?)?.year();
+        for (date, name) in online_holidays(&online, years, current_year, config)? {
+            calendar.holidays.entry(date).or_insert(name);
+        }
+    }
+    Ok(calendar)
+}
+
+/// The years a command dealing with `dates` needs holidays of, including the weeks around them which
+/// adjacent workdays may fall into.
+fn years_around(dates: &[NaiveDate]) -> RangeInclusive<i32> {
+    let margin = chrono::Duration::weeks(2);
+    match (dates.iter().min(), dates.iter().max()) {
+        (Some(first), Some(last)) => {
+            let first = first.checked_sub_signed(margin).unwrap_or(*first);
+            let last = last.checked_add_signed(margin).unwrap_or(*last);
+            first.year()..=last.year()
+        }
+        _ => RangeInclusive::new(1, 0),
+    }
+}
+
+/// The years shifting `date` by `offset` needs holidays of, estimated by shifting it in a calendar
+/// without holidays.
+fn shifted_years(
+    matches: &ArgMatches,
+    config: &Config,
+    date: &NaiveDate,
+    offset: &Offset,
+) -> Result<RangeInclusive<i32>, String> {
+    let estimate = shift(&Calendar::new(workdays(matches, config)?), date, offset)?;
+    Ok(years_around(&[*date, estimate]))
+}
+
+/// The holidays of the `online:` holiday calendars of `countries` in `years`, fetched from the
+/// holidays endpoint.
+#[cfg(feature = "online")]
+fn online_holidays(
+    countries: &[String],
+    years: RangeInclusive<i32>,
+    current_year: i32,
+    config: &Config,
+) -> Result<im::HashMap<NaiveDate, Option<String>>, String> {
+    let endpoint = config.holidays_endpoint()?;
+    let cache = config::cache_dir();
+    let mut holidays = im::HashMap::new();
+    for country in countries {
+        let fetched = online::holidays(country, years.clone(), current_year, &endpoint, cache.as_deref())?;
+        holidays = holidays.union(fetched);
+    }
+    Ok(holidays)
+}
+
+#[cfg(not(feature = "online"))]
+fn online_holidays(
+    countries: &[String],
+    _years: RangeInclusive<i32>,
+    _current_year: i32,
+    _config: &Config,
+) -> Result<im::HashMap<NaiveDate, Option<String>>, String> {
+    Err(format!(
+        "holiday calendar online:{} requires the online feature, only built-in calendars and files are available",
+        countries.join(", online:")
+    ))
+}
+
+/// `--workdays` wins over the configuration files, which win over the built-in default.
+fn workdays(matches: &ArgMatches, config: &Config) -> Result<HashSet<u32>, String> {
+    let workdays = match matches.optional::<HashSet<u32>>("workdays")? {
+        Some(workdays) => workdays.clone(),
+        None => match config.workdays()? {
+            Some(workdays) => workdays,
+            None => workdays_to_hashset(config::DEFAULT_WORKDAYS)?,
+        },
+    };
+    info!(?workdays, "resolved workdays");
+    Ok(workdays)
+}
+
+/// The given values, or the non-empty lines of stdin if there are none.
+fn inputs(values: Option<ValuesRef<'_, String>>) -> Result<Vec<String>, String> {
+    if let Some(values) = values {
+        return Ok(values.cloned().collect());
+    }
+    let mut inputs = vec![];
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("failed to read stdin: {}", e))?;
+        if !line.trim().is_empty() {
+            inputs.push(line.trim().to_string());
+        }
+    }
+    Ok(inputs)
+}
+
+/// The inputs with their date and time of day, in the order given.
+fn dated<'a>(inputs: &'a [String], schema: &Schema) -> Result<Vec<(&'a str, NaiveDateTime)>, String> {
+    inputs
+        .iter()
+        .map(|input| {
+            let (date, time) = datetime_str(input, schema)?;
+            Ok((input.as_str(), at(&date, time.as_ref())))
+        })
+        .collect()
+}
+
+/// Sorts chronologically, inputs referring to the same point in time keep their order. With
+/// `unique` only the first of them is kept.
+fn sort<'a>(
+    inputs: &'a [String],
+    schema: &Schema,
+    reverse: bool,
+    unique: bool,
+) -> Result<Vec<(&'a str, NaiveDateTime)>, String> {
+    let mut sorted = dated(inputs, schema)?;
+    match reverse {
+        true => sorted.sort_by(|(_, a), (_, b)| b.cmp(a)),
+        false => sorted.sort_by_key(|(_, at)| *at),
+    }
+    if unique {
+        sorted.dedup_by_key(|(_, at)| *at);
+    }
+    Ok(sorted)
+}
+
+/// Groups the inputs referring to the same date, in the order of their first occurrence.
+fn dedup<'a>(inputs: &'a [String], schema: &Schema) -> Result<Vec<(NaiveDate, Vec<&'a str>)>, String> {
+    let mut groups: Vec<(NaiveDate, Vec<&str>)> = vec![];
+    let mut indices: HashMap<NaiveDate, usize> = HashMap::new();
+    for input in inputs {
+        let date = parse::loose_date_str(input, schema)?;
+        match indices.get(&date) {
+            Some(&index) => groups[index].1.push(input),
+            None => {
+                indices.insert(date, groups.len());
+                groups.push((date, vec![input]));
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// The days a bisect endpoint stands for, a single day or a week-only version like `21w45`, and its
+/// build, by default the first build of the day for the left side and the last one for the right side.
+fn bisect_endpoint(endpoint: &str, till: bool, slots: u32, schema: &Schema) -> Result<(DateRange, u32), String> {
+    let (day, index) = parse::split_slot(endpoint);
+    let index = match index {
+        Some(index) if index >= slots => {
+            return Err(format!(
+                "{} is build {} of its day, but there are only {} build(s) per day, pass --slots {}",
+                endpoint,
+                index + 1,
+                slots,
+                index + 1
+            ))
+        }
+        Some(index) => index,
+        None if till => slots - 1,
+        None => 0,
+    };
+    let days = match parse::is_week(day) {
+        true => parse::week_str(day, schema)?,
+        false => {
+            let date = date_str(day, schema)?;
+            DateRange::new(date, date)?
+        }
+    };
+    Ok((days, index))
+}
+
+/// A bisect endpoint on its workday: a week is its first workday on the left side of a bisect range,
+/// and its last workday on the right side.
+fn bisect_slot(calendar: &Calendar, (days, index): (DateRange, u32), till: bool) -> Slot {
+    let workdays = range::workdays(calendar, &days);
+    let date = match till {
+        true => workdays.last().copied().unwrap_or(days.till),
+        false => workdays.first().copied().unwrap_or(days.from),
+    };
+    Slot { date, index }
+}
+
+/// A range, or a range named in the configuration files if prefixed with `@`.
+fn named_range(range: &str, config: &Config, schema: &Schema) -> Result<DateRange, String> {
+    let name = match range.strip_prefix('@') {
+        Some(name) => name,
+        None => return Ok(range_str(range, schema)?),
+    };
+    let ranges = config.ranges();
+    match ranges.iter().find(|(defined, _)| defined == name) {
+        Some((_, range)) => range_str(range, schema).map_err(|e| format!("range {}: {}", name, e)),
+        None if ranges.is_empty() => Err(format!(
+            "unknown range @{}, no ranges are defined in the configuration files",
+            name
+        )),
+        None => Err(format!(
+            "unknown range @{}, defined are {}",
+            name,
+            ranges
+                .iter()
+                .map(|(defined, _)| format!("@{}", defined))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// The dates of a file with one cw version or ISO date per line, see `read_lines`.
+fn read_list(path: &str, schema: &Schema) -> Result<Vec<NaiveDate>, String> {
+    read_lines(path, |line| date_str(line, schema))
+}
+
+/// Parses the non-empty lines of a file, `-` reads stdin. `#` starts a comment.
+fn read_lines<T, E: fmt::Display>(path: &str, parse: impl Fn(&str) -> Result<T, E>) -> Result<Vec<T>, String> {
+    read_text(path)?
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            match line.is_empty() {
+                true => None,
+                false => Some(parse(line).map_err(|e| format!("{}: line {}: {}", path, number + 1, e))),
+            }
+        })
+        .collect()
+}
+
+/// The content of a file, `-` reads stdin.
+fn read_text(path: &str) -> Result<String, String> {
+    match path {
+        "-" => {
+            let mut content = String::new();
+            io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| format!("failed to read stdin: {}", e))?;
+            Ok(content)
+        }
+        _ => fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e)),
+    }
+}
+
+/// The files below a directory whose name matches the glob and contains a cw version, sorted by
+/// date. Versions not denoting an existing date are skipped with a warning.
+fn scan(dir: &Path, glob: &str, schema: &Schema) -> Result<Vec<(PathBuf, NaiveDate)>, String> {
+    let embedded = Schema {
+        strict: false,
+        ..schema.clone()
+    };
+    let mut artifacts = vec![];
+    for path in files::walk(dir, &[])? {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if !files::glob_match(glob, &name) {
+            continue;
+        }
+        let version = match CwVersion::parse(&name, &embedded) {
+            Ok(version) => version,
+            Err(_) => continue,
+        };
+        match version.to_date(schema) {
+            Ok(date) => artifacts.push((path, date)),
+            Err(e) => warn!("{}: {}", path.display(), e),
+        }
+    }
+    artifacts.sort_by(|(a_path, a), (b_path, b)| (a, a_path).cmp(&(b, b_path)));
+    Ok(artifacts)
+}
+
+/// The file name with its cw versions rewritten into ISO dates, or its ISO dates into cw versions.
+/// Cw versions not denoting an existing date are kept with a warning.
+fn rename(name: &str, schema: &Schema, to_iso: bool) -> String {
+    let replacements: Vec<(Range<usize>, String)> = match to_iso {
+        true => parse::find_cwvers(name, false)
+            .into_iter()
+            .filter_map(|(range, version)| match version.to_date(schema) {
+                Ok(date) => Some((range, date.to_string())),
+                Err(e) => {
+                    warn!("{}: {}", name, e);
+                    None
+                }
+            })
+            .collect(),
+        false => parse::find_iso_dates(name)
+            .into_iter()
+            .map(|(range, date)| (range, CwVersion::from_date(&date, schema).format(schema)))
+            .collect(),
+    };
+    let mut renamed = String::new();
+    let mut end = 0;
+    for (range, replacement) in replacements {
+        renamed += &name[end..range.start];
+        renamed += &replacement;
+        end = range.end;
+    }
+    renamed += &name[end..];
+    renamed
+}
+
+/// The files below a directory to rename and their new paths, see `rename`. Files whose new name is
+/// taken already are skipped with a warning.
+fn renames(dir: &Path, schema: &Schema, to_iso: bool) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let mut renames: Vec<(PathBuf, PathBuf)> = vec![];
+    for path in files::walk(dir, &[])? {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let renamed = path.with_file_name(rename(&name, schema, to_iso));
+        if renamed == path {
+            continue;
+        }
+        match renamed.exists() || renames.iter().any(|(_, to)| to == &renamed) {
+            true => warn!("not renaming {}, {} exists already", path.display(), renamed.display()),
+            false => renames.push((path, renamed)),
+        }
+    }
+    Ok(renames)
+}
+
+/// The malformed or impossible cw versions in the name and, unless it is binary, the content of a
+/// file, see `parse::validate`. Weeks must be zero-padded.
+fn lint(path: &Path, schema: &Schema) -> Vec<Issue> {
+    let strict = Schema {
+        strict: true,
+        ..schema.clone()
+    };
+    let issues = |line: Option<usize>, text: &str| -> Vec<Issue> {
+        parse::find_cwvers(text, true)
+            .into_iter()
+            .filter_map(|(range, _)| {
+                let reason = parse::validate(&text[range.clone()], &strict).err()?;
+                Some(Issue {
+                    path: path.to_path_buf(),
+                    line,
+                    input: text[range].to_string(),
+                    reason: reason.to_string(),
+                })
+            })
+            .collect()
+    };
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let mut found = issues(None, &name);
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            for (number, line) in content.lines().enumerate() {
+                found.extend(issues(Some(number + 1), line));
+            }
+        }
+        Err(e) => debug!(path = %path.display(), error = %e, "not linting the content"),
+    }
+    found
+}
+
+/// The cw version strings in a text with their line number counted from 1. Those not denoting an
+/// existing date are skipped with a warning.
+fn grep<'a>(text: &'a str, schema: &Schema) -> Vec<(usize, &'a str, NaiveDate)> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(number, line)| {
+            parse::find_cwvers(line, false)
+                .into_iter()
+                .filter_map(move |(range, version)| match version.to_date(schema) {
+                    Ok(date) => Some((number + 1, &line[range], date)),
+                    Err(e) => {
+                        warn!("line {}: {}", number + 1, e);
+                        None
+                    }
+                })
+        })
+        .collect()
+}
+
+fn convert(input: &str, style: &Style) -> Result<String, String> {
+    let to_iso = parse::is_cwver_str(input, &style.schema);
+    let (date, time) = datetime_str(input, &style.schema)?;
+    Ok(output::conversion(input, &date, time.as_ref(), to_iso, style))
+}
+
+/// Converts a column of CSV records whose first one is the header, writing the conversions to the
+/// `target` column which is added unless it exists. Empty values stay empty, values failing to
+/// convert are left empty and reported with their line.
+fn convert_csv(
+    text: &str,
+    column: &str,
+    target: &str,
+    style: &Style,
+) -> Result<(Vec<Vec<String>>, Vec<String>), String> {
+    let mut records = csv::parse(text)?;
+    let header = records.first_mut().ok_or("the CSV lacks a header line")?;
+    let source = header
+        .iter()
+        .position(|name| name == column)
+        .ok_or_else(|| format!("the CSV lacks the column {}, it has {}", column, header.join(", ")))?;
+    let target = match header.iter().position(|name| name == target) {
+        Some(index) => index,
+        None => {
+            header.push(target.to_string());
+            header.len() - 1
+        }
+    };
+    let quiet = Style {
+        format: Format::Plain,
+        quiet: true,
+        template: None,
+        ..style.clone()
+    };
+    let mut errors = vec![];
+    for (number, record) in records.iter_mut().enumerate().skip(1) {
+        let value = record.get(source).map(|value| value.trim()).unwrap_or_default();
+        let converted = match value.is_empty() {
+            true => String::new(),
+            false => convert(value, &quiet).unwrap_or_else(|e| {
+                errors.push(format!("record {}: {}", number + 1, e));
+                String::new()
+            }),
+        };
+        if record.len() <= target {
+            record.resize(target + 1, String::new());
+        }
+        record[target] = converted;
+    }
+    Ok((records, errors))
+}
+
+/// Converts one input per line. The line buffer is reused, so memory usage is bounded
+/// by the longest line rather than by the size of the whole input. Inputs failing to convert are
+/// reported to `errors` with their line number, the run fails once all lines are done.
+fn convert_lines(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    mut errors: impl Write,
+    style: &Style,
+) -> Result<(), String> {
+    let mut line = String::new();
+    let (mut number, mut converted, mut failed) = (0, 0, 0);
+    loop {
+        line.clear();
+        if input
+            .read_line(&mut line)
+            .map_err(|e| format!("failed to read stdin: {}", e))?
+            == 0
+        {
+            break;
+        }
+        number += 1;
+        let date_str = line.trim();
+        if date_str.is_empty() {
+            continue;
+        }
+        match convert(date_str, style) {
+            Ok(conversion) => {
+                converted += 1;
+                writeln!(output, "{}", conversion).map_err(|e| format!("failed to write stdout: {}", e))?
+            }
+            Err(e) if style.format == Format::Jsonl => {
+                failed += 1;
+                let document = serde_json::json!({ "line": number, "input": date_str, "error": e });
+                writeln!(output, "{}", document).map_err(|e| format!("failed to write stdout: {}", e))?
+            }
+            Err(e) => {
+                failed += 1;
+                writeln!(errors, "line {}: {}", number, e).map_err(|e| format!("failed to write stderr: {}", e))?
+            }
+        }
+    }
+    match failed {
+        0 => Ok(()),
+        _ => Err(format!(
+            "{} of {} line(s) failed to convert",
+            failed,
+            converted + failed
+        )),
+    }
+}
+
+/// Rewrites the loosely written cw version strings in a text to their canonical form, keeping the
+/// text around them.
+fn normalize(text: &str, schema: &Schema) -> Result<String, String> {
+    let mut normalized = String::new();
+    let mut end = 0;
+    for (range, version) in parse::find_cwvers(text, true) {
+        let date = version.to_date(schema)?;
+        normalized += &text[end..range.start];
+        normalized += &CwVersion::from_date(&date, schema).format(schema);
+        end = range.end;
+    }
+    normalized += &text[end..];
+    Ok(normalized)
+}
+
+/// Appends the ISO date to each cw version string in a text like `21w45.7 (2021-11-14)`, or
+/// replaces the cw version string with it. Those not denoting an existing date are kept as they
+/// are with a warning.
+fn annotate(text: &str, schema: &Schema, replace: bool) -> String {
+    let mut annotated = String::new();
+    let mut end = 0;
+    for (range, version) in parse::find_cwvers(text, false) {
+        annotated += &text[end..range.start];
+        let token = &text[range.clone()];
+        match version.to_date(schema) {
+            Ok(date) if replace => annotated += &date.to_string(),
+            Ok(date) => annotated += &format!("{} ({})", token, date),
+            Err(e) => {
+                warn!("{}", e);
+                annotated += token;
+            }
+        }
+        end = range.end;
+    }
+    annotated += &text[end..];
+    annotated
+}
+
+/// Rewrites a text line by line, e.g. with `normalize`. Lines are passed through verbatim
+/// otherwise, so that e.g. changelogs keep their layout.
+fn rewrite_lines(
+    input: impl BufRead,
+    mut output: impl Write,
+    rewrite: impl Fn(&str) -> Result<String, String>,
+) -> Result<(), String> {
+    for (number, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| format!("failed to read stdin: {}", e))?;
+        let rewritten = rewrite(&line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        writeln!(output, "{}", rewritten).map_err(|e| format!("failed to write stdout: {}", e))?;
+    }
+    Ok(())
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+    #[cfg(test)]
+    use clap::error::ErrorKind;
+
+    #[test]
+    fn test_env_flags() {
+        assert_eq!(env_var("deterministic"), "CWVER_DETERMINISTIC".to_string());
+        assert_eq!(truthy("TRUE"), Some(true));
+        assert_eq!(truthy("0"), Some(false));
+        assert_eq!(truthy("maybe"), None);
+    }
+
+    #[test]
+    fn test_years_around() {
+        assert_eq!(years_around(&[NaiveDate::from_ymd(2015, 6, 25)]), 2015..=2015);
+        assert_eq!(
+            years_around(&[NaiveDate::from_ymd(2021, 3, 1), NaiveDate::from_ymd(2015, 12, 25)]),
+            2015..=2021
+        );
+        assert_eq!(years_around(&[NaiveDate::from_ymd(2015, 1, 5)]), 2014..=2015);
+        assert!(years_around(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_app() {
+        app(&[("b".to_string(), "bisect".to_string())], false).debug_assert();
+        app(&[], true).debug_assert();
+    }
+
+    #[test]
+    fn test_switch() {
+        let strict = |args: &[&str]| -> Result<Option<bool>, ErrorKind> {
+            let matches = app(&[], false).try_get_matches_from(args).map_err(|e| e.kind())?;
+            Ok(matches.optional::<bool>("strict").unwrap().copied())
+        };
+        assert_eq!(strict(&["cwver", "today"]), Ok(None));
+        assert_eq!(strict(&["cwver", "--strict", "today"]), Ok(Some(true)));
+        assert_eq!(strict(&["cwver", "--strict=off", "today"]), Ok(Some(false)));
+        assert_eq!(strict(&["cwver", "today", "--strict"]), Ok(Some(true)));
+        assert_eq!(
+            strict(&["cwver", "--strict=maybe", "today"]),
+            Err(ErrorKind::ValueValidation)
+        );
+    }
+
+    /// Runs cwver with `args` and without configuration files, returning its status and output.
+    /// It runs deterministically, so time-relative commands need `--relative-to`.
+    #[cfg(test)]
+    fn cwver(args: &[&str]) -> (Result<ExitCode, String>, String) {
+        let args = ["cwver", "--deterministic"].iter().chain(args).copied();
+        let matches = app(&[], false).try_get_matches_from(args).unwrap();
+        let mut out = vec![];
+        let status = run(&matches, &Config::default(), &mut out);
+        (status, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_cmp() {
+        let ordered = |output: &str| (Ok(ExitCode::SUCCESS), output.to_string());
+        assert_eq!(
+            cwver(&["cmp", "21w45.1", "21w45.3"]),
+            (Ok(ExitCode::from(2)), "lt\n".to_string())
+        );
+        assert_eq!(cwver(&["cmp", "21w45.3", "2021-11-10"]), ordered("eq\n"));
+        assert_eq!(
+            cwver(&["cmp", "21w45.3", "21w45.1"]),
+            (Ok(ExitCode::from(3)), "gt\n".to_string())
+        );
+        assert_eq!(cwver(&["cmp", "-q", "21w45.3", "21w45.1"]).1, "1\n");
+        assert_eq!(cwver(&["cmp", "21w45.1", "21w45.3", "--before"]), ordered("lt\n"));
+        assert_eq!(cwver(&["cmp", "21w45.3", "21w45.1", "--after"]), ordered("gt\n"));
+        assert_eq!(cwver(&["cmp", "21w45.3", "2021-11-10", "--same"]), ordered("eq\n"));
+        assert_eq!(
+            cwver(&["cmp", "21w45.1", "21w45.3", "--after"]).0,
+            Err("21w45.1 is not later than 21w45.3".to_string())
+        );
+        assert_eq!(
+            cwver(&["cmp", "21w45.1", "21w45.3", "--same"]).0,
+            Err("21w45.1 is not the same as 21w45.3".to_string())
+        );
+        assert_eq!(
+            cwver(&["cmp", "21w45.3", "21w45.1", "--before"]).0,
+            Err("21w45.3 is not earlier than 21w45.1".to_string())
+        );
+    }
+
+    /// A holiday file for `--holidays` with the given lines, named after the test using it.
+    #[cfg(test)]
+    fn holidays_file(test: &str, lines: &[&str]) -> String {
+        let path = env::temp_dir().join(format!("cwver-test-{}-{}.txt", test, std::process::id()));
+        fs::write(&path, lines.join("\n")).unwrap();
+        path.display().to_string()
+    }
+
+    #[test]
+    fn test_next_and_prev() {
+        let succeeded = |output: &str| (Ok(ExitCode::SUCCESS), output.to_string());
+        assert_eq!(
+            cwver(&["next", "21w45.1", "--workdays", "1,3"]),
+            succeeded("Next workday after 21w45.1 = 21w45.3 (2021-11-10)\n")
+        );
+        assert_eq!(cwver(&["prev", "-q", "21w45.1", "-w", "2,4"]), succeeded("21w44.4\n"));
+        assert_eq!(
+            cwver(&["next", "21w45.6"]),
+            succeeded("Next workday after 21w45.6 = 21w46.1 (2021-11-15)\n")
+        );
+        assert_eq!(
+            cwver(&["prev", "21w45.7"]),
+            succeeded("Previous workday before 21w45.7 = 21w45.5 (2021-11-12)\n")
+        );
+        let holidays = holidays_file("next-and-prev", &["21w45.2 company day", "2021-11-10"]);
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "next", "-q", "21w45.1"]),
+            succeeded("21w45.4\n")
+        );
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "prev", "-q", "21w45.4"]),
+            succeeded("21w45.1\n")
+        );
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "next", "-q", "21w44.7"]),
+            succeeded("21w45.1\n")
+        );
+        fs::remove_file(holidays).unwrap();
+    }
+
+    #[test]
+    fn test_add() {
+        let succeeded = |output: &str| (Ok(ExitCode::SUCCESS), output.to_string());
+        assert_eq!(
+            cwver(&["add", "21w45.1", "+5wd"]),
+            succeeded("21w45.1 +5wd = 21w46.1 (2021-11-15)\n")
+        );
+        assert_eq!(cwver(&["add", "-q", "21w45.1", "-2w"]), succeeded("21w43.1\n"));
+        assert_eq!(cwver(&["add", "-q", "21w45.1", "+10d"]), succeeded("21w46.4\n"));
+        assert_eq!(cwver(&["add", "-q", "21w45.5", "+1wd"]), succeeded("21w46.1\n"));
+        let holidays = holidays_file("add", &["21w45.2 company day"]);
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "add", "-q", "21w45.1", "+1wd"]),
+            succeeded("21w45.3\n")
+        );
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "add", "-q", "21w45.3", "-1wd"]),
+            succeeded("21w45.1\n")
+        );
+        fs::remove_file(holidays).unwrap();
+    }
+
+    #[test]
+    fn test_age() {
+        let age = |max: &str| cwver(&["--relative-to", "21w46.1", "age", "21w45.1", "--max-workdays", max]);
+        let output = "21w45.1 (2021-11-08): 7 day(s), 5 workday(s) old\n".to_string();
+        assert_eq!(age("5"), (Ok(ExitCode::SUCCESS), output.clone()));
+        assert_eq!(
+            age("4"),
+            (
+                Err("21w45.1 is 5 workday(s) old, more than --max-workdays 4".to_string()),
+                output
+            )
+        );
+        assert_eq!(
+            cwver(&[
+                "--relative-to",
+                "21w45.1",
+                "-q",
+                "age",
+                "21w46.1",
+                "--max-workdays",
+                "0"
+            ]),
+            (Ok(ExitCode::SUCCESS), "-7d -5wd\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_week_workdays_only() {
+        let holidays = holidays_file("week", &["24w07.3 carnival"]);
+        let week = |args: &[&str]| cwver(&[&["--holidays", &holidays, "week", "24w07"], args].concat());
+        assert_eq!(
+            week(&["--workdays-only"]).1,
+            [
+                "24w07.1 (2024-02-12): workday",
+                "24w07.2 (2024-02-13): workday",
+                "24w07.4 (2024-02-15): workday",
+                "24w07.5 (2024-02-16): workday\n",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            week(&["--workdays-only", "-q", "-w", "1,2,3,6"]),
+            (Ok(ExitCode::SUCCESS), "24w07.1\n24w07.2\n24w07.6\n".to_string())
+        );
+        assert_eq!(
+            week(&[]).1.lines().nth(2),
+            Some("24w07.3 (2024-02-14): holiday (carnival)")
+        );
+        fs::remove_file(holidays).unwrap();
+    }
+
+    #[test]
+    fn test_week_bounds() {
+        let succeeded = |output: &str| (Ok(ExitCode::SUCCESS), output.to_string());
+        assert_eq!(
+            cwver(&["week-bounds", "24w07"]),
+            succeeded("24w07: 2024-02-12..2024-02-18\n")
+        );
+        assert_eq!(
+            cwver(&["week-bounds", "-q", "24w07"]),
+            succeeded("2024-02-12 2024-02-18\n")
+        );
+        let holidays = holidays_file("week-bounds", &["24w07.1", "2024-02-16"]);
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "week-bounds", "24w07", "--with-workdays"]),
+            succeeded("24w07: 2024-02-12..2024-02-18, workdays 2024-02-13..2024-02-15\n")
+        );
+        assert_eq!(
+            cwver(&["--holidays", &holidays, "week-bounds", "-q", "24w07", "--with-workdays"]),
+            succeeded("2024-02-12 2024-02-18 2024-02-13 2024-02-15\n")
+        );
+        fs::remove_file(holidays).unwrap();
+    }
+
+    #[test]
+    fn test_latest_and_earliest() {
+        let versions = ["2022w01.2", "21w52.7", "2021-12-31", "22w01.1", "2021w52.6"];
+        let selected = |command: &str| cwver(&[&[command][..], &versions].concat());
+        assert_eq!(selected("latest"), (Ok(ExitCode::SUCCESS), "2022w01.2\n".to_string()));
+        assert_eq!(
+            selected("earliest"),
+            (Ok(ExitCode::SUCCESS), "2021-12-31\n".to_string())
+        );
+        assert_eq!(
+            cwver(&["latest", "99w52.7", "2000w01.1", "00w01.2"]).1,
+            "99w52.7\n".to_string()
+        );
+        assert_eq!(
+            cwver(&["--pivot", "1970", "latest", "99w52.7", "2000w01.1", "00w01.2"]).1,
+            "00w01.2\n".to_string()
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        let style = Style::default();
+        assert_eq!(convert("24w07.3", &style), Ok("24w07.3 = 2024-02-14".to_string()));
+        assert_eq!(convert("2024-02-14", &style), Ok("2024-02-14 = 24w07.3".to_string()));
+    }
+
+    #[test]
+    fn test_convert_csv() {
+        let text = "name,build_version\nfirst,21w45.7\nsecond,\nthird,21w53.1\n";
+        let strings = |fields: &[&str]| fields.iter().map(|field| field.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            convert_csv(text, "build_version", "build_date", &Style::default()),
+            Ok((
+                vec![
+                    strings(&["name", "build_version", "build_date"]),
+                    strings(&["first", "21w45.7", "2021-11-14"]),
+                    strings(&["second", "", ""]),
+                    strings(&["third", "21w53.1", ""]),
+                ],
+                vec!["record 4: failed to calculate date of 21w53.1".to_string()]
+            ))
+        );
+        assert_eq!(
+            convert_csv("build\n2021-11-14\n", "build", "build", &Style::default()).map(|(records, _)| records),
+            Ok(vec![strings(&["build"]), strings(&["21w45.7"])])
+        );
+        assert_eq!(
+            convert_csv(text, "version", "date", &Style::default()),
+            Err("the CSV lacks the column version, it has name, build_version".to_string())
+        );
+    }
+
+    #[test]
+    fn test_convert_lines() {
+        let mut output = vec![];
+        assert_eq!(
+            convert_lines(
+                "21w01.1\n\n2021-03-14\n".as_bytes(),
+                &mut output,
+                io::sink(),
+                &Style::default()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "21w01.1 = 2021-01-04\n2021-03-14 = 21w10.7\n".to_string()
+        );
+        let (mut output, mut errors) = (vec![], vec![]);
+        assert_eq!(
+            convert_lines(
+                "21w01.1\nfoo\n21w01.2\n".as_bytes(),
+                &mut output,
+                &mut errors,
+                &Style::default()
+            ),
+            Err("1 of 3 line(s) failed to convert".to_string())
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "21w01.1 = 2021-01-04\n21w01.2 = 2021-01-05\n".to_string()
+        );
+        assert_eq!(
+            String::from_utf8(errors).unwrap(),
+            "line 2: Failed to parse ISO date foo\n".to_string()
+        );
+        let mut output = vec![];
+        assert!(convert_lines(
+            "21w01.1\nfoo\n".as_bytes(),
+            &mut output,
+            io::sink(),
+            &Style {
+                format: Format::Jsonl,
+                ..Style::default()
+            }
+        )
+        .is_err());
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "{\"cwver\":\"21w01.1\",\"date\":\"2021-01-04\",\"day\":1,\"input\":\"21w01.1\",\"week\":1,\"year\":2021}\n",
+                "{\"error\":\"Failed to parse ISO date foo\",\"input\":\"foo\",\"line\":2}\n"
+            )
+        );
+
+        let mut output = vec![];
+        assert_eq!(
+            convert_lines(
+                "21w01.1\n2021-03-14\n".as_bytes(),
+                &mut output,
+                io::sink(),
+                &Style {
+                    quiet: true,
+                    ..Style::default()
+                }
+            ),
+            Ok(())
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), "2021-01-04\n21w10.7\n".to_string());
+    }
+
+    #[test]
+    fn test_sort() {
+        let inputs: Vec<String> = ["nightly-21w01.1", "20w53.5", "2021-01-04", "20w53.5T08:00"]
+            .iter()
+            .map(|input| input.to_string())
+            .collect();
+        fn names(sorted: Vec<(&str, NaiveDateTime)>) -> Vec<&str> {
+            sorted.into_iter().map(|(input, _)| input).collect()
+        }
+        let schema = Schema::default();
+        assert_eq!(
+            sort(&inputs, &schema, false, false).map(names),
+            Ok(vec!["20w53.5", "20w53.5T08:00", "nightly-21w01.1", "2021-01-04"])
+        );
+        assert_eq!(
+            sort(&inputs, &schema, true, true).map(names),
+            Ok(vec!["nightly-21w01.1", "20w53.5T08:00", "20w53.5"])
+        );
+        let strict = Schema {
+            strict: true,
+            ..Schema::default()
+        };
+        assert_eq!(
+            sort(&inputs, &strict, false, false).map(names),
+            Err("failed to parse nightly-21w01.1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let inputs: Vec<String> = ["24w7.1", "24w08.1", "2024-02-12", "24W07-1"]
+            .iter()
+            .map(|input| input.to_string())
+            .collect();
+        assert_eq!(
+            dedup(&inputs, &Schema::default()),
+            Ok(vec![
+                (
+                    NaiveDate::from_ymd(2024, 2, 12),
+                    vec!["24w7.1", "2024-02-12", "24W07-1"]
+                ),
+                (NaiveDate::from_ymd(2024, 2, 19), vec!["24w08.1"]),
+            ])
+        );
+        assert_eq!(
+            dedup(&["24w7".to_string()], &Schema::default()),
+            Err("failed to parse 24w7".to_string())
+        );
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = env::temp_dir().join(format!("cwver-scan-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "nightly-24w07.3.tar.gz",
+            "nightly-24w07.1.tar.gz",
+            "nightly-24w07.1.zip",
+            "readme.txt",
+        ]
+        .iter()
+        {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        assert_eq!(
+            scan(&dir, "*.tar.gz", &Schema::default()),
+            Ok(vec![
+                (dir.join("nightly-24w07.1.tar.gz"), NaiveDate::from_ymd(2024, 2, 12)),
+                (dir.join("nightly-24w07.3.tar.gz"), NaiveDate::from_ymd(2024, 2, 14)),
+            ])
+        );
+        assert_eq!(scan(&dir, "*", &Schema::default()).map(|found| found.len()), Ok(3));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rename() {
+        let schema = Schema::default();
+        assert_eq!(
+            rename("nightly-24w07.1.tar.gz", &schema, true),
+            "nightly-2024-02-12.tar.gz".to_string()
+        );
+        assert_eq!(
+            rename("nightly-2024-02-12.tar.gz", &schema, false),
+            "nightly-24w07.1.tar.gz".to_string()
+        );
+        assert_eq!(rename("24w54.1.zip", &schema, true), "24w54.1.zip".to_string());
+
+        let dir = env::temp_dir().join(format!("cwver-rename-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["24w07.1.zip", "2024-02-12.zip", "24w07.2.zip", "readme.txt"].iter() {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        assert_eq!(
+            renames(&dir, &schema, true),
+            Ok(vec![(dir.join("24w07.2.zip"), dir.join("2024-02-13.zip"))])
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_lint() {
+        let dir = env::temp_dir().join(format!("cwver-lint-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("nightly-24w7.1.txt");
+        fs::write(&path, "24w07.1 is fine\n24w53.1 and 24w07.0 are not\n").unwrap();
+        let reasons: Vec<(Option<usize>, String, String)> = lint(&path, &Schema::default())
+            .into_iter()
+            .map(|issue| (issue.line, issue.input, issue.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    None,
+                    "24w7.1".to_string(),
+                    "week 7 must be zero-padded to 07".to_string()
+                ),
+                (
+                    Some(2),
+                    "24w53.1".to_string(),
+                    "week 53 does not exist in 2024, which has 52 weeks".to_string()
+                ),
+                (
+                    Some(2),
+                    "24w07.0".to_string(),
+                    "day of week 0 is out of range, expected 1 (Monday) till 7 (Sunday)".to_string()
+                ),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_grep() {
+        let text = "built 24w07.1 and 24w07.2\nno version\n24w54.1 is invalid, 2024w08.3 not\n";
+        assert_eq!(
+            grep(text, &Schema::default()),
+            vec![
+                (1, "24w07.1", NaiveDate::from_ymd(2024, 2, 12)),
+                (1, "24w07.2", NaiveDate::from_ymd(2024, 2, 13)),
+                (3, "2024w08.3", NaiveDate::from_ymd(2024, 2, 21)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotate() {
+        let schema = Schema::default();
+        assert_eq!(
+            annotate("Fixed in 21w45.7, broken since 21w40.1.", &schema, false),
+            "Fixed in 21w45.7 (2021-11-14), broken since 21w40.1 (2021-10-04).".to_string()
+        );
+        assert_eq!(
+            annotate("Fixed in 21w45.7, broken since 21w40.1.", &schema, true),
+            "Fixed in 2021-11-14, broken since 2021-10-04.".to_string()
+        );
+        assert_eq!(
+            annotate("21w54.1 and 21w7.1 stay", &schema, true),
+            "21w54.1 and 21w7.1 stay".to_string()
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let schema = Schema::default();
+        assert_eq!(
+            normalize("24w7.1, 24W07-1 and 2024w07_1", &schema),
+            Ok("24w07.1, 24w07.1 and 24w07.1".to_string())
+        );
+        assert_eq!(
+            normalize("nightly-24w7.3.tar.gz", &schema),
+            Ok("nightly-24w07.3.tar.gz".to_string())
+        );
+
+        let mut output = vec![];
+        assert_eq!(
+            rewrite_lines("# Changelog\n\n  * 24w7.1: fixed\n".as_bytes(), &mut output, |line| {
+                normalize(line, &schema)
+            }),
+            Ok(())
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "# Changelog\n\n  * 24w07.1: fixed\n".to_string()
+        );
+        assert_eq!(
+            rewrite_lines("24w7.1\n24w54.1\n".as_bytes(), io::sink(), |line| normalize(
+                line, &schema
+            )),
+            Err("line 2: failed to calculate date of 24w54.1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_value_parsers() {
+        let date = |value| date_arg(false)(value).map_err(|e| e.to_string());
+        assert_eq!(date("21w53.1"), Ok("21w53.1".to_string()));
+        assert_eq!(date("2021-11-10T14:30"), Ok("2021-11-10T14:30".to_string()));
+        assert_eq!(date("21w45"), Ok("21w45".to_string()));
+        assert_eq!(date("21w5.1"), Err("failed to parse 21w5.1".to_string()));
+        assert_eq!(date_arg(true)("B21W45-7").unwrap(), "B21W45-7".to_string());
+        assert_eq!(number::<u32>("-1"), Err("-1 is not a valid number".to_string()));
+        assert_eq!(
+            workdays_to_hashset("1,8").map_err(|e| e.to_string()),
+            Err("given workday 8 not in range [1-7]".to_string())
+        );
+        assert_eq!(slot_arg(false)("21w45.7b").unwrap(), "21w45.7b".to_string());
+        assert_eq!(
+            slots_number("0"),
+            Err("0 slots per day are not supported, expected 1 to 26".to_string())
+        );
+        assert_eq!(
+            week_syntax("24w7.1"),
+            Err("24w7.1 is not a week like 24w07".to_string())
+        );
+        assert_eq!(boolean("maybe"), Err("maybe is neither true nor false".to_string()));
+    }
+
+    #[test]
+    fn test_bisect_slot() {
+        let calendar = &Calendar::new(HashSet::from(vec![1, 2, 3, 4, 5]));
+        let schema = &Schema::default();
+        let slot = |day, index| Slot {
+            date: NaiveDate::from_ymd(2021, 11, day),
+            index,
+        };
+        let endpoint =
+            |endpoint, till| bisect_endpoint(endpoint, till, 2, schema).map(|e| bisect_slot(calendar, e, till));
+        assert_eq!(endpoint("21w45.3b", false), Ok(slot(10, 1)));
+        assert_eq!(endpoint("21w45.3", false), Ok(slot(10, 0)));
+        assert_eq!(endpoint("21w45", true), Ok(slot(12, 1)));
+        assert_eq!(
+            endpoint("21w45.3c", true),
+            Err("21w45.3c is build 3 of its day, but there are only 2 build(s) per day, pass --slots 3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_verbose_version_requested() {
+        let requested = |args: &[&str]| verbose_version_requested(&args.iter().map(OsString::from).collect::<Vec<_>>());
+        assert!(requested(&["cwver", "--version", "--verbose"]));
+        assert!(requested(&["cwver", "-vv", "-V"]));
+        assert!(!requested(&["cwver", "--version"]));
+        assert!(!requested(&["cwver", "-v", "today"]));
+        assert!(!requested(&["cwver", "-v", "--", "--version"]));
+    }
+
+    #[test]
+    fn test_config_path() {
+        let path = |args: &[&str]| config_path(&args.iter().map(OsString::from).collect::<Vec<_>>());
+        assert_eq!(
+            path(&["cwver", "--config", "ci.toml", "today"]),
+            Some(PathBuf::from("ci.toml"))
+        );
+        assert_eq!(
+            path(&["cwver", "today", "--config=ci.toml"]),
+            Some(PathBuf::from("ci.toml"))
+        );
+        assert_eq!(path(&["cwver", "--configuration", "today"]), None);
+    }
+
+    #[test]
+    fn test_resolve_subcommand() {
+        let aliases = vec![
+            ("b".to_string(), "bisect".to_string()),
+            ("sel".to_string(), "selftest".to_string()),
+        ];
+        let resolve = |args: &[&str]| {
+            resolve_subcommand(args.iter().map(OsString::from).collect(), &aliases).map(|args| {
+                args.into_iter()
+                    .map(|arg| arg.into_string().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            resolve(&["cwver", "b", "21w01.1"]),
+            Ok(vec!["cwver".into(), "bisect".into(), "21w01.1".into()])
+        );
+        assert_eq!(
+            resolve(&["cwver", "-v", "--profile", "b", "conv"]),
+            Ok(vec![
+                "cwver".into(),
+                "-v".into(),
+                "--profile".into(),
+                "b".into(),
+                "convert".into()
+            ])
+        );
+        assert_eq!(
+            resolve(&["cwver", "--profile=b", "sel"]),
+            Ok(vec!["cwver".into(), "--profile=b".into(), "selftest".into()])
+        );
+        assert_eq!(
+            resolve(&["cwver", "--locale", "de", "sel"]),
+            Ok(vec!["cwver".into(), "--locale".into(), "de".into(), "selftest".into()])
+        );
+        assert_eq!(
+            resolve(&["cwver", "--config", "b", "sel"]),
+            Ok(vec!["cwver".into(), "--config".into(), "b".into(), "selftest".into()])
+        );
+        assert_eq!(resolve(&["cwver", "config"]), Ok(vec!["cwver".into(), "config".into()]));
+        assert_eq!(resolve(&["cwver"]), Ok(vec!["cwver".into()]));
+        assert_eq!(
+            resolve(&["cwver", "frobnicate"]),
+            Ok(vec!["cwver".into(), "frobnicate".into()])
+        );
+        assert_eq!(
+            resolve(&["cwver", "co"]),
+            Err("ambiguous subcommand co, could be compare, config, convert, countdown".to_string())
+        );
+    }
+}
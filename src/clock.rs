@@ -0,0 +1,143 @@
+use crate::parse::iso_str_to_date;
+use chrono::{Local, NaiveDate, NaiveDateTime, Utc};
+#[cfg(feature = "timezone")]
+use chrono_tz::Tz;
+use std::env;
+use tracing::info;
+
+/// The timezone today's date is determined in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    Local,
+    Utc,
+    #[cfg(feature = "timezone")]
+    Named(Tz),
+}
+
+impl Timezone {
+    /// `local`, `UTC` or an IANA timezone name like `Europe/Berlin`.
+    pub fn parse(name: &str) -> Result<Timezone, String> {
+        match name {
+            "local" => Ok(Timezone::Local),
+            "UTC" | "utc" => Ok(Timezone::Utc),
+            _ => named_timezone(name),
+        }
+    }
+}
+
+#[cfg(feature = "timezone")]
+fn named_timezone(name: &str) -> Result<Timezone, String> {
+    name.parse::<Tz>()
+        .map(Timezone::Named)
+        .map_err(|_| format!("unknown timezone {}", name))
+}
+
+#[cfg(not(feature = "timezone"))]
+fn named_timezone(name: &str) -> Result<Timezone, String> {
+    Err(format!(
+        "timezone {} requires the timezone feature, only local and UTC are available",
+        name
+    ))
+}
+
+/// Today's date in the given timezone. In deterministic mode any dependency on the wall
+/// clock is an error, so that invocations inside hermetic builds are reproducible. See `pinned`
+/// for replacing the wall clock.
+pub fn today(deterministic: bool, timezone: Timezone) -> Result<NaiveDate, String> {
+    now(deterministic, timezone).map(|now| now.date())
+}
+
+/// The wall clock time in the given timezone, see `today`.
+pub fn now(deterministic: bool, timezone: Timezone) -> Result<NaiveDateTime, String> {
+    if let Some(now) = pinned(env::var("CWVER_TODAY").ok(), env::var("SOURCE_DATE_EPOCH").ok())? {
+        info!(%now, "wall clock pinned by the environment");
+        return Ok(now);
+    }
+    if deterministic {
+        return Err("today's date depends on the wall clock and timezone, which --deterministic forbids".to_string());
+    }
+    let now = Utc::now();
+    Ok(match timezone {
+        Timezone::Local => now.with_timezone(&Local).naive_local(),
+        Timezone::Utc => now.naive_utc(),
+        #[cfg(feature = "timezone")]
+        Timezone::Named(tz) => now.with_timezone(&tz).naive_local(),
+    })
+}
+
+/// The time pinned for reproducible builds and tests instead of the wall clock: `CWVER_TODAY`, an
+/// ISO date taken at the start of the day, wins over `SOURCE_DATE_EPOCH`, seconds since 1970-01-01
+/// taken in UTC as the reproducible builds specification asks for. Neither depends on the timezone.
+fn pinned(today: Option<String>, source_date_epoch: Option<String>) -> Result<Option<NaiveDateTime>, String> {
+    if let Some(today) = today {
+        let date = iso_str_to_date(&today).map_err(|e| format!("CWVER_TODAY: {}", e))?;
+        return Ok(Some(date.and_hms(0, 0, 0)));
+    }
+    match source_date_epoch {
+        Some(epoch) => epoch
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|seconds| NaiveDateTime::from_timestamp_opt(seconds, 0))
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "SOURCE_DATE_EPOCH: {} is not a number of seconds since 1970-01-01",
+                    epoch
+                )
+            }),
+        None => Ok(None),
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_today() {
+        assert!(today(false, Timezone::Local).is_ok());
+        assert_eq!(
+            today(true, Timezone::Utc),
+            Err("today's date depends on the wall clock and timezone, which --deterministic forbids".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pinned() {
+        let var = |value: &str| Some(value.to_string());
+        assert_eq!(pinned(None, None), Ok(None));
+        assert_eq!(
+            pinned(var("2024-02-14"), var("0")),
+            Ok(Some(NaiveDate::from_ymd(2024, 2, 14).and_hms(0, 0, 0)))
+        );
+        assert_eq!(
+            pinned(None, var("1707911999")),
+            Ok(Some(NaiveDate::from_ymd(2024, 2, 14).and_hms(11, 59, 59)))
+        );
+        assert_eq!(
+            pinned(var("24w07.3"), None),
+            Err("CWVER_TODAY: Failed to parse ISO date 24w07.3".to_string())
+        );
+        assert_eq!(
+            pinned(None, var("yesterday")),
+            Err("SOURCE_DATE_EPOCH: yesterday is not a number of seconds since 1970-01-01".to_string())
+        );
+    }
+
+    #[test]
+    fn test_timezone() {
+        assert_eq!(Timezone::parse("UTC"), Ok(Timezone::Utc));
+        assert_eq!(Timezone::parse("local"), Ok(Timezone::Local));
+        #[cfg(feature = "timezone")]
+        assert_eq!(
+            Timezone::parse("Europe/Berlin"),
+            Ok(Timezone::Named(chrono_tz::Europe::Berlin))
+        );
+        #[cfg(feature = "timezone")]
+        assert_eq!(
+            Timezone::parse("Europe/Bärlin"),
+            Err("unknown timezone Europe/Bärlin".to_string())
+        );
+    }
+}
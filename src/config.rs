@@ -0,0 +1,974 @@
+use crate::clock::Timezone;
+use crate::holidays;
+use crate::locale::Locale;
+use crate::output::Format;
+use crate::parse::workdays_to_hashset;
+use crate::pattern::Pattern;
+use chrono::Weekday;
+use directories::ProjectDirs;
+use im::HashSet;
+use num::traits::FromPrimitive;
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use tracing::{info, warn};
+
+const USER_CONFIG: &str = "config.toml";
+pub const PROJECT_CONFIG: &str = ".cwver.toml";
+const PROFILES: &str = "profile";
+const ALIASES: &str = "aliases";
+const MILESTONES: &str = "milestones";
+const RANGES: &str = "ranges";
+
+pub const DEFAULT_WORKDAYS: &str = "1,2,3,4,5";
+pub const DEFAULT_HOLIDAYS_ENDPOINT: &str = "https://date.nager.at";
+
+/// A configuration option and the environment variable overriding it.
+pub struct Key {
+    pub name: &'static str,
+    pub env: &'static str,
+    pub default: Option<&'static str>,
+}
+
+pub const KEYS: &[Key] = &[
+    Key {
+        name: "workdays",
+        env: "CWVER_WORKDAYS",
+        default: Some(DEFAULT_WORKDAYS),
+    },
+    Key {
+        name: "holidays",
+        env: "CWVER_HOLIDAYS",
+        default: None,
+    },
+    Key {
+        name: "holidays_endpoint",
+        env: "CWVER_HOLIDAYS_ENDPOINT",
+        default: Some(DEFAULT_HOLIDAYS_ENDPOINT),
+    },
+    Key {
+        name: "format",
+        env: "CWVER_OUTPUT",
+        default: Some("plain"),
+    },
+    Key {
+        name: "quiet",
+        env: "CWVER_QUIET",
+        default: None,
+    },
+    Key {
+        name: "locale",
+        env: "CWVER_LOCALE",
+        default: None,
+    },
+    Key {
+        name: "timezone",
+        env: "CWVER_TIMEZONE",
+        default: Some("local"),
+    },
+    Key {
+        name: "first_weekday",
+        env: "CWVER_FIRST_WEEKDAY",
+        default: Some("1"),
+    },
+    Key {
+        name: "pivot",
+        env: "CWVER_PIVOT",
+        default: Some("2000"),
+    },
+    Key {
+        name: "pattern",
+        env: "CWVER_PATTERN",
+        default: None,
+    },
+    Key {
+        name: "default",
+        env: "CWVER_DEFAULT",
+        default: Some("today"),
+    },
+];
+
+pub fn key(name: &str) -> Result<&'static Key, String> {
+    KEYS.iter()
+        .find(|key| key.name == name)
+        .ok_or_else(|| format!("unknown configuration key {}", name))
+}
+
+/// A single parsed configuration file.
+#[derive(Debug)]
+pub struct Layer {
+    path: PathBuf,
+    table: Table,
+}
+
+impl Layer {
+    pub fn parse(path: &Path, content: &str) -> Result<Layer, String> {
+        let table = content
+            .parse::<Table>()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        match table.get(PROFILES) {
+            Some(Value::Table(profiles)) => {
+                if let Some((name, _)) = profiles.iter().find(|(_, profile)| !profile.is_table()) {
+                    return Err(format!("{}: {}.{} must be a table", path.display(), PROFILES, name));
+                }
+            }
+            Some(_) => return Err(format!("{}: {} must be a table", path.display(), PROFILES)),
+            None => {}
+        }
+        for name in [ALIASES, MILESTONES, RANGES] {
+            match table.get(name) {
+                Some(Value::Table(entries)) => {
+                    if let Some((entry, _)) = entries.iter().find(|(_, value)| !value.is_str()) {
+                        return Err(format!("{}: {}.{} must be a string", path.display(), name, entry));
+                    }
+                }
+                Some(_) => return Err(format!("{}: {} must be a table", path.display(), name)),
+                None => {}
+            }
+        }
+        Ok(Layer {
+            path: path.to_path_buf(),
+            table,
+        })
+    }
+
+    fn profile(&self, name: &str) -> Option<&Table> {
+        self.table.get(PROFILES)?.get(name)?.as_table()
+    }
+
+    fn origin(&self, profile: Option<&String>) -> Origin {
+        Origin {
+            path: self.path.clone(),
+            profile: profile.cloned(),
+        }
+    }
+
+    fn warn_about_unknown_keys(&self) {
+        warn_about_unknown_keys(&self.path, "", &self.table);
+        for (name, profile) in self.table.get(PROFILES).and_then(Value::as_table).into_iter().flatten() {
+            if let Some(profile) = profile.as_table() {
+                warn_about_unknown_keys(&self.path, &format!("{}.{}.", PROFILES, name), profile);
+            }
+        }
+    }
+
+    fn read(path: &Path) -> Result<Option<Layer>, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Layer::parse(path, &content).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("failed to read {}: {}", path.display(), e)),
+        }
+    }
+}
+
+fn warn_about_unknown_keys(path: &Path, prefix: &str, table: &Table) {
+    let is_known = |name: &str| {
+        KEYS.iter().any(|key| key.name == name)
+            || (prefix.is_empty() && [PROFILES, ALIASES, MILESTONES, RANGES].contains(&name))
+    };
+    for key in table.keys().filter(|key| !is_known(key)) {
+        warn!("{}: ignoring unknown key {}{}", path.display(), prefix, key);
+    }
+}
+
+/// Where a configuration value was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Origin {
+    path: PathBuf,
+    profile: Option<String>,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.profile {
+            Some(profile) => write!(f, "{} [{}.{}]", self.path.display(), PROFILES, profile),
+            None => write!(f, "{}", self.path.display()),
+        }
+    }
+}
+
+/// Where an effective configuration value comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    Env(&'static str),
+    File(Origin),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::Env(var) => write!(f, "environment variable {}", var),
+            Source::File(origin) => write!(f, "{}", origin),
+        }
+    }
+}
+
+/// All configuration files found, in ascending order of precedence, and the selected profile.
+#[derive(Debug, Default)]
+pub struct Config {
+    layers: Vec<Layer>,
+    profile: Option<String>,
+    /// Looks up the `CWVER_*` variables overriding the files, instead of the process environment.
+    env: Option<fn(&str) -> Option<String>>,
+}
+
+impl Config {
+    /// Reads the user configuration and the nearest project configuration (`.cwver.toml` in the
+    /// current directory or any of its parents). Settings of the project configuration win.
+    ///
+    /// An explicitly given configuration file replaces both of them and has to exist.
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        if let Some(path) = path {
+            let layer =
+                Layer::read(path)?.ok_or_else(|| format!("configuration file {} does not exist", path.display()))?;
+            return Ok(Config::from_layers(vec![layer]));
+        }
+        let mut layers = vec![];
+        for path in user_config_path().into_iter().chain(project_config_path()) {
+            layers.extend(Layer::read(&path)?);
+        }
+        Ok(Config::from_layers(layers))
+    }
+
+    pub fn from_layers(layers: Vec<Layer>) -> Config {
+        Config {
+            layers,
+            profile: None,
+            env: None,
+        }
+    }
+
+    /// The configuration of the files alone, ignoring the `CWVER_*` variables.
+    fn without_env(self) -> Config {
+        Config {
+            env: Some(|_| None),
+            ..self
+        }
+    }
+
+    /// Settings of the selected `profile` take precedence over all settings outside of profiles.
+    ///
+    /// The configuration has to be loaded before the command line is parsed, so this is also
+    /// where the configuration files are reported once logging is set up.
+    pub fn select_profile(self, profile: Option<&str>) -> Result<Config, String> {
+        for layer in &self.layers {
+            info!("read configuration from {}", layer.path.display());
+            layer.warn_about_unknown_keys();
+        }
+        if let Some(profile) = profile {
+            if !self.layers.iter().any(|layer| layer.profile(profile).is_some()) {
+                return Err(format!("profile {} is not defined in any configuration file", profile));
+            }
+            info!("using profile {}", profile);
+        }
+        Ok(Config {
+            profile: profile.map(str::to_string),
+            ..self
+        })
+    }
+
+    /// Subcommand aliases defined in the `[aliases]` tables, which don't depend on the profile.
+    pub fn aliases(&self) -> Vec<(String, String)> {
+        self.string_table(ALIASES)
+    }
+
+    /// Named milestones like `code-freeze = "22w05.5"` defined in the `[milestones]` tables, which
+    /// don't depend on the profile either. The dates are left to be parsed by the caller.
+    pub fn milestones(&self) -> Vec<(String, String)> {
+        self.string_table(MILESTONES)
+    }
+
+    /// Named ranges like `release-2021-4 = "21w40.1..21w48.5"` defined in the `[ranges]` tables,
+    /// referred to as `@release-2021-4` wherever a range is expected.
+    pub fn ranges(&self) -> Vec<(String, String)> {
+        self.string_table(RANGES)
+    }
+
+    /// The entries of a table of strings merged over all configuration files, files of higher
+    /// precedence redefining entries.
+    fn string_table(&self, name: &str) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = vec![];
+        for layer in &self.layers {
+            for (entry, value) in layer.table.get(name).and_then(Value::as_table).into_iter().flatten() {
+                entries.retain(|(defined, _)| defined != entry);
+                entries.push((entry.clone(), value.as_str().unwrap_or_default().to_string()));
+            }
+        }
+        entries
+    }
+
+    /// The value of `key` from the configuration file with the highest precedence defining it.
+    fn get(&self, key: &str) -> Option<(&Value, Origin)> {
+        let in_profile = self.profile.as_ref().and_then(|profile| {
+            self.layers.iter().rev().find_map(|layer| {
+                let value = layer.profile(profile)?.get(key)?;
+                Some((value, layer.origin(Some(profile))))
+            })
+        });
+        in_profile.or_else(|| {
+            self.layers
+                .iter()
+                .rev()
+                .find_map(|layer| layer.table.get(key).map(|value| (value, layer.origin(None))))
+        })
+    }
+
+    /// The value of `key` taking environment variables, configuration files and defaults into account.
+    pub fn effective(&self, key: &Key) -> Option<(Value, Source)> {
+        let env_value = match self.env {
+            Some(env) => env(key.env),
+            None => env::var(key.env).ok(),
+        };
+        self.effective_with_env(key, env_value)
+    }
+
+    fn effective_with_env(&self, key: &Key, env_value: Option<String>) -> Option<(Value, Source)> {
+        match (env_value, self.get(key.name)) {
+            (Some(value), _) => Some((Value::String(value), Source::Env(key.env))),
+            (None, Some((value, origin))) => Some((value.clone(), Source::File(origin))),
+            (None, None) => key
+                .default
+                .map(|default| (Value::String(default.to_string()), Source::Default)),
+        }
+    }
+
+    /// Every setting of every configuration file, including all profiles, in ascending order of
+    /// precedence. Settings of profiles are named `profile.<name>.<key>`.
+    pub fn settings(&self) -> Vec<(String, &Value, Origin)> {
+        let mut settings = vec![];
+        for layer in &self.layers {
+            for (key, value) in layer.table.iter().filter(|(key, _)| key.as_str() != PROFILES) {
+                match value.as_table() {
+                    Some(table) => {
+                        for (name, nested) in table {
+                            settings.push((format!("{}.{}", key, name), nested, layer.origin(None)));
+                        }
+                    }
+                    None => settings.push((key.clone(), value, layer.origin(None))),
+                }
+            }
+            let profiles = layer
+                .table
+                .get(PROFILES)
+                .and_then(Value::as_table)
+                .into_iter()
+                .flatten();
+            for (profile, table) in profiles {
+                for (key, value) in table.as_table().into_iter().flatten() {
+                    let name = format!("{}.{}.{}", PROFILES, profile, key);
+                    settings.push((name, value, layer.origin(Some(profile))));
+                }
+            }
+        }
+        settings
+    }
+
+    /// Checks all settings for valid values.
+    pub fn validate(&self) -> Result<(), String> {
+        self.workdays()?;
+        self.holidays()?;
+        self.holidays_endpoint()?;
+        self.default_command()?;
+        self.quiet()?;
+        self.locale()?;
+        self.timezone()?;
+        self.first_weekday()?;
+        self.pivot()?;
+        self.pattern()?;
+        Ok(())
+    }
+
+    /// Workdays are given either in the same syntax as `--workdays` or as an array of days.
+    pub fn workdays(&self) -> Result<Option<HashSet<u32>>, String> {
+        let (value, origin) = match self.get("workdays") {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        let workdays_str = match value {
+            Value::String(workdays) => workdays.clone(),
+            Value::Integer(day) => day.to_string(),
+            Value::Array(days) if days.iter().all(Value::is_integer) => {
+                days.iter().map(|day| day.to_string()).collect::<Vec<_>>().join(",")
+            }
+            _ => {
+                return Err(format!(
+                    "{}: workdays must be a string like \"1,2,3,4,5\" or an array like [1, 2, 3, 4, 5]",
+                    origin
+                ))
+            }
+        };
+        workdays_to_hashset(&workdays_str)
+            .map(Some)
+            .map_err(|e| format!("{}: {}", origin, e))
+    }
+
+    /// Holiday sources, e.g. `["country:DE-BY", "~/team.ics"]`. Relative paths refer to the directory
+    /// of the configuration file, the environment variable separates sources by commas.
+    pub fn holidays(&self) -> Result<Vec<holidays::Source>, String> {
+        let (value, source) = match self.effective(key("holidays")?) {
+            Some(found) => found,
+            None => return Ok(vec![]),
+        };
+        let base = match &source {
+            Source::File(origin) => origin.path.parent().map(Path::to_path_buf),
+            _ => None,
+        };
+        let sources: Vec<&str> = match &value {
+            Value::String(sources) if matches!(source, Source::Env(_)) => sources.split(',').collect(),
+            Value::String(source) => vec![source],
+            Value::Array(sources) if sources.iter().all(Value::is_str) => {
+                sources.iter().filter_map(Value::as_str).collect()
+            }
+            _ => {
+                return Err(format!(
+                    "{}: holidays must be an array like [\"country:DE-BY\", \"~/team.ics\"]",
+                    source
+                ))
+            }
+        };
+        sources
+            .into_iter()
+            .map(str::trim)
+            .filter(|holiday_source| !holiday_source.is_empty())
+            .map(|holiday_source| {
+                holidays::Source::parse(holiday_source, base.as_deref()).map_err(|e| format!("{}: {}", source, e))
+            })
+            .collect()
+    }
+
+    /// The nager.date compatible service `online:` holiday sources are fetched from, e.g. a mirror
+    /// inside a company network.
+    pub fn holidays_endpoint(&self) -> Result<String, String> {
+        match self.effective(key("holidays_endpoint")?) {
+            Some((Value::String(endpoint), _)) => Ok(endpoint),
+            Some((_, source)) => Err(format!(
+                "{}: holidays_endpoint must be a URL like \"https://date.nager.at\"",
+                source
+            )),
+            None => Ok(DEFAULT_HOLIDAYS_ENDPOINT.to_string()),
+        }
+    }
+
+    /// The default of `--output`, e.g. so that automation environments get JSON.
+    pub fn format(&self) -> Result<Option<Format>, String> {
+        match self.get("format") {
+            Some((Value::String(name), origin)) => {
+                Format::parse(name).map(Some).map_err(|e| format!("{}: {}", origin, e))
+            }
+            Some((_, origin)) => Err(format!("{}: format must be a string like \"json\"", origin)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn quiet(&self) -> Result<Option<bool>, String> {
+        match self.get("quiet") {
+            Some((Value::Boolean(quiet), _)) => Ok(Some(*quiet)),
+            Some((_, origin)) => Err(format!("{}: quiet must be true or false", origin)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn locale(&self) -> Result<Option<Locale>, String> {
+        match self.get("locale") {
+            Some((Value::String(name), origin)) => {
+                Locale::parse(name).map(Some).map_err(|e| format!("{}: {}", origin, e))
+            }
+            Some((_, origin)) => Err(format!("{}: locale must be a string like \"de\"", origin)),
+            None => Ok(None),
+        }
+    }
+
+    /// The timezone today's date is determined in, e.g. so that CI containers running in UTC and
+    /// engineers in CET agree on the date.
+    pub fn timezone(&self) -> Result<Timezone, String> {
+        match self.effective(key("timezone")?) {
+            Some((Value::String(name), source)) => Timezone::parse(&name).map_err(|e| format!("{}: {}", source, e)),
+            Some((_, source)) => Err(format!("{}: timezone must be a string like \"Europe/Berlin\"", source)),
+            None => Ok(Timezone::Local),
+        }
+    }
+
+    /// The weekday of `.1` for parsing and formatting, 1 = monday till 7 = sunday. Some partner
+    /// formats use `.1` = Sunday.
+    pub fn first_weekday(&self) -> Result<Weekday, String> {
+        let (value, source) = self
+            .effective(key("first_weekday")?)
+            .ok_or("first_weekday is not set")?;
+        let day = match &value {
+            Value::Integer(day) => Some(*day),
+            Value::String(day) => day.trim().parse().ok(),
+            _ => None,
+        };
+        day.filter(|day| (1..=7).contains(day))
+            .and_then(|day| Weekday::from_i64(day - 1))
+            .ok_or_else(|| {
+                format!(
+                    "{}: first_weekday must be a day of week, 1 = monday till 7 = sunday",
+                    source
+                )
+            })
+    }
+
+    /// The first year of the 100 years two-digit years refer to: 2000 (the default) or 1900 for a
+    /// fixed century, any other year like 1970 for a window spanning two centuries.
+    pub fn pivot(&self) -> Result<i32, String> {
+        let (value, source) = self.effective(key("pivot")?).ok_or("pivot is not set")?;
+        let pivot = match &value {
+            Value::Integer(year) => i32::try_from(*year).ok(),
+            Value::String(year) => year.trim().parse().ok(),
+            _ => None,
+        };
+        pivot.ok_or_else(|| format!("{}: pivot must be a year like 1970", source))
+    }
+
+    /// A naming scheme like `B{yy}W{ww}-{d}` for versions named other than `21w45.7`.
+    pub fn pattern(&self) -> Result<Option<Pattern>, String> {
+        match self.get("pattern") {
+            Some((Value::String(pattern), origin)) => {
+                pattern.parse().map(Some).map_err(|e| format!("{}: {}", origin, e))
+            }
+            Some((_, origin)) => Err(format!(
+                "{}: pattern must be a string like \"B{{yy}}W{{ww}}-{{d}}\"",
+                origin
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// The subcommand and its arguments run by a bare `cwver`, given either as command line like
+    /// `"bisect 21w01.1 21w10.1"` or as array of arguments for arguments containing spaces.
+    pub fn default_command(&self) -> Result<Vec<String>, String> {
+        let (value, source) = self.effective(key("default")?).ok_or("default is not set")?;
+        let args = match value {
+            Value::String(command) => command.split_whitespace().map(str::to_string).collect(),
+            Value::Array(args) if args.iter().all(Value::is_str) => {
+                args.iter().filter_map(Value::as_str).map(str::to_string).collect()
+            }
+            _ => return Err(format!("{}: default must be a string or an array of strings", source)),
+        };
+        Ok(args)
+    }
+}
+
+/// Sets `key` to `value` in the configuration file at `path`, which is created if necessary.
+/// The value is taken as TOML literal if it is one (e.g. `[1, 2, 3]`), as plain string otherwise.
+pub fn set(path: &Path, name: &str, value: &str) -> Result<(), String> {
+    let key = key(name)?;
+    let mut table = Layer::read(path)?.map(|layer| layer.table).unwrap_or_default();
+    table.insert(key.name.to_string(), toml_value(value));
+    let content = toml::to_string(&table).map_err(|e| format!("failed to serialize {}: {}", path.display(), e))?;
+    // a broken `CWVER_*` variable isn't the new file's fault
+    Config::from_layers(vec![Layer::parse(path, &content)?])
+        .without_env()
+        .validate()?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {}", dir.display(), e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("failed to write {}: {}", path.display(), e))
+}
+
+fn toml_value(value: &str) -> Value {
+    format!("value = {}", value)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "cwver")
+}
+
+/// `$XDG_CONFIG_HOME/cwver/config.toml` on Linux, `%APPDATA%\cwver\config\config.toml` on Windows and
+/// `~/Library/Application Support/cwver/config.toml` on macOS.
+pub fn user_config_path() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join(USER_CONFIG))
+}
+
+/// `$XDG_CACHE_HOME/cwver` on Linux, `%LOCALAPPDATA%\cwver\cache` on Windows and `~/Library/Caches/cwver` on macOS.
+pub fn cache_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.cache_dir().to_path_buf())
+}
+
+pub fn project_config_path() -> Option<PathBuf> {
+    let cwd = env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG))
+        .find(|path| path.is_file())
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[cfg(test)]
+    fn config(files: &[(&str, &str)]) -> Result<Config, String> {
+        profile_config(files, None)
+    }
+
+    #[cfg(test)]
+    fn profile_config(files: &[(&str, &str)], profile: Option<&str>) -> Result<Config, String> {
+        let layers = files
+            .iter()
+            .map(|(path, content)| Layer::parse(Path::new(path), content))
+            .collect::<Result<_, _>>()?;
+        Config::from_layers(layers).select_profile(profile)
+    }
+
+    #[test]
+    fn test_workdays() {
+        assert_eq!(config(&[]).unwrap().workdays(), Ok(None));
+        assert_eq!(
+            config(&[("user.toml", "workdays = \"1,2,3,4\"")]).unwrap().workdays(),
+            Ok(Some(hashset![1, 2, 3, 4]))
+        );
+        assert_eq!(
+            config(&[("user.toml", "workdays = [6, 7]")]).unwrap().workdays(),
+            Ok(Some(hashset![6, 7]))
+        );
+        assert_eq!(
+            config(&[("user.toml", "workdays = [6, 8]")]).unwrap().workdays(),
+            Err("user.toml: given workday 8 not in range [1-7]".to_string())
+        );
+        assert_eq!(
+            config(&[("user.toml", "workdays = 5")]).unwrap().workdays(),
+            Ok(Some(hashset![5]))
+        );
+        assert_eq!(
+            config(&[("user.toml", "workdays = true")]).unwrap().workdays(),
+            Err("user.toml: workdays must be a string like \"1,2,3,4,5\" or an array like [1, 2, 3, 4, 5]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let config = config(&[
+            ("user.toml", "workdays = \"1,2,3,4\""),
+            ("project.toml", "workdays = \"1,2,3,4,5,6\""),
+        ])
+        .unwrap();
+        assert_eq!(config.workdays(), Ok(Some(hashset![1, 2, 3, 4, 5, 6])));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let files = &[
+            (
+                "user.toml",
+                "workdays = \"1,2,3,4\"\n[profile.automotive]\nworkdays = \"1,2,3\"\n[profile.infotainment]\n",
+            ),
+            ("project.toml", "workdays = \"1,2,3,4,5,6\""),
+        ];
+        assert_eq!(
+            profile_config(files, Some("automotive")).unwrap().workdays(),
+            Ok(Some(hashset![1, 2, 3]))
+        );
+        assert_eq!(
+            profile_config(files, Some("infotainment")).unwrap().workdays(),
+            Ok(Some(hashset![1, 2, 3, 4, 5, 6]))
+        );
+        assert_eq!(
+            profile_config(files, Some("aerospace")).unwrap_err(),
+            "profile aerospace is not defined in any configuration file".to_string()
+        );
+        assert_eq!(
+            profile_config(&[("user.toml", "[profile.automotive]\nworkdays = 1.5")], Some("automotive"))
+                .unwrap()
+                .workdays(),
+            Err("user.toml [profile.automotive]: workdays must be a string like \"1,2,3,4,5\" or an array like [1, 2, 3, 4, 5]".to_string())
+        );
+        assert_eq!(
+            config(&[("user.toml", "profile = 1")]).unwrap_err(),
+            "user.toml: profile must be a table".to_string()
+        );
+    }
+
+    #[test]
+    fn test_aliases() {
+        let aliases = config(&[
+            ("user.toml", "[aliases]\nbi = \"bisect\"\ncv = \"convert\"\n"),
+            ("project.toml", "[aliases]\nbi = \"bisect\"\ncv = \"config\"\n"),
+        ])
+        .unwrap()
+        .aliases();
+        assert_eq!(
+            aliases,
+            vec![
+                ("bi".to_string(), "bisect".to_string()),
+                ("cv".to_string(), "config".to_string())
+            ]
+        );
+        assert_eq!(
+            config(&[("user.toml", "[aliases]\nbi = 1")]).unwrap_err(),
+            "user.toml: aliases.bi must be a string".to_string()
+        );
+    }
+
+    #[test]
+    fn test_milestones() {
+        let milestones = config(&[
+            ("user.toml", "[milestones]\ncode-freeze = \"22w05.5\"\n"),
+            (
+                "project.toml",
+                "[milestones]\nSOP = \"23w36.1\"\ncode-freeze = \"22w06.5\"\n",
+            ),
+        ])
+        .unwrap()
+        .milestones();
+        assert_eq!(
+            milestones,
+            vec![
+                ("SOP".to_string(), "23w36.1".to_string()),
+                ("code-freeze".to_string(), "22w06.5".to_string())
+            ]
+        );
+        assert_eq!(
+            config(&[("user.toml", "milestones = \"22w05.5\"")]).unwrap_err(),
+            "user.toml: milestones must be a table".to_string()
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        let ranges = config(&[
+            ("user.toml", "[ranges]\nrelease-2021-4 = \"21w40.1..21w48.5\"\n"),
+            ("project.toml", "[profile.a]\nquiet = true\n"),
+        ])
+        .unwrap()
+        .ranges();
+        assert_eq!(
+            ranges,
+            vec![("release-2021-4".to_string(), "21w40.1..21w48.5".to_string())]
+        );
+        assert_eq!(
+            config(&[("user.toml", "[ranges]\nrelease = [1, 2]")]).unwrap_err(),
+            "user.toml: ranges.release must be a string".to_string()
+        );
+    }
+
+    #[test]
+    fn test_effective() {
+        let key = key("workdays").unwrap();
+        let config = config(&[("user.toml", "workdays = [1, 2]")]).unwrap();
+        assert_eq!(
+            config.effective_with_env(key, Some("3".to_string())),
+            Some((Value::String("3".to_string()), Source::Env("CWVER_WORKDAYS")))
+        );
+        assert_eq!(
+            config.effective_with_env(key, None),
+            Some((
+                Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+                Source::File(Origin {
+                    path: PathBuf::from("user.toml"),
+                    profile: None
+                })
+            ))
+        );
+        assert_eq!(
+            Config::default().effective_with_env(key, None),
+            Some((Value::String(DEFAULT_WORKDAYS.to_string()), Source::Default))
+        );
+        assert_eq!(
+            super::key("holydays").err(),
+            Some("unknown configuration key holydays".to_string())
+        );
+    }
+
+    #[test]
+    fn test_env_vars() {
+        for key in KEYS {
+            assert!(key.env.starts_with("CWVER_"), "{} lacks the CWVER_ prefix", key.env);
+            assert_eq!(KEYS.iter().filter(|other| other.env == key.env).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_holidays() {
+        assert_eq!(config(&[]).unwrap().holidays(), Ok(vec![]));
+        assert_eq!(
+            config(&[("/etc/cwver/config.toml", "holidays = [\"country:DE-BY\", \"team.txt\"]")])
+                .unwrap()
+                .holidays(),
+            Ok(vec![
+                holidays::Source::Country("DE-BY".to_string()),
+                holidays::Source::File(PathBuf::from("/etc/cwver/team.txt"))
+            ])
+        );
+        assert_eq!(
+            config(&[("user.toml", "holidays = 1")]).unwrap().holidays(),
+            Err("user.toml: holidays must be an array like [\"country:DE-BY\", \"~/team.ics\"]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(config(&[]).unwrap().format(), Ok(None));
+        assert_eq!(
+            config(&[("user.toml", "format = \"json\"")]).unwrap().format(),
+            Ok(Some(Format::Json))
+        );
+        assert_eq!(
+            config(&[("user.toml", "format = \"xml\"")]).unwrap().format(),
+            Err("user.toml: unknown output format xml, supported are plain, json, yaml, jsonl".to_string())
+        );
+    }
+
+    #[test]
+    fn test_quiet() {
+        assert_eq!(config(&[]).unwrap().quiet(), Ok(None));
+        assert_eq!(
+            config(&[("user.toml", "quiet = true")]).unwrap().quiet(),
+            Ok(Some(true))
+        );
+        assert_eq!(
+            config(&[("user.toml", "quiet = \"yes\"")]).unwrap().quiet(),
+            Err("user.toml: quiet must be true or false".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pattern() {
+        assert_eq!(Config::default().pattern(), Ok(None));
+        assert_eq!(
+            config(&[("user.toml", "pattern = \"B{yy}W{ww}-{d}\"")])
+                .unwrap()
+                .pattern(),
+            Ok(Some("B{yy}W{ww}-{d}".parse().unwrap()))
+        );
+        assert_eq!(
+            config(&[("user.toml", "pattern = \"B{yy}W{ww}\"")]).unwrap().pattern(),
+            Err("user.toml: pattern B{yy}W{ww} lacks {d}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_timezone() {
+        assert_eq!(Config::default().timezone(), Ok(Timezone::Local));
+        assert_eq!(
+            config(&[("user.toml", "timezone = \"UTC\"")]).unwrap().timezone(),
+            Ok(Timezone::Utc)
+        );
+        assert_eq!(
+            config(&[("user.toml", "timezone = 1")]).unwrap().timezone(),
+            Err("user.toml: timezone must be a string like \"Europe/Berlin\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_first_weekday() {
+        assert_eq!(Config::default().first_weekday(), Ok(Weekday::Mon));
+        assert_eq!(
+            config(&[("user.toml", "first_weekday = 7")]).unwrap().first_weekday(),
+            Ok(Weekday::Sun)
+        );
+        assert_eq!(
+            config(&[("user.toml", "first_weekday = 0")]).unwrap().first_weekday(),
+            Err("user.toml: first_weekday must be a day of week, 1 = monday till 7 = sunday".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pivot() {
+        assert_eq!(Config::default().pivot(), Ok(2000));
+        assert_eq!(config(&[("user.toml", "pivot = 1970")]).unwrap().pivot(), Ok(1970));
+        assert_eq!(
+            config(&[("user.toml", "pivot = \"70s\"")]).unwrap().pivot(),
+            Err("user.toml: pivot must be a year like 1970".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_command() {
+        assert_eq!(Config::default().default_command(), Ok(vec!["today".to_string()]));
+        assert_eq!(
+            config(&[("user.toml", "default = \"bisect  21w01.1 21w10.1\"")])
+                .unwrap()
+                .default_command(),
+            Ok(vec!["bisect".to_string(), "21w01.1".to_string(), "21w10.1".to_string()])
+        );
+        assert_eq!(
+            config(&[("user.toml", "default = [\"convert\", \"21w01.1\"]")])
+                .unwrap()
+                .default_command(),
+            Ok(vec!["convert".to_string(), "21w01.1".to_string()])
+        );
+        assert_eq!(
+            config(&[("user.toml", "default = 1")]).unwrap().default_command(),
+            Err("user.toml: default must be a string or an array of strings".to_string())
+        );
+    }
+
+    #[test]
+    fn test_settings() {
+        let config = config(&[("user.toml", "workdays = 1\n[profile.automotive]\nworkdays = 2\n")]).unwrap();
+        let names: Vec<_> = config.settings().into_iter().map(|(name, _, _)| name).collect();
+        assert_eq!(names, vec!["workdays", "profile.automotive.workdays"]);
+    }
+
+    #[test]
+    fn test_set() {
+        let path = env::temp_dir()
+            .join(format!("cwver-test-set-{}", std::process::id()))
+            .join("config.toml");
+        assert_eq!(set(&path, "workdays", "[1, 2, 3]"), Ok(()));
+        assert_eq!(set(&path, "workdays", "1,2,3,4"), Ok(()));
+        assert_eq!(
+            set(&path, "workdays", "1,2,9").unwrap_err(),
+            format!("{}: given workday 9 not in range [1-7]", path.display())
+        );
+        assert_eq!(
+            set(&path, "holydays", "DE").unwrap_err(),
+            "unknown configuration key holydays".to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "workdays = \"1,2,3,4\"\n".to_string()
+        );
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_validate_without_env() {
+        let broken_env = Config {
+            env: Some(|name| match name {
+                "CWVER_HOLIDAYS" => Some("country:".to_string()),
+                _ => None,
+            }),
+            ..config(&[("config.toml", "locale = \"de\"")]).unwrap()
+        };
+        assert!(broken_env
+            .validate()
+            .unwrap_err()
+            .starts_with("environment variable CWVER_HOLIDAYS"));
+        assert_eq!(broken_env.without_env().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = env::temp_dir().join(format!("cwver-test-load-{}", std::process::id()));
+        let path = dir.join("ci.toml");
+        assert_eq!(
+            Config::load(Some(&path)).unwrap_err(),
+            format!("configuration file {} does not exist", path.display())
+        );
+        assert_eq!(set(&path, "workdays", "1,2,3,4"), Ok(()));
+        let config = Config::load(Some(&path)).unwrap();
+        assert_eq!(config.workdays(), Ok(Some(hashset![1, 2, 3, 4])));
+        assert_eq!(config.layers.len(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        assert!(config(&[("user.toml", "workdays = ")])
+            .unwrap_err()
+            .starts_with("user.toml: "));
+    }
+}
@@ -0,0 +1,398 @@
+use crate::error::CwverError;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::convert::TryFrom;
+
+/// How a day is determined in a given year.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Rule {
+    /// Month and day.
+    Fixed(u32, u32),
+    /// Days after Easter Sunday, negative for days before.
+    Easter(i64),
+    /// The nth weekday of a month, the last one for -1.
+    Nth(u32, Weekday, i32),
+    /// The last weekday strictly before month and day, e.g. the Wednesday before 23 November.
+    Before(u32, u32, Weekday),
+}
+
+/// What happens to a holiday on a weekend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Observance {
+    /// Nothing, the day is simply lost.
+    None,
+    /// Saturdays are observed on the Friday before, Sundays on the Monday after.
+    NearestWeekday,
+    /// A substitute day is taken on the next weekday which isn't a holiday already.
+    Substitute,
+}
+
+struct Holiday {
+    name: &'static str,
+    rule: Rule,
+    /// The regions observing the holiday, all regions if empty.
+    regions: &'static [&'static str],
+    since: i32,
+    until: i32,
+}
+
+struct Country {
+    code: &'static str,
+    regions: &'static [&'static str],
+    /// The region of a country code without region, whose holidays aren't nationwide.
+    default_region: Option<&'static str>,
+    observance: Observance,
+    holidays: &'static [Holiday],
+}
+
+const fn holiday(name: &'static str, rule: Rule, regions: &'static [&'static str]) -> Holiday {
+    Holiday {
+        name,
+        rule,
+        regions,
+        since: i32::MIN,
+        until: i32::MAX,
+    }
+}
+
+const fn since(year: i32, holiday: Holiday) -> Holiday {
+    Holiday { since: year, ..holiday }
+}
+
+const fn only(year: i32, holiday: Holiday) -> Holiday {
+    Holiday {
+        since: year,
+        until: year,
+        ..holiday
+    }
+}
+
+const ALL: &[&str] = &[];
+
+const COUNTRIES: &[Country] = &[
+    Country {
+        code: "AT",
+        regions: &[],
+        default_region: None,
+        observance: Observance::None,
+        holidays: &[
+            holiday("New Year's Day", Rule::Fixed(1, 1), ALL),
+            holiday("Epiphany", Rule::Fixed(1, 6), ALL),
+            holiday("Easter Monday", Rule::Easter(1), ALL),
+            holiday("Labour Day", Rule::Fixed(5, 1), ALL),
+            holiday("Ascension Day", Rule::Easter(39), ALL),
+            holiday("Whit Monday", Rule::Easter(50), ALL),
+            holiday("Corpus Christi", Rule::Easter(60), ALL),
+            holiday("Assumption Day", Rule::Fixed(8, 15), ALL),
+            holiday("National Day", Rule::Fixed(10, 26), ALL),
+            holiday("All Saints' Day", Rule::Fixed(11, 1), ALL),
+            holiday("Immaculate Conception", Rule::Fixed(12, 8), ALL),
+            holiday("Christmas Day", Rule::Fixed(12, 25), ALL),
+            holiday("St. Stephen's Day", Rule::Fixed(12, 26), ALL),
+        ],
+    },
+    Country {
+        code: "DE",
+        regions: &[
+            "BB", "BE", "BW", "BY", "HB", "HE", "HH", "MV", "NI", "NW", "RP", "SH", "SL", "SN", "ST", "TH",
+        ],
+        default_region: None,
+        observance: Observance::None,
+        holidays: &[
+            holiday("New Year's Day", Rule::Fixed(1, 1), ALL),
+            holiday("Epiphany", Rule::Fixed(1, 6), &["BW", "BY", "ST"]),
+            since(2019, holiday("International Women's Day", Rule::Fixed(3, 8), &["BE"])),
+            since(2023, holiday("International Women's Day", Rule::Fixed(3, 8), &["MV"])),
+            holiday("Good Friday", Rule::Easter(-2), ALL),
+            holiday("Easter Monday", Rule::Easter(1), ALL),
+            holiday("Labour Day", Rule::Fixed(5, 1), ALL),
+            holiday("Ascension Day", Rule::Easter(39), ALL),
+            holiday("Whit Monday", Rule::Easter(50), ALL),
+            holiday(
+                "Corpus Christi",
+                Rule::Easter(60),
+                &["BW", "BY", "HE", "NW", "RP", "SL"],
+            ),
+            holiday("Assumption Day", Rule::Fixed(8, 15), &["SL"]),
+            since(2019, holiday("World Children's Day", Rule::Fixed(9, 20), &["TH"])),
+            since(1990, holiday("German Unity Day", Rule::Fixed(10, 3), ALL)),
+            holiday("Reformation Day", Rule::Fixed(10, 31), &["BB", "MV", "SN", "ST", "TH"]),
+            since(
+                2018,
+                holiday("Reformation Day", Rule::Fixed(10, 31), &["HB", "HH", "NI", "SH"]),
+            ),
+            only(2017, holiday("Reformation Day", Rule::Fixed(10, 31), ALL)),
+            holiday("All Saints' Day", Rule::Fixed(11, 1), &["BW", "BY", "NW", "RP", "SL"]),
+            holiday(
+                "Day of Repentance and Prayer",
+                Rule::Before(11, 23, Weekday::Wed),
+                &["SN"],
+            ),
+            holiday("Christmas Day", Rule::Fixed(12, 25), ALL),
+            holiday("Boxing Day", Rule::Fixed(12, 26), ALL),
+        ],
+    },
+    Country {
+        code: "FR",
+        regions: &[],
+        default_region: None,
+        observance: Observance::None,
+        holidays: &[
+            holiday("New Year's Day", Rule::Fixed(1, 1), ALL),
+            holiday("Easter Monday", Rule::Easter(1), ALL),
+            holiday("Labour Day", Rule::Fixed(5, 1), ALL),
+            holiday("Victory in Europe Day", Rule::Fixed(5, 8), ALL),
+            holiday("Ascension Day", Rule::Easter(39), ALL),
+            holiday("Whit Monday", Rule::Easter(50), ALL),
+            holiday("Bastille Day", Rule::Fixed(7, 14), ALL),
+            holiday("Assumption Day", Rule::Fixed(8, 15), ALL),
+            holiday("All Saints' Day", Rule::Fixed(11, 1), ALL),
+            holiday("Armistice Day", Rule::Fixed(11, 11), ALL),
+            holiday("Christmas Day", Rule::Fixed(12, 25), ALL),
+        ],
+    },
+    Country {
+        code: "GB",
+        regions: &["ENG", "NIR", "SCT", "WLS"],
+        default_region: Some("ENG"),
+        observance: Observance::Substitute,
+        holidays: &[
+            holiday("New Year's Day", Rule::Fixed(1, 1), ALL),
+            holiday("2nd January", Rule::Fixed(1, 2), &["SCT"]),
+            holiday("St Patrick's Day", Rule::Fixed(3, 17), &["NIR"]),
+            holiday("Good Friday", Rule::Easter(-2), ALL),
+            holiday("Easter Monday", Rule::Easter(1), &["ENG", "NIR", "WLS"]),
+            holiday("Early May bank holiday", Rule::Nth(5, Weekday::Mon, 1), ALL),
+            holiday("Spring bank holiday", Rule::Nth(5, Weekday::Mon, -1), ALL),
+            holiday("Battle of the Boyne", Rule::Fixed(7, 12), &["NIR"]),
+            holiday("Summer bank holiday", Rule::Nth(8, Weekday::Mon, 1), &["SCT"]),
+            holiday(
+                "Summer bank holiday",
+                Rule::Nth(8, Weekday::Mon, -1),
+                &["ENG", "NIR", "WLS"],
+            ),
+            holiday("St Andrew's Day", Rule::Fixed(11, 30), &["SCT"]),
+            holiday("Christmas Day", Rule::Fixed(12, 25), ALL),
+            holiday("Boxing Day", Rule::Fixed(12, 26), ALL),
+        ],
+    },
+    Country {
+        code: "US",
+        regions: &[],
+        default_region: None,
+        observance: Observance::NearestWeekday,
+        holidays: &[
+            holiday("New Year's Day", Rule::Fixed(1, 1), ALL),
+            since(
+                1986,
+                holiday("Martin Luther King Jr. Day", Rule::Nth(1, Weekday::Mon, 3), ALL),
+            ),
+            holiday("Washington's Birthday", Rule::Nth(2, Weekday::Mon, 3), ALL),
+            holiday("Memorial Day", Rule::Nth(5, Weekday::Mon, -1), ALL),
+            since(2021, holiday("Juneteenth", Rule::Fixed(6, 19), ALL)),
+            holiday("Independence Day", Rule::Fixed(7, 4), ALL),
+            holiday("Labor Day", Rule::Nth(9, Weekday::Mon, 1), ALL),
+            holiday("Columbus Day", Rule::Nth(10, Weekday::Mon, 2), ALL),
+            holiday("Veterans Day", Rule::Fixed(11, 11), ALL),
+            holiday("Thanksgiving Day", Rule::Nth(11, Weekday::Thu, 4), ALL),
+            holiday("Christmas Day", Rule::Fixed(12, 25), ALL),
+        ],
+    },
+];
+
+/// The codes of all built-in holiday calendars, countries like `DE` followed by their regions like
+/// `DE-BY`.
+pub fn codes() -> Vec<String> {
+    COUNTRIES
+        .iter()
+        .flat_map(|country| {
+            std::iter::once(country.code.to_string()).chain(
+                country
+                    .regions
+                    .iter()
+                    .map(move |region| format!("{}-{}", country.code, region)),
+            )
+        })
+        .collect()
+}
+
+/// The public holidays of a country or region like `DE-BY` in `year`, following the regular rules;
+/// one-off holidays like royal jubilees aren't included. Holidays on a weekend are followed by the
+/// days they are observed on instead, e.g. `Christmas Day (substitute day)`.
+pub fn holidays(code: &str, year: i32) -> Result<Vec<(NaiveDate, String)>, CwverError> {
+    let unknown = || {
+        CwverError::Unknown(format!(
+            "no built-in holiday calendar for {}, supported are {}",
+            code,
+            codes().join(", ")
+        ))
+    };
+    let code = code.to_uppercase();
+    let (country_code, region) = match code.split_once('-') {
+        Some((country, region)) => (country, Some(region)),
+        None => (code.as_str(), None),
+    };
+    let country = COUNTRIES
+        .iter()
+        .find(|country| country.code == country_code)
+        .ok_or_else(unknown)?;
+    if region.is_some_and(|region| !country.regions.contains(&region)) {
+        return Err(unknown());
+    }
+    let region = region.or(country.default_region);
+
+    let mut holidays: Vec<(NaiveDate, String)> = country
+        .holidays
+        .iter()
+        .filter(|holiday| (holiday.since..=holiday.until).contains(&year))
+        .filter(|holiday| match region {
+            Some(region) => holiday.regions.is_empty() || holiday.regions.contains(&region),
+            None => holiday.regions.is_empty(),
+        })
+        .filter_map(|holiday| Some((date(holiday.rule, year)?, holiday.name.to_string())))
+        .collect();
+    holidays.sort();
+    let observed = observed(&holidays, country.observance);
+    holidays.extend(observed);
+    Ok(holidays)
+}
+
+fn date(rule: Rule, year: i32) -> Option<NaiveDate> {
+    match rule {
+        Rule::Fixed(month, day) => NaiveDate::from_ymd_opt(year, month, day),
+        Rule::Easter(offset) => Some(easter(year)? + Duration::days(offset)),
+        Rule::Nth(month, weekday, n) if n > 0 => {
+            NaiveDate::from_weekday_of_month_opt(year, month, weekday, u8::try_from(n).ok()?)
+        }
+        Rule::Nth(month, weekday, _) => {
+            let next_month = match month {
+                12 => NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+                _ => NaiveDate::from_ymd_opt(year, month + 1, 1)?,
+            };
+            last_before(next_month, weekday)
+        }
+        Rule::Before(month, day, weekday) => last_before(NaiveDate::from_ymd_opt(year, month, day)?, weekday),
+    }
+}
+
+/// The last `weekday` before `date`.
+fn last_before(date: NaiveDate, weekday: Weekday) -> Option<NaiveDate> {
+    let days_back = (7 + date.weekday().num_days_from_monday() - weekday.num_days_from_monday() - 1) % 7 + 1;
+    date.checked_sub_signed(Duration::days(i64::from(days_back)))
+}
+
+/// Easter Sunday of the Gregorian calendar, by the anonymous Gregorian algorithm.
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year.rem_euclid(19);
+    let (b, c) = (year.div_euclid(100), year.rem_euclid(100));
+    let (d, e) = (b / 4, b % 4);
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, u32::try_from(month).ok()?, u32::try_from(day).ok()?)
+}
+
+/// The days the holidays on weekends are observed on, `holidays` sorted by date.
+fn observed(holidays: &[(NaiveDate, String)], observance: Observance) -> Vec<(NaiveDate, String)> {
+    let is_weekend = |date: &NaiveDate| matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+    let mut observed: Vec<(NaiveDate, String)> = vec![];
+    for (date, name) in holidays.iter().filter(|(date, _)| is_weekend(date)) {
+        match observance {
+            Observance::None => {}
+            Observance::NearestWeekday => {
+                let day = match date.weekday() {
+                    Weekday::Sat => *date - Duration::days(1),
+                    _ => *date + Duration::days(1),
+                };
+                observed.push((day, format!("{} (observed)", name)));
+            }
+            Observance::Substitute => {
+                let taken = |day: &NaiveDate| {
+                    is_weekend(day)
+                        || holidays.iter().any(|(holiday, _)| holiday == day)
+                        || observed.iter().any(|(substitute, _)| substitute == day)
+                };
+                let substitute = date.iter_days().find(|day| !taken(day));
+                observed.extend(substitute.map(|day| (day, format!("{} (substitute day)", name))));
+            }
+        }
+    }
+    observed
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[cfg(test)]
+    fn dates(code: &str, year: i32) -> Vec<String> {
+        holidays(code, year)
+            .unwrap()
+            .into_iter()
+            .map(|(date, name)| format!("{} {}", date, name))
+            .collect()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter(2021), NaiveDate::from_ymd_opt(2021, 4, 4));
+        assert_eq!(easter(2024), NaiveDate::from_ymd_opt(2024, 3, 31));
+        assert_eq!(easter(2038), NaiveDate::from_ymd_opt(2038, 4, 25));
+        assert_eq!(easter(1818), NaiveDate::from_ymd_opt(1818, 3, 22));
+    }
+
+    #[test]
+    fn test_holidays() {
+        assert_eq!(
+            dates("DE", 2021),
+            vec![
+                "2021-01-01 New Year's Day",
+                "2021-04-02 Good Friday",
+                "2021-04-05 Easter Monday",
+                "2021-05-01 Labour Day",
+                "2021-05-13 Ascension Day",
+                "2021-05-24 Whit Monday",
+                "2021-10-03 German Unity Day",
+                "2021-12-25 Christmas Day",
+                "2021-12-26 Boxing Day",
+            ]
+        );
+        let bavaria = dates("de-by", 2021);
+        assert!(bavaria.contains(&"2021-06-03 Corpus Christi".to_string()));
+        assert!(bavaria.contains(&"2021-11-01 All Saints' Day".to_string()));
+        assert!(!bavaria.contains(&"2021-10-31 Reformation Day".to_string()));
+        assert!(dates("DE-SN", 2021).contains(&"2021-11-17 Day of Repentance and Prayer".to_string()));
+        assert!(dates("DE", 2017).contains(&"2017-10-31 Reformation Day".to_string()));
+        assert!(!dates("DE-BE", 2018).contains(&"2018-03-08 International Women's Day".to_string()));
+        assert_eq!(
+            holidays("DE-XX", 2021).unwrap_err().to_string(),
+            format!(
+                "no built-in holiday calendar for DE-XX, supported are {}",
+                codes().join(", ")
+            )
+        );
+    }
+
+    #[test]
+    fn test_observed() {
+        let england = dates("GB", 2021);
+        assert!(england.contains(&"2021-12-27 Christmas Day (substitute day)".to_string()));
+        assert!(england.contains(&"2021-12-28 Boxing Day (substitute day)".to_string()));
+        assert!(england.contains(&"2021-05-31 Spring bank holiday".to_string()));
+        assert!(england.contains(&"2021-08-30 Summer bank holiday".to_string()));
+        let scotland = dates("GB-SCT", 2022);
+        assert!(scotland.contains(&"2022-01-03 New Year's Day (substitute day)".to_string()));
+        assert!(scotland.contains(&"2022-01-04 2nd January (substitute day)".to_string()));
+        assert!(!scotland.iter().any(|holiday| holiday.ends_with("Easter Monday")));
+
+        let us = dates("US", 2021);
+        assert!(us.contains(&"2021-07-05 Independence Day (observed)".to_string()));
+        assert!(us.contains(&"2021-12-24 Christmas Day (observed)".to_string()));
+        assert!(us.contains(&"2021-11-25 Thanksgiving Day".to_string()));
+        assert!(us.contains(&"2021-01-18 Martin Luther King Jr. Day".to_string()));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::error::CwverError;
+
+/// Parses comma separated values as RFC 4180 describes them: fields may be quoted with `"`, and
+/// quoted fields may contain commas, line breaks and `""` for a quote. Line breaks may be `\n` or
+/// `\r\n`.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, CwverError> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => {
+                        if c == '\n' {
+                            line += 1;
+                        }
+                        field.push(c);
+                    }
+                    None => {
+                        return Err(CwverError::Parse("unclosed quote".to_string()).context(format!("line {}", line)))
+                    }
+                }
+            },
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// One line of comma separated values, fields are quoted only if they need to.
+pub fn record(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| match field.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field.clone(),
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let strings = |fields: &[&str]| fields.iter().map(|field| field.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            parse("name,build\r\n\"Release, final\",21w45.7\n\"say \"\"hi\"\"\",\"two\nlines\"\n"),
+            Ok(vec![
+                strings(&["name", "build"]),
+                strings(&["Release, final", "21w45.7"]),
+                strings(&["say \"hi\"", "two\nlines"]),
+            ])
+        );
+        assert_eq!(parse("a,,b"), Ok(vec![strings(&["a", "", "b"])]));
+        assert_eq!(parse(""), Ok(vec![]));
+        assert_eq!(
+            parse("a\n\"b"),
+            Err(CwverError::Parse("unclosed quote".to_string()).context("line 2"))
+        );
+        assert_eq!(parse("a\n\"b").unwrap_err().to_string(), "line 2: unclosed quote");
+    }
+
+    #[test]
+    fn test_record() {
+        let fields: Vec<String> = ["plain", "with, comma", "with \"quote\""]
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        assert_eq!(
+            record(&fields),
+            "plain,\"with, comma\",\"with \"\"quote\"\"\"".to_string()
+        );
+        assert_eq!(parse(&record(&fields)), Ok(vec![fields]));
+    }
+}
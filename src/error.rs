@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+use thiserror::Error;
+
+/// The errors of the library, displayed as the human-readable messages the command line tool
+/// prints.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CwverError {
+    /// Input that can't be parsed at all, e.g. `21w4.5` or `2021-13-01`.
+    #[error("{0}")]
+    Parse(String),
+    /// Input that parses but doesn't denote an existing day, week, year or position, e.g. `21w53.1`.
+    #[error("{0}")]
+    OutOfRange(String),
+    /// A range ending before it starts.
+    #[error("{from} must be before {till} in time")]
+    InvertedRange { from: NaiveDate, till: NaiveDate },
+    /// A day of week other than 1 (Monday) till 7 (Sunday) given as workday.
+    #[error("given workday {0} not in range [1-7]")]
+    InvalidWorkday(u32),
+    /// A milestone, holiday country or other name that isn't defined.
+    #[error("{0}")]
+    Unknown(String),
+    /// Reading a holiday source failed.
+    #[error("{0}")]
+    Io(String),
+    /// Another error with what it occurred in, e.g. the milestone whose date fails to parse.
+    #[error("{context}: {source}")]
+    Context { context: String, source: Box<CwverError> },
+}
+
+impl CwverError {
+    pub fn context(self, context: impl Into<String>) -> CwverError {
+        CwverError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The error without any context.
+    pub fn root(&self) -> &CwverError {
+        match self {
+            CwverError::Context { source, .. } => source.root(),
+            error => error,
+        }
+    }
+}
+
+/// The command line tool reports all errors as messages.
+impl From<CwverError> for String {
+    fn from(error: CwverError) -> String {
+        error.to_string()
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_context() {
+        let error = CwverError::Parse("failed to parse 21w4.5".to_string())
+            .context("line 3")
+            .context("versions.txt");
+        assert_eq!(error.to_string(), "versions.txt: line 3: failed to parse 21w4.5");
+        assert_eq!(error.root(), &CwverError::Parse("failed to parse 21w4.5".to_string()));
+        assert_eq!(
+            String::from(CwverError::InvertedRange {
+                from: NaiveDate::from_ymd(2021, 10, 5),
+                till: NaiveDate::from_ymd(2021, 10, 4)
+            }),
+            "2021-10-05 must be before 2021-10-04 in time".to_string()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Whether a file name matches a glob like `*.tar.gz`: `*` stands for any text and `?` for any
+/// one character.
+pub fn glob_match(glob: &str, name: &str) -> bool {
+    let (glob, name): (Vec<char>, Vec<char>) = (glob.chars().collect(), name.chars().collect());
+    let (mut g, mut n) = (0, 0);
+    // the last `*` and the position in the name it was tried at, to backtrack to
+    let mut star = None;
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, n));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_g, star_n)) => {
+                    star = Some((star_g, star_n + 1));
+                    g = star_g + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+/// The files below a directory, sorted by path, or the path itself if it is a file. Files and
+/// directories whose name matches one of the `ignore` globs are left out. Symbolic links to
+/// directories are not followed.
+pub fn walk(path: &Path, ignore: &[String]) -> Result<Vec<PathBuf>, String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+    if !metadata.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))? {
+        let entry = entry.map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if ignore.iter().any(|glob| glob_match(glob, &name)) {
+            debug!(path = %entry.path().display(), "ignored");
+            continue;
+        }
+        files.extend(walk(&entry.path(), ignore)?);
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The patterns of the `.gitignore` and `.cwverignore` files in a directory, if any. Only patterns
+/// matching names are supported, like `target/` or `*.log`; negations and patterns of paths like
+/// `docs/*.md` are skipped.
+pub fn ignore_patterns(dir: &Path) -> Vec<String> {
+    [".gitignore", ".cwverignore"]
+        .iter()
+        .filter_map(|name| fs::read_to_string(dir.join(name)).ok())
+        .flat_map(|content| {
+            content
+                .lines()
+                .map(|line| line.trim().trim_start_matches('/').trim_end_matches('/').to_string())
+                .filter(|pattern| !pattern.is_empty() && !pattern.starts_with(['#', '!']) && !pattern.contains('/'))
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.tar.gz", "nightly-21w45.7.tar.gz"));
+        assert!(!glob_match("*.tar.gz", "nightly-21w45.7.zip"));
+        assert!(glob_match("nightly-??w*", "nightly-21w45.7.zip"));
+        assert!(!glob_match("nightly-??w*", "nightly-2021w45.7.zip"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("a", "ab"));
+    }
+
+    #[test]
+    fn test_walk() {
+        let dir = std::env::temp_dir().join(format!("cwver-walk-{}", std::process::id()));
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("b").join("21w45.7.zip"), "").unwrap();
+        fs::write(dir.join("a.txt"), "").unwrap();
+        assert_eq!(
+            walk(&dir, &[]),
+            Ok(vec![dir.join("a.txt"), dir.join("b").join("21w45.7.zip")])
+        );
+        assert_eq!(walk(&dir, &["b".to_string()]), Ok(vec![dir.join("a.txt")]));
+        assert_eq!(walk(&dir.join("a.txt"), &[]), Ok(vec![dir.join("a.txt")]));
+        assert!(walk(&dir.join("missing"), &[]).is_err());
+
+        fs::write(
+            dir.join(".gitignore"),
+            "# build output\n/target/\n*.log\n!keep.log\ndocs/*.md\n",
+        )
+        .unwrap();
+        assert_eq!(ignore_patterns(&dir), vec!["target".to_string(), "*.log".to_string()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
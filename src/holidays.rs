@@ -0,0 +1,419 @@
+use crate::countries;
+use crate::error::CwverError;
+use crate::parse::{date_str, is_week, range_str, week_str, Schema};
+use crate::range::DateRange;
+use chrono::NaiveDate;
+use directories::BaseDirs;
+use im::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use tracing::{debug, info, warn};
+
+/// Where holidays are taken from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// A built-in holiday calendar of a country or region, e.g. `country:DE-BY`.
+    Country(String),
+    /// The holiday calendar of a country or region fetched from an online service, e.g.
+    /// `online:DE-BY`. Fetching them is up to the command line tool.
+    Online(String),
+    /// An iCalendar file (`.ics`) or a text file with one ISO date or cw version per line.
+    File(PathBuf),
+}
+
+impl Source {
+    /// Relative paths are resolved against `base`, e.g. the directory of the configuration file
+    /// they are listed in. A leading `~/` refers to the home directory.
+    pub fn parse(source: &str, base: Option<&Path>) -> Result<Source, CwverError> {
+        let country = |country: &str| match country.is_empty() {
+            true => Err(CwverError::Parse(format!("holiday source {} lacks a country", source))),
+            false => Ok(country.to_string()),
+        };
+        if let Some(code) = source.strip_prefix("country:") {
+            return country(code).map(Source::Country);
+        }
+        if let Some(code) = source.strip_prefix("online:") {
+            return country(code).map(Source::Online);
+        }
+        let path = match source.strip_prefix("~/") {
+            Some(relative) => BaseDirs::new()
+                .ok_or_else(|| CwverError::Io(format!("failed to locate the home directory for {}", source)))?
+                .home_dir()
+                .join(relative),
+            None => PathBuf::from(source),
+        };
+        Ok(Source::File(match (path.is_relative(), base) {
+            (true, Some(base)) => base.join(path),
+            _ => path,
+        }))
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Country(country) => write!(f, "country:{}", country),
+            Source::Online(country) => write!(f, "online:{}", country),
+            Source::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// The years built-in holiday calendars are generated for.
+const COUNTRY_YEARS: std::ops::RangeInclusive<i32> = 1900..=2199;
+
+/// The holidays of all sources and their names if known. The first name found for a date wins.
+/// cw versions in text files are read in the given `schema`.
+pub fn load(sources: &[Source], schema: &Schema) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    merge(sources, |source| load_source(source, schema))
+}
+
+/// Holidays read from a source, in the schema they were read in.
+type Loaded = (Source, Schema, HashMap<NaiveDate, Option<String>>);
+
+/// The holidays loaded by [`load_cached`] in this process.
+static LOADED: Mutex<Vec<Loaded>> = Mutex::new(Vec::new());
+
+/// The holidays of all sources like [`load`], each source loaded only once per process. Built-in
+/// calendars are also stored below `cache`, so that later invocations read them instead of
+/// generating them again.
+pub fn load_cached(
+    sources: &[Source],
+    schema: &Schema,
+    cache: Option<&Path>,
+) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    merge(sources, |source| {
+        let mut loaded = LOADED.lock().unwrap_or_else(PoisonError::into_inner);
+        let known = loaded
+            .iter()
+            .find(|(known, read_in, _)| known == source && read_in == schema);
+        if let Some((.., holidays)) = known {
+            debug!("reusing the holidays of {}", source);
+            return Ok(holidays.clone());
+        }
+        let holidays = match (source, cache) {
+            (Source::Country(country), Some(cache)) => load_country(country, cache)?,
+            _ => load_source(source, schema)?,
+        };
+        loaded.push((source.clone(), schema.clone(), holidays.clone()));
+        Ok(holidays)
+    })
+}
+
+fn merge(
+    sources: &[Source],
+    mut load_source: impl FnMut(&Source) -> Result<HashMap<NaiveDate, Option<String>>, CwverError>,
+) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    let mut holidays = HashMap::new();
+    for source in sources {
+        let loaded = load_source(source)?;
+        info!("read {} holiday(s) from {}", loaded.len(), source);
+        for (date, name) in loaded {
+            let known = holidays.entry(date).or_insert(None);
+            if known.is_none() {
+                *known = name;
+            }
+        }
+    }
+    Ok(holidays)
+}
+
+fn load_source(source: &Source, schema: &Schema) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    Ok(match source {
+        Source::Country(country) => {
+            let mut holidays = HashMap::new();
+            for year in COUNTRY_YEARS {
+                for (date, name) in countries::holidays(country, year)? {
+                    holidays.entry(date).or_insert(Some(name));
+                }
+            }
+            holidays
+        }
+        Source::Online(country) => {
+            return Err(CwverError::Unknown(format!(
+                "online holiday calendar {} has to be fetched before loading",
+                country
+            )))
+        }
+        Source::File(path) => {
+            let content = fs::read_to_string(path)
+                .map_err(|e| CwverError::Io(format!("failed to read {}: {}", path.display(), e)))?;
+            match path
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"))
+            {
+                true => parse_ics(&content),
+                false => parse_date_list(&content, schema),
+            }
+            .map_err(|e| e.context(path.display().to_string()))?
+        }
+    })
+}
+
+/// A built-in calendar, stored below `cache` as a holiday file of ISO dates. The file is named
+/// after the version of cwver, whose rules may differ from the ones of other versions.
+fn load_country(country: &str, cache: &Path) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    let file_name = format!("country-{}-{}.txt", country.to_uppercase(), env!("CARGO_PKG_VERSION"));
+    let path = cache.join("holidays").join(file_name);
+    let cached = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| parse_date_list(&content, &Schema::default()).ok());
+    if let Some(holidays) = cached {
+        debug!("read the cached holidays of {} from {}", country, path.display());
+        return Ok(holidays);
+    }
+    let holidays = load_source(&Source::Country(country.to_string()), &Schema::default())?;
+    let mut dates: Vec<_> = holidays.iter().collect();
+    dates.sort();
+    let content: String = dates
+        .into_iter()
+        .map(|(date, name)| format!("{} {}\n", date, name.as_deref().unwrap_or_default()))
+        .collect();
+    // a cache that can't be written only costs generating the holidays again next time
+    let stored = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, content));
+    if let Err(e) = stored {
+        warn!("failed to cache holidays in {}: {}", path.display(), e);
+    }
+    Ok(holidays)
+}
+
+/// One ISO date or cw version per line, optionally followed by its name. A week like `21w52` covers
+/// the whole week, e.g. for a company shutdown. `#` starts a comment.
+fn parse_date_list(content: &str, schema: &Schema) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    let schema = Schema {
+        strict: true,
+        ..schema.clone()
+    };
+    let mut holidays = HashMap::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (day_str, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let dates = match is_week(day_str) {
+            true => week_str(day_str, &schema).map(|week| week.from.iter_days().take(7).collect()),
+            false => date_str(day_str, &schema).map(|date| vec![date]),
+        }
+        .map_err(|_| {
+            CwverError::Parse(format!("failed to parse ISO date or cw version {}", day_str))
+                .context(format!("line {}", number + 1))
+        })?;
+        let name = Some(name.trim()).filter(|name| !name.is_empty());
+        for date in dates {
+            holidays.insert(date, name.map(str::to_string));
+        }
+    }
+    Ok(holidays)
+}
+
+/// Blackout ranges like `21w51.3..22w01.5 vacation`, one per line and optionally followed by its
+/// name. A single date or a week like `21w52` is a range as well. `#` starts a comment.
+pub fn load_blackouts(path: &Path, schema: &Schema) -> Result<Vec<(DateRange, Option<String>)>, CwverError> {
+    let content =
+        fs::read_to_string(path).map_err(|e| CwverError::Io(format!("failed to read {}: {}", path.display(), e)))?;
+    let blackouts = parse_blackouts(&content, schema).map_err(|e| e.context(path.display().to_string()))?;
+    info!("read {} blackout range(s) from {}", blackouts.len(), path.display());
+    Ok(blackouts)
+}
+
+fn parse_blackouts(content: &str, schema: &Schema) -> Result<Vec<(DateRange, Option<String>)>, CwverError> {
+    let schema = Schema {
+        strict: true,
+        ..schema.clone()
+    };
+    let mut blackouts = vec![];
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (range, name) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let range = range_str(range, &schema).map_err(|e| e.context(format!("line {}", number + 1)))?;
+        let name = Some(name.trim()).filter(|name| !name.is_empty());
+        blackouts.push((range, name.map(str::to_string)));
+    }
+    Ok(blackouts)
+}
+
+/// All days covered by the events of an iCalendar file, named by their `SUMMARY`. Only all-day
+/// events (`DTSTART;VALUE=DATE`) and the start day of timed events are taken into account,
+/// recurrence rules are not supported.
+fn parse_ics(content: &str) -> Result<HashMap<NaiveDate, Option<String>>, CwverError> {
+    let value_of = |line: &str| line.rsplit(':').next().unwrap_or_default().trim().to_string();
+    let date_of = |line: &str| {
+        let value = value_of(line);
+        let date_str = value.get(..8).unwrap_or(&value);
+        NaiveDate::parse_from_str(date_str, "%Y%m%d")
+            .map_err(|_| CwverError::Parse(format!("failed to parse date in {}", line)))
+    };
+
+    let mut holidays = HashMap::new();
+    let (mut start, mut end, mut summary) = (None, None, None);
+    for line in content.lines() {
+        let name = line.split([';', ':']).next().unwrap_or_default();
+        match name {
+            "BEGIN" if line.trim() == "BEGIN:VEVENT" => (start, end, summary) = (None, None, None),
+            "DTSTART" => start = Some(date_of(line)?),
+            "DTEND" if !value_of(line).contains('T') => end = Some(date_of(line)?),
+            "SUMMARY" => {
+                summary = line
+                    .split_once(':')
+                    .map(|(_, summary)| summary.trim().replace("\\,", ",").replace("\\;", ";"))
+            }
+            "RRULE" => debug!("ignoring recurrence rule {}", line),
+            "END" if line.trim() == "END:VEVENT" => {
+                let start = start.ok_or_else(|| CwverError::Parse("event without DTSTART".to_string()))?;
+                // DTEND of all-day events is exclusive
+                let end = end
+                    .and_then(|end: NaiveDate| end.pred_opt())
+                    .unwrap_or(start)
+                    .max(start);
+                for date in start.iter_days().take_while(|date| date <= &end) {
+                    holidays.insert(date, summary.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(holidays)
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_source() {
+        assert_eq!(
+            Source::parse("country:DE-BY", None),
+            Ok(Source::Country("DE-BY".to_string()))
+        );
+        assert_eq!(
+            Source::parse("team.txt", Some(Path::new("/etc/cwver"))),
+            Ok(Source::File(PathBuf::from("/etc/cwver/team.txt")))
+        );
+        assert_eq!(
+            Source::parse("/srv/team.ics", Some(Path::new("/etc/cwver"))),
+            Ok(Source::File(PathBuf::from("/srv/team.ics")))
+        );
+        assert_eq!(
+            Source::parse("online:GB-SCT", None),
+            Ok(Source::Online("GB-SCT".to_string()))
+        );
+        assert_eq!(
+            Source::parse("country:", None),
+            Err(CwverError::Parse("holiday source country: lacks a country".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_date_list() {
+        assert_eq!(
+            parse_date_list(
+                "# team holidays\n2021-12-24 Christmas Eve\n\n2021-12-31  # New Year's Eve\n",
+                &Schema::default()
+            ),
+            Ok(hashmap! {
+                NaiveDate::from_ymd(2021, 12, 24) => Some("Christmas Eve".to_string()),
+                NaiveDate::from_ymd(2021, 12, 31) => None
+            })
+        );
+        assert_eq!(
+            parse_date_list("21w51.5 Christmas Eve\n2021w52.5\n", &Schema::default()),
+            Ok(hashmap! {
+                NaiveDate::from_ymd(2021, 12, 24) => Some("Christmas Eve".to_string()),
+                NaiveDate::from_ymd(2021, 12, 31) => None
+            })
+        );
+        assert_eq!(
+            parse_date_list("22w01 shutdown\n", &Schema::default())
+                .unwrap()
+                .keys()
+                .copied()
+                .collect::<std::collections::BTreeSet<_>>(),
+            NaiveDate::from_ymd(2022, 1, 3)
+                .iter_days()
+                .take(7)
+                .collect::<std::collections::BTreeSet<_>>()
+        );
+        assert_eq!(
+            parse_date_list("2021-12-24\n24.12.2021\n", &Schema::default()),
+            Err(CwverError::Parse("failed to parse ISO date or cw version 24.12.2021".to_string()).context("line 2"))
+        );
+        assert!(parse_date_list("x21w51.5\n", &Schema::default()).is_err());
+    }
+
+    #[test]
+    fn test_load_cached() {
+        let cache = std::env::temp_dir().join(format!("cwver-test-holidays-{}", std::process::id()));
+        let sources = [Source::Country("DE-BY".to_string())];
+        let generated = load(&sources, &Schema::default()).unwrap();
+        assert_eq!(load_country("DE-BY", &cache), Ok(generated.clone()));
+        let path = cache
+            .join("holidays")
+            .join(format!("country-DE-BY-{}.txt", env!("CARGO_PKG_VERSION")));
+        assert!(fs::read_to_string(&path).unwrap().contains("2021-01-06 Epiphany\n"));
+        assert_eq!(load_country("DE-BY", &cache), Ok(generated.clone()));
+        fs::remove_dir_all(&cache).unwrap();
+        assert_eq!(load_cached(&sources, &Schema::default(), None), Ok(generated));
+    }
+
+    #[test]
+    fn test_parse_blackouts() {
+        let range = |from: (i32, u32, u32), till: (i32, u32, u32)| {
+            DateRange::new(
+                NaiveDate::from_ymd(from.0, from.1, from.2),
+                NaiveDate::from_ymd(till.0, till.1, till.2),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            parse_blackouts(
+                "# anna\n21w51.3..22w01.5 vacation\n2022-02-14\n22w10  # training\n",
+                &Schema::default()
+            ),
+            Ok(vec![
+                (range((2021, 12, 22), (2022, 1, 7)), Some("vacation".to_string())),
+                (range((2022, 2, 14), (2022, 2, 14)), None),
+                (range((2022, 3, 7), (2022, 3, 13)), None),
+            ])
+        );
+        assert_eq!(
+            parse_blackouts("22w01.5..21w51.3\n", &Schema::default()),
+            Err(CwverError::InvertedRange {
+                from: NaiveDate::from_ymd(2022, 1, 7),
+                till: NaiveDate::from_ymd(2021, 12, 22)
+            }
+            .context("line 1"))
+        );
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   SUMMARY:Christmas\\, Boxing Day\r\n\
+                   DTSTART;VALUE=DATE:20211225\r\n\
+                   DTEND;VALUE=DATE:20211227\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   DTSTART:20211231T090000Z\r\n\
+                   DTEND:20211231T120000Z\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        assert_eq!(
+            parse_ics(ics),
+            Ok(hashmap! {
+                NaiveDate::from_ymd(2021, 12, 25) => Some("Christmas, Boxing Day".to_string()),
+                NaiveDate::from_ymd(2021, 12, 26) => Some("Christmas, Boxing Day".to_string()),
+                NaiveDate::from_ymd(2021, 12, 31) => None
+            })
+        );
+    }
+}
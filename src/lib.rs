@@ -0,0 +1,31 @@
+//! Calendar week versions like `21w45.3`: year `21`, week `45`, Wednesday. The library behind the
+//! `cwver` command line tool, for tools that want to parse, format or calculate with them directly:
+//!
+//! ```
+//! use cwver::{CwVersion, Schema};
+//!
+//! let version: CwVersion = "21w45.3".parse().unwrap();
+//! let date = version.to_date(&Schema::default()).unwrap();
+//! assert_eq!(date.to_string(), "2021-11-10");
+//! assert_eq!(CwVersion::from_date(&date, &Schema::default()).to_string(), "21w45.3");
+//! ```
+
+#[cfg_attr(test, macro_use)]
+extern crate im;
+
+pub mod bisect;
+pub mod calendar;
+pub mod countries;
+pub mod error;
+pub mod holidays;
+pub mod milestone;
+pub mod parse;
+pub mod pattern;
+pub mod range;
+pub mod selftest;
+pub mod set;
+pub mod version;
+
+pub use error::CwverError;
+pub use parse::Schema;
+pub use version::CwVersion;
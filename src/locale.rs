@@ -0,0 +1,115 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::env;
+use tracing::debug;
+
+/// The language of messages, weekday names and dates displayed in prose. cw version strings,
+/// ISO dates of conversions and error messages are the same in every locale.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+
+impl Locale {
+    pub const NAMES: &'static [&'static str] = &["en", "de"];
+
+    /// Accepts a language (`de`) as well as a POSIX locale name (`de_DE.UTF-8`).
+    pub fn parse(name: &str) -> Result<Locale, String> {
+        match language(name) {
+            "en" | "C" | "POSIX" => Ok(Locale::En),
+            "de" => Ok(Locale::De),
+            _ => Err(format!(
+                "unsupported locale {}, supported are {}",
+                name,
+                Locale::NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// The locale of the environment, taken from `LC_ALL`, `LC_TIME` or `LANG` like the C library does.
+    pub fn detect() -> Locale {
+        let var = |name| env::var(name).ok();
+        Locale::detect_from(var("LC_ALL"), var("LC_TIME"), var("LANG"))
+    }
+
+    fn detect_from(lc_all: Option<String>, lc_time: Option<String>, lang: Option<String>) -> Locale {
+        match vec![lc_all, lc_time, lang]
+            .into_iter()
+            .flatten()
+            .find(|name| !name.is_empty())
+        {
+            Some(name) => Locale::parse(&name).unwrap_or_else(|e| {
+                debug!("{}, falling back to en", e);
+                Locale::En
+            }),
+            None => Locale::En,
+        }
+    }
+
+    /// Picks the translation of a message part for this locale.
+    pub fn text(&self, en: &'static str, de: &'static str) -> &'static str {
+        match self {
+            Locale::En => en,
+            Locale::De => de,
+        }
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        match weekday {
+            Weekday::Mon => self.text("Monday", "Montag"),
+            Weekday::Tue => self.text("Tuesday", "Dienstag"),
+            Weekday::Wed => self.text("Wednesday", "Mittwoch"),
+            Weekday::Thu => self.text("Thursday", "Donnerstag"),
+            Weekday::Fri => self.text("Friday", "Freitag"),
+            Weekday::Sat => self.text("Saturday", "Samstag"),
+            Weekday::Sun => self.text("Sunday", "Sonntag"),
+        }
+    }
+
+    /// Dates in prose, e.g. in the regression range of `bisect`. English output keeps ISO dates.
+    pub fn date(&self, date: &NaiveDate) -> String {
+        match self {
+            Locale::En => date.format("%Y-%m-%d").to_string(),
+            Locale::De => format!("{}, {}", &self.weekday(date.weekday())[..2], date.format("%d.%m.%Y")),
+        }
+    }
+}
+
+fn language(name: &str) -> &str {
+    name.split(['_', '.', '@']).next().unwrap_or_default()
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Locale::parse("de"), Ok(Locale::De));
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Ok(Locale::De));
+        assert_eq!(Locale::parse("en_US"), Ok(Locale::En));
+        assert_eq!(Locale::parse("C"), Ok(Locale::En));
+        assert_eq!(
+            Locale::parse("fr_FR"),
+            Err("unsupported locale fr_FR, supported are en, de".to_string())
+        );
+    }
+
+    #[test]
+    fn test_detect_from() {
+        let var = |value: &str| Some(value.to_string());
+        assert_eq!(Locale::detect_from(None, None, None), Locale::En);
+        assert_eq!(Locale::detect_from(None, None, var("de_AT.UTF-8")), Locale::De);
+        assert_eq!(Locale::detect_from(None, var("en_GB"), var("de_DE")), Locale::En);
+        assert_eq!(Locale::detect_from(var(""), var("de_DE"), var("en_US")), Locale::De);
+        assert_eq!(Locale::detect_from(None, None, var("fr_FR")), Locale::En);
+    }
+
+    #[test]
+    fn test_date() {
+        let date = NaiveDate::from_ymd(2021, 1, 21);
+        assert_eq!(Locale::En.date(&date), "2021-01-21".to_string());
+        assert_eq!(Locale::De.date(&date), "Do, 21.01.2021".to_string());
+    }
+}
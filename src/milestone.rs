@@ -0,0 +1,168 @@
+use crate::calendar::{count_workdays, effort, Calendar};
+use crate::error::CwverError;
+use crate::parse::{date_str, Schema};
+use chrono::{NaiveDate, NaiveDateTime};
+
+/// A named date of the project schedule, e.g. a code freeze.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Milestone {
+    pub name: String,
+    pub date: NaiveDate,
+}
+
+/// Parses the dates of milestone definitions, see `Config::milestones`. The milestones are sorted by
+/// date.
+pub fn resolve(definitions: &[(String, String)], schema: &Schema) -> Result<Vec<Milestone>, CwverError> {
+    let mut milestones = definitions
+        .iter()
+        .map(|(name, date)| {
+            Ok(Milestone {
+                name: name.clone(),
+                date: date_str(date, schema).map_err(|e| e.context(format!("milestone {}", name)))?,
+            })
+        })
+        .collect::<Result<Vec<_>, CwverError>>()?;
+    milestones.sort_by(|a, b| (a.date, &a.name).cmp(&(b.date, &b.name)));
+    Ok(milestones)
+}
+
+pub fn find<'a>(milestones: &'a [Milestone], name: &str) -> Result<&'a Milestone, CwverError> {
+    if milestones.is_empty() {
+        return Err(CwverError::Unknown(format!(
+            "unknown milestone {}, no milestones are defined in the configuration files",
+            name
+        )));
+    }
+    milestones
+        .iter()
+        .find(|milestone| milestone.name == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = milestones.iter().map(|milestone| milestone.name.as_str()).collect();
+            CwverError::Unknown(format!("unknown milestone {}, defined are {}", name, names.join(", ")))
+        })
+}
+
+/// The workdays left from `from` till `till`, negative if `till` has passed already.
+pub fn workdays_until(calendar: &Calendar, from: &NaiveDate, till: &NaiveDate) -> Result<i64, CwverError> {
+    match from <= till {
+        true => count_workdays(calendar, from, till).map(i64::from),
+        false => count_workdays(calendar, till, from).map(|count| -i64::from(count)),
+    }
+}
+
+/// The weighted workdays left from `from` till `till`, negative if `till` has passed already.
+pub fn effort_until(calendar: &Calendar, from: &NaiveDate, till: &NaiveDate) -> Result<f64, CwverError> {
+    match from <= till {
+        true => effort(calendar, from, till),
+        false => effort(calendar, till, from).map(|effort| -effort),
+    }
+}
+
+/// Time left till the start of a day, negative once it has begun.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Countdown {
+    /// Fractional if the workdays are weighted.
+    pub workdays: f64,
+    pub days: i64,
+    pub hours: i64,
+}
+
+pub fn countdown(calendar: &Calendar, now: &NaiveDateTime, target: &NaiveDate) -> Result<Countdown, CwverError> {
+    Ok(Countdown {
+        workdays: effort_until(calendar, &now.date(), target)?,
+        days: target.signed_duration_since(now.date()).num_days(),
+        hours: target.and_hms(0, 0, 0).signed_duration_since(*now).num_hours(),
+    })
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let definitions = vec![
+            ("SOP".to_string(), "23w36.1".to_string()),
+            ("code-freeze".to_string(), "2022-02-04".to_string()),
+        ];
+        let milestones = resolve(&definitions, &Schema::default()).unwrap();
+        assert_eq!(
+            milestones,
+            vec![
+                Milestone {
+                    name: "code-freeze".to_string(),
+                    date: NaiveDate::from_ymd(2022, 2, 4)
+                },
+                Milestone {
+                    name: "SOP".to_string(),
+                    date: NaiveDate::from_ymd(2023, 9, 4)
+                }
+            ]
+        );
+        assert_eq!(find(&milestones, "SOP"), Ok(&milestones[1]));
+        assert_eq!(
+            find(&milestones, "GA"),
+            Err(CwverError::Unknown(
+                "unknown milestone GA, defined are code-freeze, SOP".to_string()
+            ))
+        );
+        assert_eq!(
+            resolve(&[("GA".to_string(), "soon".to_string())], &Schema::default()),
+            Err(CwverError::Parse("Failed to parse ISO date soon".to_string()).context("milestone GA"))
+        );
+    }
+
+    #[test]
+    fn test_workdays_until() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let code_freeze = NaiveDate::from_ymd(2022, 2, 4);
+        let monday = NaiveDate::from_ymd(2022, 1, 31);
+        let next_monday = NaiveDate::from_ymd(2022, 2, 7);
+        assert_eq!(workdays_until(commercial_workdays, &monday, &code_freeze), Ok(4));
+        assert_eq!(workdays_until(commercial_workdays, &next_monday, &code_freeze), Ok(-1));
+        assert_eq!(workdays_until(commercial_workdays, &code_freeze, &code_freeze), Ok(0));
+    }
+
+    #[test]
+    fn test_countdown() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let code_freeze = NaiveDate::from_ymd(2022, 2, 4);
+        assert_eq!(
+            countdown(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2022, 1, 31).and_hms(18, 30, 0),
+                &code_freeze
+            ),
+            Ok(Countdown {
+                workdays: 4.0,
+                days: 4,
+                hours: 77
+            })
+        );
+        assert_eq!(
+            countdown(
+                commercial_workdays,
+                &NaiveDate::from_ymd(2022, 2, 4).and_hms(9, 0, 0),
+                &code_freeze
+            ),
+            Ok(Countdown {
+                workdays: 0.0,
+                days: 0,
+                hours: -9
+            })
+        );
+        let half_day_fridays = &Calendar {
+            weights: hashmap! {5 => 0.5},
+            ..commercial_workdays.clone()
+        };
+        assert_eq!(
+            countdown(
+                half_day_fridays,
+                &NaiveDate::from_ymd(2022, 1, 31).and_hms(18, 30, 0),
+                &code_freeze
+            )
+            .map(|countdown| countdown.workdays),
+            Ok(3.5)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use chrono::NaiveDate;
+use im::HashMap;
+use serde_json::Value;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+/// Holidays of the current and future years may still change, so their cache is refreshed after
+/// this long. Past years are never fetched again.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// The public holidays of a country or region like `DE-BY` in `years`, fetched from the nager.date
+/// API at `endpoint` and cached per country and year below `cache`. When fetching fails the cached
+/// holidays are used, even if they are outdated.
+pub fn holidays(
+    code: &str,
+    years: RangeInclusive<i32>,
+    current_year: i32,
+    endpoint: &str,
+    cache: Option<&Path>,
+) -> Result<HashMap<NaiveDate, Option<String>>, String> {
+    let code = code.to_uppercase();
+    let country = code.split('-').next().unwrap_or_default();
+    let mut holidays = HashMap::new();
+    for year in years {
+        let cached = cache.map(|cache| cache.join("holidays").join(format!("{}-{}.json", country, year)));
+        let fresh = cached
+            .as_deref()
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok())
+            .is_some_and(|modified| year < current_year || is_recent(modified));
+        let json = match (&cached, fresh) {
+            (Some(path), true) => read(path)?,
+            _ => {
+                let url = format!(
+                    "{}/api/v3/PublicHolidays/{}/{}",
+                    endpoint.trim_end_matches('/'),
+                    year,
+                    country
+                );
+                match (fetch(&url), &cached) {
+                    (Ok(json), Some(path)) => {
+                        store(path, &json);
+                        json
+                    }
+                    (Ok(json), None) => json,
+                    (Err(e), Some(path)) if path.is_file() => {
+                        warn!("{}, using the cached holidays of {}", e, path.display());
+                        read(path)?
+                    }
+                    (Err(e), _) => return Err(format!("{} and no holidays of {} for {} are cached", e, year, country)),
+                }
+            }
+        };
+        for (date, name) in parse(&json, &code).map_err(|e| format!("holidays of {} for {}: {}", year, country, e))? {
+            holidays.entry(date).or_insert(Some(name));
+        }
+    }
+    info!("read {} holiday(s) of online:{}", holidays.len(), code);
+    Ok(holidays)
+}
+
+fn is_recent(modified: SystemTime) -> bool {
+    SystemTime::now()
+        .duration_since(modified)
+        .map_or(true, |age| age < MAX_AGE)
+}
+
+/// The body of a GET request, fetched with `curl` so that no TLS implementation has to be built in.
+fn fetch(url: &str) -> Result<String, String> {
+    info!("fetching {}", url);
+    let output = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--location",
+            "--max-time",
+            "10",
+            url,
+        ])
+        .output()
+        .map_err(|e| format!("failed to run curl for {}: {}", url, e))?;
+    match output.status.success() {
+        true => String::from_utf8(output.stdout).map_err(|_| format!("{} returned invalid UTF-8", url)),
+        false => Err(format!(
+            "failed to fetch {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+    }
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))
+}
+
+/// A cache that can't be written only costs another request next time.
+fn store(path: &Path, json: &str) {
+    let stored = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(path, json));
+    if let Err(e) = stored {
+        warn!("failed to cache holidays in {}: {}", path.display(), e);
+    }
+}
+
+/// The holidays of a nager.date response which apply to `code`: nationwide ones, and for a region
+/// like `DE-BY` the ones of that region.
+fn parse(json: &str, code: &str) -> Result<Vec<(NaiveDate, String)>, String> {
+    let entries: Vec<Value> = serde_json::from_str(json).map_err(|e| format!("invalid response: {}", e))?;
+    let mut holidays = vec![];
+    for entry in entries {
+        let date_str = entry["date"].as_str().ok_or("invalid response: holiday without date")?;
+        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+            .map_err(|_| format!("invalid response: failed to parse ISO date {}", date_str))?;
+        let applies = match entry["counties"].as_array() {
+            Some(counties) => counties.iter().any(|county| county.as_str() == Some(code)),
+            None => entry["global"].as_bool().unwrap_or(true),
+        };
+        let name = entry["name"]
+            .as_str()
+            .or_else(|| entry["localName"].as_str())
+            .unwrap_or_default();
+        if applies {
+            holidays.push((date, name.to_string()));
+        }
+    }
+    Ok(holidays)
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[cfg(test)]
+    const RESPONSE: &str = r#"[
+        {"date":"2021-01-06","localName":"Heilige Drei Könige","name":"Epiphany","countryCode":"DE","global":false,"counties":["DE-BW","DE-BY","DE-ST"]},
+        {"date":"2021-10-03","localName":"Tag der Deutschen Einheit","name":"German Unity Day","countryCode":"DE","global":true,"counties":null},
+        {"date":"2021-10-31","localName":"Reformationstag","name":"Reformation Day","countryCode":"DE","global":false,"counties":["DE-BB","DE-SN"]}
+    ]"#;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(RESPONSE, "DE-BY"),
+            Ok(vec![
+                (NaiveDate::from_ymd(2021, 1, 6), "Epiphany".to_string()),
+                (NaiveDate::from_ymd(2021, 10, 3), "German Unity Day".to_string()),
+            ])
+        );
+        assert_eq!(parse(RESPONSE, "DE").unwrap().len(), 1);
+        assert!(parse("<html>", "DE").unwrap_err().starts_with("invalid response: "));
+    }
+
+    #[test]
+    fn test_holidays() {
+        let cache = std::env::temp_dir().join(format!("cwver-test-online-{}", std::process::id()));
+        store(&cache.join("holidays").join("DE-2021.json"), RESPONSE);
+        let unreachable = "http://127.0.0.1:9";
+        let bavaria = holidays("de-by", 2021..=2021, 2026, unreachable, Some(&cache)).unwrap();
+        assert_eq!(bavaria.len(), 2);
+        assert_eq!(
+            bavaria.get(&NaiveDate::from_ymd(2021, 1, 6)),
+            Some(&Some("Epiphany".to_string()))
+        );
+        assert!(holidays("DE", 2022..=2022, 2026, unreachable, Some(&cache))
+            .unwrap_err()
+            .ends_with("and no holidays of 2022 for DE are cached"));
+        fs::remove_dir_all(cache).unwrap();
+    }
+}
@@ -0,0 +1,1898 @@
+use crate::bisect::{test_cycles, Bisection, Plan, Slot};
+use crate::calendar::{Breakdown, Calendar, DayKind, Offset};
+use crate::locale::Locale;
+use crate::milestone::{Countdown, Milestone};
+use crate::parse::{time_suffix, Schema, Tolerance};
+use crate::range::{Chunk, DateRange, Remaining};
+use crate::selftest::SelfTestReport;
+use crate::template::Template;
+use crate::version::CwVersion;
+use crate::yaml;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/// What results are displayed as.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Format {
+    /// Prose for humans.
+    #[default]
+    Plain,
+    /// One JSON document per result, dates as ISO strings, for scripts.
+    Json,
+    /// Like `Json`, as YAML documents each starting with `---`.
+    Yaml,
+    /// Like `Json`, but batch operations emit one self-contained document per line, e.g. per
+    /// converted line, for jq and log processors.
+    Jsonl,
+}
+
+impl Format {
+    pub const NAMES: &'static [&'static str] = &["plain", "json", "yaml", "jsonl"];
+
+    pub fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "yaml" => Ok(Format::Yaml),
+            "jsonl" => Ok(Format::Jsonl),
+            _ => Err(format!(
+                "unknown output format {}, supported are {}",
+                name,
+                Format::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn is_structured(&self) -> bool {
+        self != &Format::Plain
+    }
+
+    /// Renders a document of a structured format.
+    fn render(&self, document: &Value) -> String {
+        match self {
+            Format::Yaml => yaml::to_string(document),
+            _ => document.to_string(),
+        }
+    }
+}
+
+/// How results are displayed. With `quiet` only the essential values are printed, one per line.
+/// Structured formats ignore `quiet` and `locale`. A `template` replaces the output of commands
+/// displaying a single date, whatever the format.
+#[derive(Debug, Clone, Default)]
+pub struct Style {
+    pub format: Format,
+    pub quiet: bool,
+    pub locale: Locale,
+    pub schema: Schema,
+    pub template: Option<Template>,
+}
+
+impl Style {
+    fn cwver(&self, date: &NaiveDate) -> String {
+        CwVersion::from_date(date, &self.schema).format(&self.schema)
+    }
+
+    /// A week as week-only version like `21w45`.
+    fn week(&self, week: &DateRange) -> String {
+        let version = CwVersion::from_date(&week.from, &self.schema);
+        format!("{:02}w{:02}", version.year, version.week)
+    }
+
+    /// A build as cw version, followed by the letter of its slot if there are several per day.
+    fn slot_cwver(&self, slot: &Slot, slots: u32) -> String {
+        match slots > 1 {
+            true => format!("{}{}", self.cwver(&slot.date), slot_letter(slot.index)),
+            false => self.cwver(&slot.date),
+        }
+    }
+
+    /// Like `slot_cwver`, as localized date.
+    fn slot_date(&self, slot: &Slot, slots: u32) -> String {
+        match slots > 1 {
+            true => format!("{}{}", self.locale.date(&slot.date), slot_letter(slot.index)),
+            false => self.locale.date(&slot.date),
+        }
+    }
+
+    /// Like `json_date`, with the letter of its slot if there are several builds per day.
+    fn json_slot(&self, slot: &Slot, slots: u32) -> Value {
+        let mut document = self.json_date(&slot.date);
+        if slots > 1 {
+            document["slot"] = json!(slot_letter(slot.index).to_string());
+        }
+        document
+    }
+
+    /// A date as both cw version string and ISO date. JSON Lines documents carry the fields of the
+    /// cw version as well, so that each line is self-contained.
+    fn json_date(&self, date: &NaiveDate) -> Value {
+        let mut document = json!({ "cwver": self.cwver(date), "date": date.to_string() });
+        if self.format == Format::Jsonl {
+            let version = CwVersion::from_date(date, &self.schema);
+            document["year"] = json!(self.schema.week_year(date));
+            document["week"] = json!(version.week);
+            document["day"] = json!(version.day);
+        }
+        document
+    }
+
+    /// Structured output flags a non-ISO first weekday in an extra field.
+    fn structured(&self, mut document: Value) -> String {
+        if !self.schema.is_iso() {
+            document["first_weekday"] = json!(self.schema.first_weekday.number_from_monday());
+        }
+        self.format.render(&document)
+    }
+
+    /// A date and why it is worked on or not.
+    fn json_day(&self, date: &NaiveDate, kind: &DayKind) -> Value {
+        let mut document = self.json_date(date);
+        document["kind"] = json!(kind_name(kind));
+        match kind {
+            DayKind::Holiday(Some(holiday)) => document["holiday"] = json!(holiday),
+            DayKind::Blackout(Some(blackout)) => document["blackout"] = json!(blackout),
+            _ => {}
+        }
+        document
+    }
+
+    fn describe_day(&self, date: &NaiveDate, kind: &DayKind) -> String {
+        match kind {
+            DayKind::Workday => self.locale.text("workday", "Arbeitstag").to_string(),
+            DayKind::Holiday(None) => self.locale.text("holiday", "Feiertag").to_string(),
+            DayKind::Holiday(Some(holiday)) => format!("{} ({})", self.locale.text("holiday", "Feiertag"), holiday),
+            DayKind::DayOff => format!(
+                "{} ({})",
+                self.locale.text("day off", "arbeitsfreier Tag"),
+                self.locale.weekday(date.weekday())
+            ),
+            DayKind::Blackout(None) => self.locale.text("blackout", "Sperrzeit").to_string(),
+            DayKind::Blackout(Some(blackout)) => {
+                format!("{} ({})", self.locale.text("blackout", "Sperrzeit"), blackout)
+            }
+        }
+    }
+
+    /// Flags cw version strings not following ISO week dates, so they aren't silently misread.
+    fn schema_note(&self) -> String {
+        match self.schema.is_iso() {
+            true => String::new(),
+            false => format!(" (.1 = {})", self.locale.weekday(self.schema.first_weekday)),
+        }
+    }
+}
+
+/// Build metadata embedded by the build script, for bug reports.
+pub fn verbose_version() -> String {
+    format!(
+        "cwver {}\ncommit: {}\nbuilt: {}\ntarget: {}\nfeatures: {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("CWVER_BUILD_COMMIT"),
+        env!("CWVER_BUILD_DATE"),
+        env!("CWVER_BUILD_TARGET"),
+        env!("CWVER_BUILD_FEATURES")
+    )
+}
+
+pub fn today(date: &NaiveDate, style: &Style) -> String {
+    if let Some(template) = &style.template {
+        return template.render(date, None, &style.schema, style.locale);
+    }
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => style.structured(style.json_date(date)),
+        (Format::Plain, true) => style.cwver(date),
+        (Format::Plain, false) => format!(
+            "{} = {}{}",
+            style.locale.text("Today", "Heute"),
+            style.cwver(date),
+            style.schema_note()
+        ),
+    }
+}
+
+/// A shields.io endpoint badge, see <https://shields.io/badges/endpoint-badge>. The document is JSON
+/// regardless of `--output`, as that's what the endpoint has to serve.
+pub fn badge(label: &str, color: &str, date: &NaiveDate, style: &Style) -> String {
+    json!({
+        "schemaVersion": 1,
+        "label": label,
+        "message": style.cwver(date),
+        "color": color,
+    })
+    .to_string()
+}
+
+/// `to_iso` tells whether `input` was converted into an ISO date or into a cw version string.
+pub fn conversion(input: &str, date: &NaiveDate, time: Option<&NaiveTime>, to_iso: bool, style: &Style) -> String {
+    let converted = match to_iso {
+        true => date.to_string(),
+        false => style.cwver(date),
+    } + &time_suffix(time);
+    if let Some(template) = &style.template {
+        return template.render(date, time, &style.schema, style.locale);
+    }
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(date);
+            document["input"] = json!(input);
+            if let Some(time) = time {
+                document["time"] = json!(time.format("%H:%M").to_string());
+            }
+            style.structured(document)
+        }
+        (Format::Plain, true) => converted,
+        (Format::Plain, false) => format!("{} = {}{}", input, converted, style.schema_note()),
+    }
+}
+
+/// A bisection of days, or of builds if there are several `slots` per day. Builds are labelled
+/// with the letter of their slot, e.g. `21w45.7b`.
+pub fn bisect(
+    from: &Slot,
+    till: &Slot,
+    workdays_in_range: u32,
+    middle_of_range: &Bisection<Slot>,
+    slots: u32,
+    style: &Style,
+) -> String {
+    let candidates = match middle_of_range {
+        Bisection::TooClose => vec![],
+        Bisection::Single(middle) => vec![*middle],
+        Bisection::Pair(middle_left, middle_right) => vec![*middle_left, *middle_right],
+    };
+    let cycles = test_cycles(workdays_in_range * slots + till.index - from.index);
+    if style.format.is_structured() || style.quiet {
+        let mut document = range_document(from, till, workdays_in_range, slots, style);
+        document["cycles"] = json!(cycles);
+        return proposed_points(
+            document,
+            &candidates,
+            |slot| style.slot_cwver(slot, slots),
+            |slot| style.json_slot(slot, slots),
+            ("candidates", "candidate"),
+            style,
+        );
+    }
+
+    let locale = &style.locale;
+    let mut lines = vec![regression_range(from, till, workdays_in_range, slots, style)];
+    match middle_of_range {
+        Bisection::TooClose => {
+            lines.push(
+                locale
+                    .text(
+                        "Dates too close to each other, no bisecting necessary",
+                        "Die Daten liegen zu nah beieinander, keine Bisektion nötig",
+                    )
+                    .to_string(),
+            );
+        }
+        Bisection::Single(middle) => {
+            lines.push(format!(
+                "{}:",
+                locale.text("Bisect starting point", "Startpunkt der Bisektion")
+            ));
+            lines.push(format!(
+                " • {} = {}",
+                style.slot_cwver(middle, slots),
+                style.slot_date(middle, slots)
+            ));
+        }
+        Bisection::Pair(middle_left, middle_right) => {
+            lines.push(format!(
+                "{}:",
+                locale.text(
+                    "Two equivalent bisect starting points",
+                    "Zwei gleichwertige Startpunkte der Bisektion"
+                )
+            ));
+            lines.push(format!(
+                " • {} = {}, {}",
+                style.slot_cwver(middle_left, slots),
+                style.slot_date(middle_left, slots),
+                locale.text("or", "oder")
+            ));
+            lines.push(format!(
+                " • {} = {}",
+                style.slot_cwver(middle_right, slots),
+                style.slot_date(middle_right, slots)
+            ));
+        }
+    }
+    if cycles > 0 {
+        lines.push(format!("\n{}", remaining_cycles(cycles, style)));
+    }
+    lines.join("\n")
+}
+
+/// The worst-case number of test cycles left in a bisection.
+fn remaining_cycles(cycles: u32, style: &Style) -> String {
+    format!(
+        "{} {} {}",
+        style.locale.text("At most", "Höchstens"),
+        cycles,
+        style
+            .locale
+            .text("test cycle(s) remaining", "verbleibende(r) Testzyklus/-zyklen")
+    )
+}
+
+/// Evenly spaced test points splitting a regression range into one part more, for testing them in
+/// parallel.
+pub fn split_points(
+    from: &Slot,
+    till: &Slot,
+    workdays_in_range: u32,
+    points: &[Slot],
+    slots: u32,
+    style: &Style,
+) -> String {
+    if style.format.is_structured() || style.quiet {
+        let document = range_document(from, till, workdays_in_range, slots, style);
+        return proposed_points(
+            document,
+            points,
+            |slot| style.slot_cwver(slot, slots),
+            |slot| style.json_slot(slot, slots),
+            ("points", "point"),
+            style,
+        );
+    }
+
+    let locale = &style.locale;
+    let mut lines = vec![regression_range(from, till, workdays_in_range, slots, style)];
+    if points.is_empty() {
+        lines.push(
+            locale
+                .text(
+                    "Dates too close to each other, no bisecting necessary",
+                    "Die Daten liegen zu nah beieinander, keine Bisektion nötig",
+                )
+                .to_string(),
+        );
+        return lines.join("\n");
+    }
+    lines.push(format!(
+        "{} {} {}:",
+        locale.text("Test points splitting it into", "Testpunkte für"),
+        points.len() + 1,
+        locale.text("parts", "Teile")
+    ));
+    for point in points {
+        lines.push(format!(
+            " • {} = {}",
+            style.slot_cwver(point, slots),
+            style.slot_date(point, slots)
+        ));
+    }
+    lines.join("\n")
+}
+
+/// The worst-case decision tree of a bisection, one test per line indented below the outcome
+/// leading to it.
+pub fn plan(from: &Slot, till: &Slot, workdays_in_range: u32, plan: &Plan<Slot>, slots: u32, style: &Style) -> String {
+    if style.format.is_structured() {
+        let mut document = range_document(from, till, workdays_in_range, slots, style);
+        document["cycles"] = json!(plan.depth());
+        document["plan"] = json_plan(plan, slots, style);
+        return style.structured(document);
+    }
+    let mut lines = vec![];
+    if !style.quiet {
+        lines.push(regression_range(from, till, workdays_in_range, slots, style));
+        lines.push(format!(
+            "{} {} {}:",
+            style.locale.text("Bisect plan, at most", "Bisektionsplan, höchstens"),
+            plan.depth(),
+            style.locale.text("test cycle(s)", "Testzyklus/-zyklen")
+        ));
+    }
+    plan_lines(plan, "", 0, slots, style, &mut lines);
+    lines.join("\n")
+}
+
+fn plan_lines(plan: &Plan<Slot>, outcome: &str, depth: usize, slots: u32, style: &Style, lines: &mut Vec<String>) {
+    let locale = &style.locale;
+    let indent = "  ".repeat(depth);
+    match plan {
+        Plan::Found(culprit) => lines.push(format!(
+            "{}{}{} {} ({})",
+            indent,
+            outcome,
+            locale.text("first bad", "erster fehlerhafter"),
+            style.slot_cwver(culprit, slots),
+            style.slot_date(culprit, slots)
+        )),
+        Plan::Test { candidate, good, bad } => {
+            lines.push(format!(
+                "{}{}{} {} ({})",
+                indent,
+                outcome,
+                locale.text("test", "teste"),
+                style.slot_cwver(candidate, slots),
+                style.slot_date(candidate, slots)
+            ));
+            let good_outcome = format!("{}: ", locale.text("good", "gut"));
+            let bad_outcome = format!("{}: ", locale.text("bad", "schlecht"));
+            plan_lines(good, &good_outcome, depth + 1, slots, style, lines);
+            plan_lines(bad, &bad_outcome, depth + 1, slots, style, lines);
+        }
+    }
+}
+
+fn json_plan(plan: &Plan<Slot>, slots: u32, style: &Style) -> Value {
+    match plan {
+        Plan::Found(culprit) => json!({ "first_bad": style.json_slot(culprit, slots) }),
+        Plan::Test { candidate, good, bad } => json!({
+            "test": style.json_slot(candidate, slots),
+            "good": json_plan(good, slots, style),
+            "bad": json_plan(bad, slots, style),
+        }),
+    }
+}
+
+/// The structured or quiet output of bisect, with the points proposed under the `names` of the
+/// array, and of each point in JSON Lines, added to the `document` of the range. Points are
+/// rendered with `label` when quiet and with `json` otherwise.
+fn proposed_points<T>(
+    document: Value,
+    points: &[T],
+    label: impl Fn(&T) -> String,
+    json: impl Fn(&T) -> Value,
+    (names, name): (&str, &str),
+    style: &Style,
+) -> String {
+    if !style.format.is_structured() {
+        let lines: Vec<String> = points.iter().map(label).collect();
+        return lines.join("\n");
+    }
+    let points: Vec<Value> = points.iter().map(json).collect();
+    if style.format == Format::Jsonl {
+        let mut lines = vec![];
+        if points.is_empty() {
+            let mut line = document.clone();
+            line[name] = Value::Null;
+            lines.push(style.structured(line));
+        }
+        for point in points {
+            let mut line = document.clone();
+            line[name] = point;
+            lines.push(style.structured(line));
+        }
+        return lines.join("\n");
+    }
+    let mut document = document;
+    document[names] = json!(points);
+    style.structured(document)
+}
+
+/// A regression range for structured output, with the number of builds if there are several per
+/// day.
+fn range_document(from: &Slot, till: &Slot, workdays_in_range: u32, slots: u32, style: &Style) -> Value {
+    let mut document = json!({
+        "from": style.json_slot(from, slots),
+        "till": style.json_slot(till, slots),
+        "workdays": workdays_in_range,
+    });
+    if slots > 1 {
+        document["builds"] = json!(workdays_in_range * slots + till.index - from.index);
+    }
+    document
+}
+
+/// The heading of bisect, with the number of builds if there are several per day.
+fn regression_range(from: &Slot, till: &Slot, workdays_in_range: u32, slots: u32, style: &Style) -> String {
+    let locale = &style.locale;
+    let builds = match slots > 1 {
+        true => format!(
+            ", {} {}",
+            workdays_in_range * slots + till.index - from.index,
+            locale.text("build(s)", "Build(s)")
+        ),
+        false => String::new(),
+    };
+    format!(
+        "{}:{}\n {:10}  ➔  {:10} ({} {}{})\n",
+        locale.text("Regression Range", "Regressionsbereich"),
+        style.schema_note(),
+        style.slot_date(from, slots),
+        style.slot_date(till, slots),
+        workdays_in_range,
+        locale.text("workday(s)", "Arbeitstag(e)"),
+        builds
+    )
+}
+
+/// A bisection of weeks, e.g. for weekly release branches, counting the weeks with workdays.
+pub fn bisect_weeks(
+    from: &DateRange,
+    till: &DateRange,
+    working_weeks: u32,
+    middle_of_range: &Bisection<DateRange>,
+    style: &Style,
+) -> String {
+    let candidates = match middle_of_range {
+        Bisection::TooClose => vec![],
+        Bisection::Single(middle) => vec![*middle],
+        Bisection::Pair(middle_left, middle_right) => vec![*middle_left, *middle_right],
+    };
+    if style.format.is_structured() || style.quiet {
+        let json_week = |week: &DateRange| json!({ "week": style.week(week), "from": style.json_date(&week.from), "till": style.json_date(&week.till) });
+        let document = json!({
+            "from": json_week(from),
+            "till": json_week(till),
+            "weeks": working_weeks,
+            "cycles": test_cycles(working_weeks),
+        });
+        return proposed_points(
+            document,
+            &candidates,
+            |week| style.week(week),
+            json_week,
+            ("candidates", "candidate"),
+            style,
+        );
+    }
+
+    let locale = &style.locale;
+    let mut lines = vec![];
+    lines.push(format!(
+        "{}:{}\n {}  ➔  {} ({} {})\n",
+        locale.text("Regression Range", "Regressionsbereich"),
+        style.schema_note(),
+        style.week(from),
+        style.week(till),
+        working_weeks,
+        locale.text("working week(s)", "Arbeitswoche(n)")
+    ));
+    let week = |week: &DateRange| {
+        format!(
+            "{} = {} ➔ {}",
+            style.week(week),
+            locale.date(&week.from),
+            locale.date(&week.till)
+        )
+    };
+    match middle_of_range {
+        Bisection::TooClose => {
+            lines.push(
+                locale
+                    .text(
+                        "Weeks too close to each other, no bisecting necessary",
+                        "Die Wochen liegen zu nah beieinander, keine Bisektion nötig",
+                    )
+                    .to_string(),
+            );
+        }
+        Bisection::Single(middle) => {
+            lines.push(format!(
+                "{}:",
+                locale.text("Bisect starting point", "Startpunkt der Bisektion")
+            ));
+            lines.push(format!(" • {}", week(middle)));
+        }
+        Bisection::Pair(middle_left, middle_right) => {
+            lines.push(format!(
+                "{}:",
+                locale.text(
+                    "Two equivalent bisect starting points",
+                    "Zwei gleichwertige Startpunkte der Bisektion"
+                )
+            ));
+            lines.push(format!(" • {}, {}", week(middle_left), locale.text("or", "oder")));
+            lines.push(format!(" • {}", week(middle_right)));
+        }
+    }
+    let cycles = test_cycles(working_weeks);
+    if cycles > 0 {
+        lines.push(format!("\n{}", remaining_cycles(cycles, style)));
+    }
+    lines.join("\n")
+}
+
+/// The letter of a build slot, `a` for the first one.
+pub fn slot_letter(index: u32) -> char {
+    char::from_u32(u32::from('a') + index).unwrap_or('?')
+}
+
+/// A date shifted by calendar days, weeks or workdays.
+pub fn shifted(input: &str, offset: &Offset, date: &NaiveDate, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(date);
+            document["from"] = json!(input);
+            document["offset"] = json!(offset.to_string());
+            style.structured(document)
+        }
+        (Format::Plain, true) => style.cwver(date),
+        (Format::Plain, false) => format!(
+            "{} {} = {} ({}){}",
+            input,
+            offset,
+            style.cwver(date),
+            style.locale.date(date),
+            style.schema_note()
+        ),
+    }
+}
+
+/// The workday following (`next`) or preceding the input.
+pub fn adjacent_workday(input: &str, date: &NaiveDate, next: bool, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(date);
+            document[if next { "after" } else { "before" }] = json!(input);
+            style.structured(document)
+        }
+        (Format::Plain, true) => style.cwver(date),
+        (Format::Plain, false) => format!(
+            "{} {} = {} ({}){}",
+            match next {
+                true => style.locale.text("Next workday after", "Nächster Arbeitstag nach"),
+                false => style.locale.text("Previous workday before", "Letzter Arbeitstag vor"),
+            },
+            input,
+            style.cwver(date),
+            style.locale.date(date),
+            style.schema_note()
+        ),
+    }
+}
+
+/// The n-th workday of a week or month.
+pub fn nth_workday(n: i32, period: &str, date: &NaiveDate, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(date);
+            document["n"] = json!(n);
+            document["of"] = json!(period);
+            style.structured(document)
+        }
+        (Format::Plain, true) => style.cwver(date),
+        (Format::Plain, false) => format!(
+            "{} {} {} {} = {} ({}){}",
+            style.locale.text("Workday", "Arbeitstag"),
+            n,
+            style.locale.text("of", "von"),
+            period,
+            style.cwver(date),
+            style.locale.date(date),
+            style.schema_note()
+        ),
+    }
+}
+
+/// The first or last workday of a month.
+pub fn month_edge(last: bool, month: &str, date: &NaiveDate, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(date);
+            document["month"] = json!(month);
+            document["edge"] = json!(if last { "last" } else { "first" });
+            style.structured(document)
+        }
+        (Format::Plain, true) => style.cwver(date),
+        (Format::Plain, false) => format!(
+            "{} {} = {} ({}){}",
+            match last {
+                true => style.locale.text("Last workday of", "Letzter Arbeitstag von"),
+                false => style.locale.text("First workday of", "Erster Arbeitstag von"),
+            },
+            month,
+            style.cwver(date),
+            style.locale.date(date),
+            style.schema_note()
+        ),
+    }
+}
+
+/// Whether a date is a workday, a holiday or a day off otherwise.
+pub fn day_kind(date: &NaiveDate, kind: &DayKind, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => style.structured(style.json_day(date, kind)),
+        (Format::Plain, true) => match kind {
+            DayKind::Holiday(Some(holiday)) => holiday.clone(),
+            _ => kind_name(kind).to_string(),
+        },
+        (Format::Plain, false) => format!(
+            "{} ({}): {}{}",
+            style.cwver(date),
+            style.locale.date(date),
+            style.describe_day(date, kind),
+            style.schema_note()
+        ),
+    }
+}
+
+/// The weeks and workdays left of a year.
+pub fn remaining(year: i32, from: &NaiveDate, remaining: &Remaining, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => style.structured(json!({
+            "year": year,
+            "from": style.json_date(from),
+            "weeks": remaining.weeks,
+            "workdays": remaining.workdays,
+        })),
+        (Format::Plain, true) => format!("{}w {}wd", remaining.weeks, remaining.workdays),
+        (Format::Plain, false) => format!(
+            "{} {} {} {}: {} {}, {} {}{}",
+            style.locale.text("Remaining in", "Verbleibend in"),
+            year,
+            style.locale.text("from", "ab"),
+            style.cwver(from),
+            remaining.weeks,
+            style.locale.text("week(s)", "Woche(n)"),
+            remaining.workdays,
+            style.locale.text("workday(s)", "Arbeitstag(e)"),
+            style.schema_note()
+        ),
+    }
+}
+
+pub fn workdays_in(range: &DateRange, workdays: usize, style: &Style) -> String {
+    if style.format.is_structured() {
+        return style.structured(json!({
+            "from": style.json_date(&range.from),
+            "till": style.json_date(&range.till),
+            "workdays": workdays
+        }));
+    }
+    match style.quiet {
+        true => workdays.to_string(),
+        false => format!(
+            "{} {} {}..{}{}",
+            workdays,
+            style.locale.text("workday(s) in", "Arbeitstag(e) in"),
+            style.cwver(&range.from),
+            style.cwver(&range.till),
+            style.schema_note()
+        ),
+    }
+}
+
+/// The years with 53 weeks and, with `stats`, how many years lie between them how often.
+pub fn week53(years: &RangeInclusive<i32>, long_years: &[i32], stats: bool, style: &Style) -> String {
+    let mut intervals: BTreeMap<i32, usize> = BTreeMap::new();
+    for pair in long_years.windows(2) {
+        *intervals.entry(pair[1] - pair[0]).or_default() += 1;
+    }
+    if style.format.is_structured() {
+        let mut document = json!({ "from": years.start(), "till": years.end(), "years": long_years });
+        if stats {
+            let intervals: serde_json::Map<String, Value> = intervals
+                .iter()
+                .map(|(interval, count)| (interval.to_string(), json!(count)))
+                .collect();
+            document["intervals"] = json!(intervals);
+        }
+        return style.format.render(&document);
+    }
+    if style.quiet && !stats {
+        return long_years.iter().map(i32::to_string).collect::<Vec<_>>().join("\n");
+    }
+    let years_list = long_years.iter().map(i32::to_string).collect::<Vec<_>>().join(", ");
+    let mut lines = match style.quiet {
+        true => vec![years_list],
+        false => vec![format!(
+            "{} {}..{}: {}",
+            style.locale.text("Years with 53 weeks in", "Jahre mit 53 Wochen in"),
+            years.start(),
+            years.end(),
+            years_list
+        )],
+    };
+    if stats {
+        lines.push(format!(
+            "{} {} {} {}",
+            long_years.len(),
+            style.locale.text("of", "von"),
+            years.end() - years.start() + 1,
+            style.locale.text("years have 53 weeks", "Jahren haben 53 Wochen")
+        ));
+        for (interval, count) in &intervals {
+            lines.push(format!(
+                " • {} {} {}: {}×",
+                interval,
+                style.locale.text("years", "Jahre"),
+                style.locale.text("apart", "Abstand"),
+                count
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// The days not worked on within a range and why, followed by the number of workdays.
+pub fn week(days: &[(NaiveDate, DayKind)], style: &Style) -> String {
+    if style.format.is_structured() {
+        let days: Vec<Value> = days.iter().map(|(date, kind)| style.json_day(date, kind)).collect();
+        return style.structured(json!({ "days": days }));
+    }
+    let lines: Vec<String> = days
+        .iter()
+        .map(|(date, kind)| match style.quiet {
+            true => style.cwver(date),
+            false => format!(
+                "{} ({}): {}",
+                style.cwver(date),
+                style.locale.date(date),
+                style.describe_day(date, kind)
+            ),
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// The Monday and Sunday of a week and optionally its first and last workday. Plain output is
+/// ISO dates only, for use in scripts.
+pub fn week_bounds(input: &str, week: &DateRange, workdays: Option<&(NaiveDate, NaiveDate)>, style: &Style) -> String {
+    if style.format.is_structured() {
+        let mut document = json!({
+            "week": input,
+            "monday": style.json_date(&week.from),
+            "sunday": style.json_date(&week.till),
+        });
+        if let Some((first, last)) = workdays {
+            document["first_workday"] = style.json_date(first);
+            document["last_workday"] = style.json_date(last);
+        }
+        return style.structured(document);
+    }
+    let mut bounds = vec![week.from, week.till];
+    bounds.extend(workdays.iter().flat_map(|(first, last)| [*first, *last]));
+    let dates: Vec<String> = bounds.iter().map(NaiveDate::to_string).collect();
+    match style.quiet {
+        true => dates.join(" "),
+        false => {
+            let mut text = format!("{}: {}..{}", input, dates[0], dates[1]);
+            if workdays.is_some() {
+                text += &format!(
+                    ", {} {}..{}",
+                    style.locale.text("workdays", "Arbeitstage"),
+                    dates[2],
+                    dates[3]
+                );
+            }
+            text
+        }
+    }
+}
+
+pub fn free_days(free_days: &[(NaiveDate, DayKind)], workdays: usize, style: &Style) -> String {
+    if style.format.is_structured() {
+        let free_days: Vec<Value> = free_days
+            .iter()
+            .map(|(date, kind)| style.json_day(date, kind))
+            .collect();
+        return style.structured(json!({ "free_days": free_days, "workdays": workdays }));
+    }
+    let mut lines: Vec<String> = free_days
+        .iter()
+        .map(|(date, kind)| match style.quiet {
+            true => style.cwver(date),
+            false => format!(
+                "{} ({}): {}",
+                style.cwver(date),
+                style.locale.date(date),
+                style.describe_day(date, kind)
+            ),
+        })
+        .collect();
+    if !style.quiet {
+        lines.push(format!(
+            "{} {}, {} {}{}",
+            free_days.len(),
+            style.locale.text("free day(s)", "freie Tag(e)"),
+            workdays,
+            style.locale.text("workday(s)", "Arbeitstag(e)"),
+            style.schema_note()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Bridge days with the holiday they bridge and its name if known.
+pub fn bridge_days(year: i32, bridge_days: &[(NaiveDate, NaiveDate, Option<String>)], style: &Style) -> String {
+    if style.format.is_structured() {
+        let bridge_days: Vec<Value> = bridge_days
+            .iter()
+            .map(|(day, holiday, name)| {
+                let mut document = style.json_date(day);
+                document["holiday"] = style.json_date(holiday);
+                if let Some(name) = name {
+                    document["holiday"]["name"] = json!(name);
+                }
+                document
+            })
+            .collect();
+        return style.structured(json!({ "year": year, "bridge_days": bridge_days }));
+    }
+    let mut lines: Vec<String> = bridge_days
+        .iter()
+        .map(|(day, holiday, name)| match style.quiet {
+            true => style.cwver(day),
+            false => {
+                let relation = match day < holiday {
+                    true => style.locale.text("bridge day before", "Brückentag vor"),
+                    false => style.locale.text("bridge day after", "Brückentag nach"),
+                };
+                let holiday = match name {
+                    Some(name) => format!("{} ({})", name, style.cwver(holiday)),
+                    None => format!(
+                        "{} {}",
+                        style.locale.text("the holiday", "dem Feiertag"),
+                        style.cwver(holiday)
+                    ),
+                };
+                format!(
+                    "{} ({}): {} {}",
+                    style.cwver(day),
+                    style.locale.date(day),
+                    relation,
+                    holiday
+                )
+            }
+        })
+        .collect();
+    if !style.quiet {
+        lines.push(format!(
+            "{} {} {}{}",
+            bridge_days.len(),
+            style.locale.text("bridge day(s) in", "Brückentag(e) in"),
+            year,
+            style.schema_note()
+        ));
+    }
+    lines.join("\n")
+}
+
+fn kind_name(kind: &DayKind) -> &'static str {
+    match kind {
+        DayKind::Workday => "workday",
+        DayKind::Holiday(_) => "holiday",
+        DayKind::DayOff => "day-off",
+        DayKind::Blackout(_) => "blackout",
+    }
+}
+
+/// Weighted workdays rounded to hundredths, which prints whole amounts without decimals.
+fn rounded(workdays: f64) -> f64 {
+    (workdays * 100.0).round() / 100.0
+}
+
+/// Weighted workdays as a JSON integer if whole, so that unweighted amounts stay integers.
+fn json_workdays(workdays: f64) -> Value {
+    match rounded(workdays) {
+        whole if whole.fract() == 0.0 => json!(whole as i64),
+        fractional => json!(fractional),
+    }
+}
+
+/// The time left till a target, or passed since it with negative amounts.
+pub fn countdown(target_input: &str, target: &NaiveDate, countdown: &Countdown, style: &Style) -> String {
+    let Countdown { workdays, days, hours } = *countdown;
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(target);
+            document["target"] = json!(target_input);
+            document["workdays"] = json_workdays(workdays);
+            document["days"] = json!(days);
+            document["hours"] = json!(hours);
+            style.structured(document)
+        }
+        (Format::Plain, true) => format!("{}wd {}d {}h", rounded(workdays), days, hours),
+        (Format::Plain, false) => {
+            let amounts = format!(
+                "{} {}, {} {}, {} {}",
+                rounded(workdays.abs()),
+                style.locale.text("workday(s)", "Arbeitstag(e)"),
+                days.abs(),
+                style.locale.text("day(s)", "Tag(e)"),
+                hours.abs(),
+                style.locale.text("hour(s)", "Stunde(n)")
+            );
+            let remaining = match hours < 0 {
+                true => format!(
+                    "{} {} {}",
+                    style.locale.text("passed", "vorbei seit"),
+                    amounts,
+                    style.locale.text("ago", "")
+                ),
+                false => format!("{} {}", amounts, style.locale.text("left", "übrig")),
+            };
+            let label = match target_input == style.cwver(target) {
+                true => String::new(),
+                false => format!("{} = ", target_input),
+            };
+            format!(
+                "{}{} ({}): {}{}",
+                label,
+                style.cwver(target),
+                style.locale.date(target),
+                remaining.trim_end(),
+                style.schema_note()
+            )
+        }
+    }
+}
+
+/// All milestones, by date.
+pub fn milestones(milestones: &[Milestone], style: &Style) -> String {
+    if style.format.is_structured() {
+        let milestones: Vec<Value> = milestones
+            .iter()
+            .map(|milestone| {
+                let mut document = style.json_date(&milestone.date);
+                document["name"] = json!(milestone.name);
+                document
+            })
+            .collect();
+        return style.structured(json!({ "milestones": milestones }));
+    }
+    let width = milestones
+        .iter()
+        .map(|milestone| milestone.name.chars().count())
+        .max()
+        .unwrap_or_default();
+    milestones
+        .iter()
+        .map(|milestone| match style.quiet {
+            true => format!("{} {}", milestone.name, style.cwver(&milestone.date)),
+            false => format!(
+                "{:width$}  {} ({}){}",
+                milestone.name,
+                style.cwver(&milestone.date),
+                style.locale.date(&milestone.date),
+                style.schema_note(),
+                width = width
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The workdays left till a milestone, negative ones if it has passed.
+pub fn milestone_until(milestone: &Milestone, from: &NaiveDate, workdays: i64, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(&milestone.date);
+            document["name"] = json!(milestone.name);
+            document["from"] = style.json_date(from);
+            document["workdays"] = json!(workdays);
+            style.structured(document)
+        }
+        (Format::Plain, true) => workdays.to_string(),
+        (Format::Plain, false) => {
+            let remaining = match workdays < 0 {
+                true => format!(
+                    "{} {} {}",
+                    style.locale.text("passed", "vorbei seit"),
+                    -workdays,
+                    style.locale.text("workday(s) ago", "Arbeitstag(en)")
+                ),
+                false => format!(
+                    "{} {}",
+                    workdays,
+                    style.locale.text("workday(s) left", "Arbeitstag(e) übrig")
+                ),
+            };
+            format!(
+                "{} = {} ({}): {}{}",
+                milestone.name,
+                style.cwver(&milestone.date),
+                style.locale.date(&milestone.date),
+                remaining,
+                style.schema_note()
+            )
+        }
+    }
+}
+
+/// Inputs reprinted as given one per line, e.g. after sorting them.
+pub fn inputs(inputs: &[(&str, NaiveDateTime)], style: &Style) -> String {
+    if style.format.is_structured() {
+        let versions: Vec<Value> = inputs
+            .iter()
+            .map(|(input, at)| {
+                let mut document = style.json_date(&at.date());
+                document["input"] = json!(input);
+                document
+            })
+            .collect();
+        return style.structured(json!({ "versions": versions }));
+    }
+    let lines: Vec<&str> = inputs.iter().map(|(input, _)| *input).collect();
+    lines.join("\n")
+}
+
+/// One input reprinted as given, e.g. the latest of a list.
+pub fn selected(input: &str, date: &NaiveDate, style: &Style) -> String {
+    if style.format.is_structured() {
+        let mut document = style.json_date(date);
+        document["input"] = json!(input);
+        return style.structured(document);
+    }
+    input.to_string()
+}
+
+/// One normalized version per date, with the inputs merged into it unless `--quiet` is given.
+pub fn dedup(groups: &[(NaiveDate, Vec<&str>)], style: &Style) -> String {
+    if style.format.is_structured() {
+        let versions: Vec<Value> = groups
+            .iter()
+            .map(|(date, inputs)| {
+                let mut document = style.json_date(date);
+                document["inputs"] = json!(inputs);
+                document
+            })
+            .collect();
+        return style.structured(json!({ "versions": versions }));
+    }
+    groups
+        .iter()
+        .map(|(date, inputs)| match (style.quiet, inputs.len()) {
+            (false, merged) if merged > 1 => format!(
+                "{} ({} {})",
+                style.cwver(date),
+                style.locale.text("merged", "zusammengefasst aus"),
+                inputs.join(", ")
+            ),
+            _ => style.cwver(date),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Files sorted by the date of the cw version in their name, with the oldest and newest one and
+/// the workdays without any. Quiet output is the paths only.
+pub fn scan(artifacts: &[(PathBuf, NaiveDate)], gaps: &[DateRange], style: &Style) -> String {
+    let (oldest, newest) = (artifacts.first(), artifacts.last());
+    if style.format.is_structured() {
+        let entry = |(path, date): &(PathBuf, NaiveDate)| {
+            let mut document = style.json_date(date);
+            document["path"] = json!(path.display().to_string());
+            document
+        };
+        let gaps: Vec<Value> = gaps
+            .iter()
+            .map(|gap| json!({ "from": style.json_date(&gap.from), "till": style.json_date(&gap.till) }))
+            .collect();
+        if style.format == Format::Jsonl {
+            let lines: Vec<String> = artifacts
+                .iter()
+                .map(|artifact| style.structured(entry(artifact)))
+                .collect();
+            return lines.join("\n");
+        }
+        return style.structured(json!({
+            "artifacts": artifacts.iter().map(entry).collect::<Vec<Value>>(),
+            "oldest": oldest.map(entry),
+            "newest": newest.map(entry),
+            "gaps": gaps,
+        }));
+    }
+    let mut lines: Vec<String> = artifacts
+        .iter()
+        .map(|(path, date)| match style.quiet {
+            true => path.display().to_string(),
+            false => format!("{} ({}) {}", style.cwver(date), style.locale.date(date), path.display()),
+        })
+        .collect();
+    if let (false, Some((_, oldest)), Some((_, newest))) = (style.quiet, oldest, newest) {
+        let gaps: Vec<String> = gaps
+            .iter()
+            .map(|gap| match gap.from == gap.till {
+                true => style.cwver(&gap.from),
+                false => format!("{}..{}", style.cwver(&gap.from), style.cwver(&gap.till)),
+            })
+            .collect();
+        lines.push(format!(
+            "{} {} {} {} {}, {}: {}{}",
+            artifacts.len(),
+            style.locale.text("file(s) from", "Datei(en) von"),
+            style.cwver(oldest),
+            style.locale.text("till", "bis"),
+            style.cwver(newest),
+            style.locale.text("workdays without any", "Arbeitstage ohne"),
+            match gaps.is_empty() {
+                true => style.locale.text("none", "keine").to_string(),
+                false => gaps.join(", "),
+            },
+            style.schema_note()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Files renamed, or to be renamed with `dry_run`. Quiet output is the new paths only.
+pub fn renames(renames: &[(PathBuf, PathBuf)], dry_run: bool, style: &Style) -> String {
+    if style.format.is_structured() {
+        let renames: Vec<Value> = renames
+            .iter()
+            .map(|(from, to)| json!({ "from": from.display().to_string(), "to": to.display().to_string() }))
+            .collect();
+        return style.structured(json!({ "renames": renames, "dry_run": dry_run }));
+    }
+    let mut lines: Vec<String> = renames
+        .iter()
+        .map(|(from, to)| match style.quiet {
+            true => to.display().to_string(),
+            false => format!("{} ➔ {}", from.display(), to.display()),
+        })
+        .collect();
+    if dry_run && !style.quiet {
+        lines.push(format!(
+            "{} {}",
+            renames.len(),
+            style.locale.text(
+                "file(s) would be renamed, nothing was changed (--dry-run)",
+                "Datei(en) würden umbenannt, nichts wurde geändert (--dry-run)"
+            )
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Cw version strings found in a text, one per line with their line number and ISO date.
+pub fn grep(found: &[(usize, &str, NaiveDate)], style: &Style) -> String {
+    if style.format.is_structured() {
+        let matches: Vec<Value> = found
+            .iter()
+            .map(|(line, input, date)| {
+                let mut document = style.json_date(date);
+                document["line"] = json!(line);
+                document["input"] = json!(input);
+                document
+            })
+            .collect();
+        return style.structured(json!({ "matches": matches }));
+    }
+    found
+        .iter()
+        .map(|(line, input, date)| match style.quiet {
+            true => format!("{} {}", input, date),
+            false => format!("{}: {} = {}", line, input, date),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Versions one per line, e.g. the result of a set operation.
+pub fn versions(dates: &[NaiveDate], style: &Style) -> String {
+    if style.format.is_structured() {
+        let versions: Vec<Value> = dates.iter().map(|date| style.json_date(date)).collect();
+        return style.structured(json!({ "versions": versions }));
+    }
+    dates
+        .iter()
+        .map(|date| match style.quiet {
+            true => style.cwver(date),
+            false => format!(
+                "{} ({}){}",
+                style.cwver(date),
+                style.locale.date(date),
+                style.schema_note()
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ranges in canonical form `21w40.1..21w42.3`, one per line.
+pub fn ranges(ranges: &[DateRange], style: &Style) -> String {
+    if style.format.is_structured() {
+        let ranges: Vec<Value> = ranges
+            .iter()
+            .map(|range| json!({ "from": style.json_date(&range.from), "till": style.json_date(&range.till) }))
+            .collect();
+        return style.structured(json!({ "ranges": ranges }));
+    }
+    ranges
+        .iter()
+        .map(|range| {
+            let canonical = format!("{}..{}", style.cwver(&range.from), style.cwver(&range.till));
+            match style.quiet {
+                true => canonical,
+                false => format!(
+                    "{} ({}  ➔  {}){}",
+                    canonical,
+                    style.locale.date(&range.from),
+                    style.locale.date(&range.till),
+                    style.schema_note()
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Named ranges on a shared axis with one column per day, workdays drawn solid and other days
+/// shaded. Milestones within the axis get a row with a marker each.
+pub fn timeline(
+    ranges: &[(String, DateRange)],
+    milestones: &[Milestone],
+    calendar: &Calendar,
+    style: &Style,
+) -> String {
+    let (from, till) = match (
+        ranges.iter().map(|(_, range)| range.from).min(),
+        ranges.iter().map(|(_, range)| range.till).max(),
+    ) {
+        (Some(from), Some(till)) => (from, till),
+        _ => return String::new(),
+    };
+    let milestones: Vec<&Milestone> = milestones
+        .iter()
+        .filter(|milestone| (from..=till).contains(&milestone.date))
+        .collect();
+    if style.format.is_structured() {
+        let ranges: Vec<Value> = ranges
+            .iter()
+            .map(|(name, range)| {
+                json!({ "name": name, "from": style.json_date(&range.from), "till": style.json_date(&range.till) })
+            })
+            .collect();
+        let milestones: Vec<Value> = milestones
+            .iter()
+            .map(|milestone| {
+                let mut document = style.json_date(&milestone.date);
+                document["name"] = json!(milestone.name);
+                document
+            })
+            .collect();
+        return style.structured(json!({ "ranges": ranges, "milestones": milestones }));
+    }
+
+    let mut start = from;
+    while start.weekday() != style.schema.first_weekday {
+        match start.pred_opt() {
+            Some(pred) => start = pred,
+            None => break,
+        }
+    }
+    let days: Vec<NaiveDate> = start.iter_days().take_while(|date| date <= &till).collect();
+    let width = ranges
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .chain(milestones.iter().map(|milestone| milestone.name.as_str()))
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let weeks: String = days
+        .iter()
+        .step_by(7)
+        .map(|date| {
+            let cwver = style.cwver(date);
+            format!("{:7}", cwver.split('.').next().unwrap_or(&cwver))
+        })
+        .collect();
+    let mut lines = vec![format!("{:width$}  {}", "", weeks.trim_end(), width = width)];
+    for (name, range) in ranges {
+        let bar: String = days
+            .iter()
+            .map(|date| match (range.from..=range.till).contains(date) {
+                true if calendar.is_workday(date) => '█',
+                true => '░',
+                false => ' ',
+            })
+            .collect();
+        lines.push(format!("{:width$}  {}", name, bar.trim_end(), width = width));
+    }
+    for milestone in milestones {
+        let offset = days.iter().take_while(|date| date < &&milestone.date).count();
+        lines.push(format!(
+            "{:width$}  {}◆",
+            milestone.name,
+            " ".repeat(offset),
+            width = width
+        ));
+    }
+    lines.join("\n")
+}
+
+/// The chunks of a split range with their workday counts.
+pub fn chunks(chunks: &[Chunk], style: &Style) -> String {
+    if style.format.is_structured() {
+        let chunks: Vec<Value> = chunks
+            .iter()
+            .map(|chunk| {
+                json!({
+                    "from": style.json_date(&chunk.range.from),
+                    "till": style.json_date(&chunk.range.till),
+                    "workdays": chunk.workdays,
+                })
+            })
+            .collect();
+        return style.structured(json!({ "chunks": chunks }));
+    }
+    chunks
+        .iter()
+        .map(|chunk| {
+            let canonical = format!("{}..{}", style.cwver(&chunk.range.from), style.cwver(&chunk.range.till));
+            match style.quiet {
+                true => canonical,
+                false => format!(
+                    "{} ({}  ➔  {}): {} {}{}",
+                    canonical,
+                    style.locale.date(&chunk.range.from),
+                    style.locale.date(&chunk.range.till),
+                    chunk.workdays,
+                    style.locale.text("workday(s)", "Arbeitstag(e)"),
+                    style.schema_note()
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The distance of two dates and whether it is within `tolerance`.
+pub fn comparison(
+    (a_input, a): (&str, &NaiveDate),
+    (b_input, b): (&str, &NaiveDate),
+    distance: u32,
+    tolerance: &Tolerance,
+    style: &Style,
+) -> String {
+    let within = distance <= tolerance.amount;
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let (mut a_json, mut b_json) = (style.json_date(a), style.json_date(b));
+            a_json["input"] = json!(a_input);
+            b_json["input"] = json!(b_input);
+            style.structured(json!({
+                "a": a_json,
+                "b": b_json,
+                "distance": format!("{}{}", distance, tolerance.unit.suffix()),
+                "within": tolerance.to_string(),
+                "ok": within,
+            }))
+        }
+        (Format::Plain, true) => distance.to_string(),
+        (Format::Plain, false) => format!(
+            "{} ➔ {}: {}{} {} {}{}",
+            a_input,
+            b_input,
+            distance,
+            tolerance.unit.suffix(),
+            match within {
+                true => style.locale.text("within", "innerhalb von"),
+                false => style.locale.text("not within", "nicht innerhalb von"),
+            },
+            tolerance,
+            style.schema_note()
+        ),
+    }
+}
+
+/// The order of two dates as lt, eq or gt, or as -1, 0 or 1 with `--quiet`.
+pub fn ordering(
+    (a_input, a): (&str, &NaiveDate),
+    (b_input, b): (&str, &NaiveDate),
+    ordering: Ordering,
+    style: &Style,
+) -> String {
+    let name = match ordering {
+        Ordering::Less => "lt",
+        Ordering::Equal => "eq",
+        Ordering::Greater => "gt",
+    };
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let (mut a_json, mut b_json) = (style.json_date(a), style.json_date(b));
+            a_json["input"] = json!(a_input);
+            b_json["input"] = json!(b_input);
+            style.structured(json!({
+                "a": a_json,
+                "b": b_json,
+                "ordering": name,
+            }))
+        }
+        (Format::Plain, true) => (ordering as i8).to_string(),
+        (Format::Plain, false) => name.to_string(),
+    }
+}
+
+/// One date per line, plain output is the bare cw version strings for use in shell loops.
+pub fn seq(dates: &[NaiveDate], style: &Style) -> String {
+    if let Some(template) = &style.template {
+        let lines: Vec<String> = dates
+            .iter()
+            .map(|date| template.render(date, None, &style.schema, style.locale))
+            .collect();
+        return lines.join("\n");
+    }
+    match style.format {
+        Format::Jsonl => {
+            let lines: Vec<String> = dates
+                .iter()
+                .map(|date| style.structured(style.json_date(date)))
+                .collect();
+            lines.join("\n")
+        }
+        Format::Json | Format::Yaml => {
+            let dates: Vec<Value> = dates.iter().map(|date| style.json_date(date)).collect();
+            style.structured(json!({ "dates": dates }))
+        }
+        Format::Plain => {
+            let lines: Vec<String> = dates.iter().map(|date| style.cwver(date)).collect();
+            lines.join("\n")
+        }
+    }
+}
+
+/// Negative amounts for builds in the future.
+pub fn age(input: &str, date: &NaiveDate, days: i64, workdays: i64, style: &Style) -> String {
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let mut document = style.json_date(date);
+            document["input"] = json!(input);
+            document["days"] = json!(days);
+            document["workdays"] = json!(workdays);
+            style.structured(document)
+        }
+        (Format::Plain, true) => format!("{}d {}wd", days, workdays),
+        (Format::Plain, false) => format!(
+            "{} ({}): {} {}, {} {} {}{}",
+            input,
+            style.locale.date(date),
+            days,
+            style.locale.text("day(s)", "Tag(e)"),
+            workdays,
+            style.locale.text("workday(s)", "Arbeitstag(e)"),
+            style.locale.text("old", "alt"),
+            style.schema_note()
+        ),
+    }
+}
+
+/// The days and workdays between two dates, optionally broken down into full weeks and the rest.
+/// Whole weeks are the calendar days divided by seven, `breakdown` decomposes the days further.
+pub fn diff(
+    (a_input, a): (&str, &NaiveDate),
+    (b_input, b): (&str, &NaiveDate),
+    days: u32,
+    workdays: f64,
+    hours: Option<u32>,
+    breakdown: Option<&Breakdown>,
+    style: &Style,
+) -> String {
+    let weeks = days / 7;
+    match (style.format, style.quiet) {
+        (Format::Json | Format::Jsonl | Format::Yaml, _) => {
+            let (mut a_json, mut b_json) = (style.json_date(a), style.json_date(b));
+            a_json["input"] = json!(a_input);
+            b_json["input"] = json!(b_input);
+            let mut document =
+                json!({ "a": a_json, "b": b_json, "days": days, "workdays": json_workdays(workdays), "weeks": weeks });
+            if let Some(hours) = hours {
+                document["hours"] = json!(hours);
+            }
+            if let Some(breakdown) = breakdown {
+                document["breakdown"] = json!({
+                    "weeks": breakdown.weeks,
+                    "workdays": breakdown.workdays,
+                    "days_off": breakdown.days_off,
+                });
+            }
+            style.structured(document)
+        }
+        (Format::Plain, true) => {
+            let diff = match breakdown {
+                Some(breakdown) => format!("{}w {}wd {}d", breakdown.weeks, breakdown.workdays, breakdown.days_off),
+                None => format!("{}d {}wd {}w", days, rounded(workdays), weeks),
+            };
+            match hours {
+                Some(hours) => format!("{} {}h", diff, hours),
+                None => diff,
+            }
+        }
+        (Format::Plain, false) => {
+            let breakdown = match breakdown {
+                Some(breakdown) => format!(
+                    " = {} {} + {} {} + {} {}",
+                    breakdown.weeks,
+                    style.locale.text("full week(s)", "volle Woche(n)"),
+                    breakdown.workdays,
+                    style.locale.text("workday(s)", "Arbeitstag(e)"),
+                    breakdown.days_off,
+                    style.locale.text("day(s) off", "freie Tag(e)")
+                ),
+                None => String::new(),
+            };
+            let weeks = match breakdown.is_empty() {
+                true => format!(", {} {}", weeks, style.locale.text("full week(s)", "volle Woche(n)")),
+                false => String::new(),
+            };
+            let hours = match hours {
+                Some(hours) => format!(", {} {}", hours, style.locale.text("hour(s)", "Stunde(n)")),
+                None => String::new(),
+            };
+            format!(
+                "{} ➔ {}: {} {}{}, {} {}{}{}{}",
+                a_input,
+                b_input,
+                days,
+                style.locale.text("day(s)", "Tag(e)"),
+                breakdown,
+                rounded(workdays),
+                style.locale.text("workday(s)", "Arbeitstag(e)"),
+                weeks,
+                hours,
+                style.schema_note()
+            )
+        }
+    }
+}
+
+/// An input failing verification.
+pub struct Deviation<'a> {
+    pub input: &'a str,
+    pub reason: String,
+    pub suggestion: Option<String>,
+}
+
+/// A malformed or impossible cw version in the name, without a line, or the content of a file.
+pub struct Issue {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub input: String,
+    pub reason: String,
+}
+
+/// The issues found by linting files, quiet output lists their locations and inputs only.
+pub fn lint(checked: usize, issues: &[Issue], style: &Style) -> String {
+    if style.format.is_structured() {
+        let issues: Vec<Value> = issues
+            .iter()
+            .map(|issue| {
+                json!({
+                    "path": issue.path.display().to_string(),
+                    "line": issue.line,
+                    "input": issue.input,
+                    "reason": issue.reason,
+                })
+            })
+            .collect();
+        return style.structured(json!({ "checked": checked, "issues": issues }));
+    }
+    let mut lines: Vec<String> = issues
+        .iter()
+        .map(|issue| {
+            let location = match issue.line {
+                Some(line) => format!("{}:{}", issue.path.display(), line),
+                None => issue.path.display().to_string(),
+            };
+            match style.quiet {
+                true => format!("{} {}", location, issue.input),
+                false => format!("{}: {}: {}", location, issue.input, issue.reason),
+            }
+        })
+        .collect();
+    if !style.quiet {
+        lines.push(format!(
+            "{} {} {}",
+            checked,
+            style
+                .locale
+                .text("file(s) linted, issues:", "Datei(en) geprüft, Probleme:"),
+            issues.len()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Deviations found by verifying inputs, each with its reason and suggested correction if any.
+/// Quiet output lists the deviating inputs only, paired with their suggested corrections.
+pub fn verification(checked: usize, deviations: &[Deviation], style: &Style) -> String {
+    if style.format.is_structured() {
+        let deviations: Vec<Value> = deviations
+            .iter()
+            .map(|deviation| {
+                let mut document = json!({ "input": deviation.input, "reason": deviation.reason });
+                if let Some(suggestion) = &deviation.suggestion {
+                    document["suggestion"] = json!(suggestion);
+                }
+                document
+            })
+            .collect();
+        return style.structured(json!({ "checked": checked, "deviations": deviations }));
+    }
+    let mut lines: Vec<String> = deviations
+        .iter()
+        .map(|deviation| match (style.quiet, &deviation.suggestion) {
+            (true, Some(suggestion)) => format!("{} {}", deviation.input, suggestion),
+            (true, None) => deviation.input.to_string(),
+            (false, Some(suggestion)) => format!(
+                "{}: {}, {} {}?",
+                deviation.input,
+                deviation.reason,
+                style.locale.text("did you mean", "gemeint ist wohl"),
+                suggestion
+            ),
+            (false, None) => format!("{}: {}", deviation.input, deviation.reason),
+        })
+        .collect();
+    if !style.quiet {
+        lines.push(format!(
+            "{} {} {}",
+            checked,
+            style
+                .locale
+                .text("input(s) verified, deviations:", "Eingabe(n) geprüft, Abweichungen:"),
+            deviations.len()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Quiet output lists all failures.
+pub fn selftest(report: &SelfTestReport, style: &Style) -> String {
+    const MAX_LISTED_FAILURES: usize = 10;
+
+    if style.format.is_structured() {
+        let document = json!({
+            "from": report.from.to_string(),
+            "till": report.till.to_string(),
+            "round_trip_failures": report.round_trip_failures,
+            "symmetry_failures": report.symmetry_failures,
+        });
+        return style.structured(document);
+    }
+    if style.quiet {
+        let failures: Vec<String> = report
+            .round_trip_failures
+            .iter()
+            .chain(&report.symmetry_failures)
+            .map(|failure| failure.to_string())
+            .collect();
+        return failures.join("\n");
+    }
+
+    let locale = &style.locale;
+    let mut lines = vec![format!("{}:", locale.text("Self-test", "Selbsttest"))];
+    lines.push(format!(
+        " {:10}  ➔  {:10}\n",
+        locale.date(&report.from),
+        locale.date(&report.till)
+    ));
+    for (check, failures) in [
+        (
+            locale.text(
+                "format ➔ parse ➔ format round trip",
+                "Formatieren ➔ Parsen ➔ Formatieren",
+            ),
+            &report.round_trip_failures,
+        ),
+        (
+            locale.text("workday count symmetry", "Symmetrie der Arbeitstagzählung"),
+            &report.symmetry_failures,
+        ),
+    ] {
+        match failures.len() {
+            0 => lines.push(format!(" • {}: ok", check)),
+            n => {
+                lines.push(format!(" • {}: {} {}", check, n, locale.text("failure(s)", "Fehler")));
+                for failure in failures.iter().take(MAX_LISTED_FAILURES) {
+                    lines.push(format!("   - {}", failure));
+                }
+                if n > MAX_LISTED_FAILURES {
+                    lines.push("   - ...".to_string());
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+pub fn config_paths(
+    user_config: Option<&Path>,
+    project_config: Option<&Path>,
+    cache: Option<&Path>,
+    style: &Style,
+) -> String {
+    let display = |path: Option<&Path>| path.map(|path| path.display().to_string());
+    if style.format.is_structured() {
+        return style.structured(json!({
+            "user_config": display(user_config),
+            "project_config": display(project_config),
+            "cache": display(cache),
+        }));
+    }
+    if style.quiet {
+        let lines: Vec<String> = [user_config, project_config, cache]
+            .iter()
+            .map(|path| display(*path).unwrap_or_default())
+            .collect();
+        return lines.join("\n");
+    }
+    let locale = &style.locale;
+    let not_found = locale.text("not found", "nicht gefunden");
+    let describe = |path: Option<&Path>| match path {
+        Some(path) if path.exists() => path.display().to_string(),
+        Some(path) => format!("{} ({})", path.display(), not_found),
+        None => format!("({})", not_found),
+    };
+    let labels = [
+        locale.text("User config", "Benutzerkonfiguration"),
+        locale.text("Project config", "Projektkonfiguration"),
+        locale.text("Cache", "Cache"),
+    ];
+    let width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or_default()
+        + 1;
+    let lines: Vec<String> = labels
+        .iter()
+        .zip([user_config, project_config, cache])
+        .map(|(label, path)| format!("{:width$} {}", format!("{}:", label), describe(path), width = width))
+        .collect();
+    lines.join("\n")
+}
+
+/// Strings are displayed without quotes, everything else in TOML syntax.
+pub fn config_value(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+pub fn config_setting(name: &str, value: &toml::Value, source: &dyn Display) -> String {
+    format!("{} = {}  # {}", name, value, source)
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_structured_formats() {
+        let yaml = Style {
+            format: Format::Yaml,
+            ..Style::default()
+        };
+        let deviations = [Deviation {
+            input: "21w4.5",
+            reason: "week must have two digits".to_string(),
+            suggestion: Some("21w04.5".to_string()),
+        }];
+        assert_eq!(
+            verification(2, &deviations, &yaml),
+            [
+                "---",
+                "checked: 2",
+                "deviations:",
+                "  - input: \"21w4.5\"",
+                "    reason: \"week must have two digits\"",
+                "    suggestion: \"21w04.5\"",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            config_paths(Some(Path::new("/etc/cwver.toml")), None, None, &yaml),
+            "---\ncache: null\nproject_config: null\nuser_config: \"/etc/cwver.toml\""
+        );
+        let sunday_first = Style {
+            format: Format::Json,
+            schema: Schema {
+                first_weekday: chrono::Weekday::Sun,
+                ..Schema::default()
+            },
+            ..Style::default()
+        };
+        assert_eq!(
+            config_paths(None, None, None, &sunday_first),
+            r#"{"cache":null,"first_weekday":7,"project_config":null,"user_config":null}"#
+        );
+    }
+}
@@ -0,0 +1,1216 @@
+use crate::calendar::{Offset, OffsetUnit, Unit};
+use crate::error::CwverError;
+use crate::pattern::Pattern;
+use crate::range::DateRange;
+use crate::version::CwVersion;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use im::HashSet;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+#[cfg(feature = "regex")]
+use std::sync::OnceLock;
+use tracing::debug;
+
+pub fn workdays_to_hashset(workdays_of_week: &str) -> Result<HashSet<u32>, CwverError> {
+    let mut v = vec![];
+    for workday_as_str in workdays_of_week.split(",").collect::<Vec<&str>>() {
+        let w = workday_as_str
+            .parse::<u32>()
+            .map_err(|_| CwverError::Parse(format!("failed to parse workday {}", workday_as_str)))?;
+        if !(1..=7).contains(&w) {
+            return Err(CwverError::InvalidWorkday(w));
+        }
+        v.push(w);
+    }
+    Ok(HashSet::from(v))
+}
+
+/// Parses the share of a full workday per day of week, e.g. `5=0.5` for half-day Fridays, separated
+/// by commas. Days of week not given count fully.
+pub fn weights_str(weights: &str) -> Result<im::HashMap<u32, f64>, CwverError> {
+    let mut map = im::HashMap::new();
+    for weight_str in weights.split(',') {
+        let (day, weight) = weight_str
+            .split_once('=')
+            .and_then(|(day, weight)| Some((day.trim().parse::<u32>().ok()?, weight.trim().parse::<f64>().ok()?)))
+            .ok_or_else(|| {
+                CwverError::Parse(format!(
+                    "failed to parse workday weight {}, expected day=weight like 5=0.5",
+                    weight_str
+                ))
+            })?;
+        if !(1..=7).contains(&day) {
+            return Err(CwverError::InvalidWorkday(day));
+        }
+        if !(weight.is_finite() && weight > 0.0) {
+            return Err(CwverError::Parse(format!(
+                "workday weight {} of day {} must be greater than 0",
+                weight, day
+            )));
+        }
+        map.insert(day, weight);
+    }
+    Ok(map)
+}
+
+/// Parses a single year (`2021`) or an inclusive range of years (`2000..2099`).
+pub fn year_range_str(years: &str) -> Result<RangeInclusive<i32>, CwverError> {
+    let parse_year = |year: &str| {
+        year.trim()
+            .parse::<i32>()
+            .map_err(|_| CwverError::Parse(format!("failed to parse year {}", year)))
+    };
+    let range = match years.split_once("..") {
+        Some((from, till)) => parse_year(from)?..=parse_year(till)?,
+        None => parse_year(years)?..=parse_year(years)?,
+    };
+    match range.is_empty() {
+        true => Err(CwverError::OutOfRange(format!("year range {} is empty", years))),
+        false => Ok(range),
+    }
+}
+
+/// How cw version strings are read and written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    /// Reject input with leading or trailing characters around the cw version string.
+    pub strict: bool,
+    /// The weekday of `.1`, Monday for ISO week dates. A week starting on another weekday is the
+    /// one containing the Monday of the ISO week it is named after, e.g. with Sunday as first
+    /// weekday `21w01.1` is the Sunday before `21w01` in ISO terms.
+    pub first_weekday: Weekday,
+    /// The first year of the 100 years two-digit years refer to, e.g. 1970 for 1970 till 2069.
+    pub pivot: i32,
+    /// Write four-digit years like `2021w45.7`. They are read regardless.
+    pub long_year: bool,
+    /// A naming scheme like `B{yy}W{ww}-{d}` replacing `{yy}w{ww}.{d}`.
+    pub pattern: Option<Pattern>,
+}
+
+pub const DEFAULT_PIVOT: i32 = 2000;
+
+impl Default for Schema {
+    fn default() -> Schema {
+        Schema {
+            strict: false,
+            first_weekday: Weekday::Mon,
+            pivot: DEFAULT_PIVOT,
+            long_year: false,
+            pattern: None,
+        }
+    }
+}
+
+impl Schema {
+    pub fn is_iso(&self) -> bool {
+        self.first_weekday == Weekday::Mon
+    }
+
+    /// The year within the pivot window ending in the given two digits, four-digit years are
+    /// taken as they are.
+    pub fn year(&self, year: i32) -> i32 {
+        if !(0..100).contains(&year) {
+            return year;
+        }
+        let year = self.pivot.div_euclid(100) * 100 + year;
+        match year < self.pivot {
+            true => year + 100,
+            false => year,
+        }
+    }
+
+    /// The week-based year a date belongs to.
+    pub fn week_year(&self, date: &NaiveDate) -> i32 {
+        date.checked_add_signed(self.shift()).unwrap_or(*date).iso_week().year()
+    }
+
+    /// Days from the first weekday till the Monday of the ISO week.
+    pub(crate) fn shift(&self) -> Duration {
+        Duration::days(((7 - self.first_weekday.num_days_from_monday()) % 7).into())
+    }
+}
+
+/// 52, or 53 for years with a leap week.
+pub fn weeks_in_year(year: i32) -> u32 {
+    match NaiveDate::from_isoywd_opt(year, 53, Weekday::Mon) {
+        Some(_) => 53,
+        None => 52,
+    }
+}
+
+/// The days of a week-based year, from `.1` of its first week till the last day of its last week.
+pub fn year_range(year: i32, schema: &Schema) -> Result<DateRange, CwverError> {
+    let first_day = |year: i32| {
+        NaiveDate::from_isoywd_opt(year, 1, Weekday::Mon).and_then(|date| date.checked_sub_signed(schema.shift()))
+    };
+    let out_of_range = || CwverError::OutOfRange(format!("year {} out of range", year));
+    let first = first_day(year).ok_or_else(out_of_range)?;
+    let last = year
+        .checked_add(1)
+        .and_then(first_day)
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(out_of_range)?;
+    DateRange::new(first, last)
+}
+
+pub fn cwver_str_to_date(cw_ver_str: &str, schema: &Schema) -> Result<NaiveDate, CwverError> {
+    let version = CwVersion::parse(cw_ver_str, schema)?;
+    debug!(
+        input = cw_ver_str,
+        year = version.year,
+        week = version.week,
+        day_of_week = version.day,
+        strict = schema.strict,
+        first_weekday = %schema.first_weekday,
+        pivot = schema.pivot,
+        "parsed cw version string"
+    );
+    version.to_date(schema)
+}
+
+pub fn iso_str_to_date(iso_str: &str) -> Result<NaiveDate, CwverError> {
+    let date = NaiveDate::parse_from_str(iso_str, "%Y-%m-%d")
+        .map_err(|_| CwverError::Parse(format!("Failed to parse ISO date {}", iso_str)))?;
+    debug!(input = iso_str, %date, "parsed ISO date");
+    Ok(date)
+}
+
+/// A maximum distance between two dates, e.g. `3wd` for 3 workdays or `5d` for 5 calendar days.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub amount: u32,
+    pub unit: Unit,
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.unit.suffix())
+    }
+}
+
+pub fn tolerance_str(tolerance: &str) -> Result<Tolerance, CwverError> {
+    let digits = tolerance.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = match &tolerance[digits.len()..] {
+        "wd" => Unit::Workdays,
+        "d" => Unit::Days,
+        "h" => Unit::Hours,
+        _ => {
+            return Err(CwverError::Parse(format!(
+                "tolerance {} must be given in workdays (3wd), days (3d) or hours (3h)",
+                tolerance
+            )))
+        }
+    };
+    let amount = digits
+        .parse()
+        .map_err(|_| CwverError::Parse(format!("failed to parse tolerance {}", tolerance)))?;
+    Ok(Tolerance { amount, unit })
+}
+
+/// Whether the input is meant as cw version string rather than as ISO date.
+pub fn is_cwver_str(input: &str, schema: &Schema) -> bool {
+    match schema.pattern {
+        Some(_) => CwVersion::parse(input, schema).is_ok(),
+        None => input.contains('w'),
+    }
+}
+
+/// A shift like `+3d`, `-2w` or `+5wd` in calendar days, weeks or workdays. The sign is optional.
+pub fn offset_str(offset: &str) -> Result<Offset, CwverError> {
+    let amount = offset.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = match &offset[amount.len()..] {
+        "d" => OffsetUnit::Days,
+        "w" => OffsetUnit::Weeks,
+        "wd" => OffsetUnit::Workdays,
+        _ => {
+            return Err(CwverError::Parse(format!(
+                "offset {} must be given in days (+3d), weeks (-2w) or workdays (+5wd)",
+                offset
+            )))
+        }
+    };
+    let amount = amount
+        .parse()
+        .map_err(|_| CwverError::Parse(format!("failed to parse offset {}", offset)))?;
+    Ok(Offset { amount, unit })
+}
+
+/// Accepts a cw version string as well as an ISO date. A week-only version like `21w45` is the
+/// first day of the week.
+pub fn date_str(date_str: &str, schema: &Schema) -> Result<NaiveDate, CwverError> {
+    match is_cwver_str(date_str, schema) {
+        true if is_week(date_str) => Ok(week_str(date_str, schema)?.from),
+        true => cwver_str_to_date(date_str, schema),
+        false => iso_str_to_date(date_str),
+    }
+}
+
+/// Like `date_str`, but also accepts loosely written cw version strings like `24w7.1` or `24W07-1`,
+/// e.g. to normalize hand-maintained lists. Custom patterns are matched as they are.
+pub fn loose_date_str(input: &str, schema: &Schema) -> Result<NaiveDate, CwverError> {
+    if schema.pattern.is_some() || iso_str_to_date(input).is_ok() {
+        return date_str(input, schema);
+    }
+    let (year, week, day) = parse_loose_cwver_str(input, schema.strict)
+        .ok_or_else(|| CwverError::Parse(format!("failed to parse {}", input)))?;
+    CwVersion { year, week, day }.to_date(schema)
+}
+
+/// Week-only versions like `21w45`, e.g. of artifacts built once a week.
+pub fn is_week(week: &str) -> bool {
+    week.split_once('w').is_some_and(|(year, week)| {
+        [year, week]
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Splits the letter of a build slot off a cw version or ISO date, e.g. `21w45.7b` into `21w45.7`
+/// and the second slot, which has index 1.
+pub fn split_slot(input: &str) -> (&str, Option<u32>) {
+    let mut chars = input.chars();
+    match (chars.next_back(), chars.next_back()) {
+        (Some(letter @ 'a'..='z'), Some(digit)) if digit.is_ascii_digit() => {
+            (&input[..input.len() - 1], Some(letter as u32 - 'a' as u32))
+        }
+        _ => (input, None),
+    }
+}
+
+/// A week-only version like `21w45`, as the range of its days.
+pub fn week_str(week: &str, schema: &Schema) -> Result<DateRange, CwverError> {
+    let strict = Schema {
+        strict: true,
+        ..schema.clone()
+    };
+    DateRange::new(
+        cwver_str_to_date(&format!("{}.1", week), &strict)?,
+        cwver_str_to_date(&format!("{}.7", week), &strict)?,
+    )
+}
+
+/// The week containing a date, which starts on the `first_weekday` of the schema.
+pub fn week_of(date: &NaiveDate, schema: &Schema) -> Result<DateRange, CwverError> {
+    let day = CwVersion::from_date(date, schema).day;
+    let monday = date
+        .checked_sub_signed(Duration::days(i64::from(day) - 1))
+        .ok_or_else(|| CwverError::OutOfRange(format!("the week of {} starts before the earliest date", date)))?;
+    let sunday = monday
+        .checked_add_signed(Duration::days(6))
+        .ok_or_else(|| CwverError::OutOfRange(format!("the week of {} ends after the latest date", date)))?;
+    DateRange::new(monday, sunday)
+}
+
+/// The consecutive weeks from the one containing `from` till the one containing `till`.
+pub fn weeks_between(from: &NaiveDate, till: &NaiveDate, schema: &Schema) -> Result<Vec<DateRange>, CwverError> {
+    DateRange::new(*from, *till)?;
+    let mut weeks = vec![week_of(from, schema)?];
+    while let Some(next) = weeks
+        .last()
+        .filter(|week| week.till < *till)
+        .map(|week| week.till.succ())
+    {
+        weeks.push(week_of(&next, schema)?);
+    }
+    Ok(weeks)
+}
+
+/// A cw version or ISO date with an optional time of day like `21w45.3T14:30`, for pipelines
+/// producing several builds per day.
+pub fn datetime_str(datetime_str: &str, schema: &Schema) -> Result<(NaiveDate, Option<NaiveTime>), CwverError> {
+    match datetime_str.rsplit_once('T').filter(|(_, time)| time.contains(':')) {
+        Some((date, time)) => {
+            let time = NaiveTime::parse_from_str(time, "%H:%M")
+                .map_err(|_| CwverError::Parse(format!("failed to parse time of day {} of {}", time, datetime_str)))?;
+            Ok((date_str(date, schema)?, Some(time)))
+        }
+        None => Ok((date_str(datetime_str, schema)?, None)),
+    }
+}
+
+/// The start of the day if no time of day is given.
+pub fn at(date: &NaiveDate, time: Option<&NaiveTime>) -> NaiveDateTime {
+    date.and_time(time.copied().unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0)))
+}
+
+/// The suffix like `T14:30` appended to a cw version or ISO date, empty without a time of day.
+pub fn time_suffix(time: Option<&NaiveTime>) -> String {
+    time.map(|time| time.format("T%H:%M").to_string()).unwrap_or_default()
+}
+
+/// A range like `21w40.1..21w50.5` of cw versions or ISO dates. A single date is a range of one day.
+/// Week-only versions like `21w45` cover their whole week, e.g. `21w40..21w42` ends on `21w42.7`.
+pub fn range_str(range: &str, schema: &Schema) -> Result<DateRange, CwverError> {
+    let (from, till) = range.split_once("..").unwrap_or((range, range));
+    let (from, till) = (date_str(from.trim(), schema)?, till.trim());
+    let till = match is_week(till) {
+        true => week_str(till, schema)?.till,
+        false => date_str(till, schema)?,
+    };
+    DateRange::new(from, till)
+}
+
+/// A name followed by a range, e.g. `suite A 21w40.1..21w42.3`. The range is the last word.
+pub fn named_range_str(line: &str, schema: &Schema) -> Result<(String, DateRange), CwverError> {
+    let line = line.trim();
+    match line.rsplit_once(char::is_whitespace) {
+        Some((name, range)) => Ok((name.trim_end().to_string(), range_str(range, schema)?)),
+        None => Err(CwverError::Parse(format!(
+            "{} lacks a name in front of the range",
+            line
+        ))),
+    }
+}
+
+/// A month like `2021-11`, as the range of its days.
+pub fn month_str(month: &str) -> Result<DateRange, CwverError> {
+    let first = month
+        .trim()
+        .split_once('-')
+        .and_then(|(year, month)| year.parse().ok().zip(month.parse().ok()))
+        .and_then(|(year, month)| NaiveDate::from_ymd_opt(year, month, 1))
+        .ok_or_else(|| CwverError::Parse(format!("failed to parse month {}", month)))?;
+    let next = match first.month() {
+        12 => NaiveDate::from_ymd_opt(first.year() + 1, 1, 1),
+        month => NaiveDate::from_ymd_opt(first.year(), month + 1, 1),
+    };
+    let last = next
+        .and_then(|next| next.pred_opt())
+        .ok_or_else(|| CwverError::OutOfRange(format!("month {} out of range", month)))?;
+    DateRange::new(first, last)
+}
+
+/// A week like `21w45` or a month like `2021-11`, as the range of its days.
+pub fn period_str(period: &str, schema: &Schema) -> Result<DateRange, CwverError> {
+    let period = period.trim();
+    if period.contains('-') {
+        return month_str(period);
+    }
+    if !is_week(period) {
+        return Err(CwverError::Parse(format!(
+            "{} is neither a week like 21w45 nor a month like 2021-11",
+            period
+        )));
+    }
+    week_str(period, schema)
+}
+
+/// Extracts year, week and day of week. Unless `strict` is set, the cw version string may be
+/// embedded in surrounding text (e.g. `nightly-21w45.7.tar.gz`).
+#[cfg(feature = "regex")]
+pub(crate) fn parse_cwver_str(cw_ver_str: &str, strict: bool) -> Option<(i32, u32, u32)> {
+    let caps = cwver_regex(strict).captures(cw_ver_str)?;
+
+    Some((
+        caps.get(1)?.as_str().parse().ok()?,
+        caps.get(2)?.as_str().parse().ok()?,
+        caps.get(3)?.as_str().parse().ok()?,
+    ))
+}
+
+/// Compiling the regex is by far the most expensive step of parsing, so it is
+/// done once per process instead of once per parsed string.
+#[cfg(feature = "regex")]
+fn cwver_regex(strict: bool) -> &'static Regex {
+    static CWVER_REGEX: OnceLock<Regex> = OnceLock::new();
+    static STRICT_CWVER_REGEX: OnceLock<Regex> = OnceLock::new();
+    match strict {
+        true => STRICT_CWVER_REGEX.get_or_init(|| Regex::new(r"^([0-9]{4}|[0-9]{2})w([0-9]{2})\.([0-9])$").unwrap()),
+        false => CWVER_REGEX.get_or_init(|| Regex::new(r"([0-9]{4}|[0-9]{2})w([0-9]{2})\.([0-9])").unwrap()),
+    }
+}
+
+/// Hand-written equivalent of the regex based parser for builds without the `regex` feature.
+#[cfg(not(feature = "regex"))]
+pub(crate) fn parse_cwver_str(cw_ver_str: &str, strict: bool) -> Option<(i32, u32, u32)> {
+    // everything from the `w` on
+    const TAIL: usize = "Www.d".len();
+    let bytes = cw_ver_str.as_bytes();
+    let digits = |b: &[u8]| b.iter().all(u8::is_ascii_digit);
+    let matches_at = |w: usize| {
+        let b = &bytes[w..w + TAIL];
+        b[0] == b'w' && digits(&b[1..3]) && b[3] == b'.' && digits(&b[4..5])
+    };
+    let year_digits = |w: usize| match w {
+        w if w >= 4 && digits(&bytes[w - 4..w]) => Some(4),
+        w if w >= 2 && digits(&bytes[w - 2..w]) => Some(2),
+        _ => None,
+    };
+
+    let (start, w) = match bytes.len() {
+        len if len < TAIL => None,
+        len => (0..=len - TAIL)
+            .filter(|&w| matches_at(w))
+            .find_map(|w| year_digits(w).map(|n| (w - n, w))),
+    }?;
+    if strict && (start != 0 || w + TAIL != bytes.len()) {
+        return None;
+    }
+    // all matched bytes are ASCII, so these are valid char boundaries
+    Some((
+        cw_ver_str[start..w].parse().ok()?,
+        cw_ver_str[w + 1..w + 3].parse().ok()?,
+        cw_ver_str[w + 4..w + 5].parse().ok()?,
+    ))
+}
+
+/// Like `parse_cwver_str`, but the week may lack its leading zero, the `w` may be upper case and
+/// the day may be separated by `-` or `_` as well.
+pub(crate) fn parse_loose_cwver_str(cw_ver_str: &str, strict: bool) -> Option<(i32, u32, u32)> {
+    let (range, version) = find_cwver(cw_ver_str, true)?;
+    match strict && range != (0..cw_ver_str.len()) {
+        true => None,
+        false => Some((version.year, version.week, version.day)),
+    }
+}
+
+/// The byte ranges and dates of the ISO dates in a text, e.g. a file name. Digits right before or
+/// after a date rule it out.
+pub fn find_iso_dates(text: &str) -> Vec<(Range<usize>, NaiveDate)> {
+    const LEN: usize = "yyyy-mm-dd".len();
+    let bytes = text.as_bytes();
+    let digit_at = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+    let mut found = vec![];
+    let mut start = 0;
+    while start + LEN <= bytes.len() {
+        let shaped = (0..LEN).all(|i| match i {
+            4 | 7 => bytes[start + i] == b'-',
+            _ => digit_at(start + i),
+        });
+        let isolated = (start == 0 || !digit_at(start - 1)) && !digit_at(start + LEN);
+        // all matched bytes are ASCII, so these are valid char boundaries
+        match shaped && isolated {
+            true => match NaiveDate::parse_from_str(&text[start..start + LEN], "%Y-%m-%d") {
+                Ok(date) => {
+                    found.push((start..start + LEN, date));
+                    start += LEN;
+                }
+                Err(_) => start += 1,
+            },
+            false => start += 1,
+        }
+    }
+    found
+}
+
+/// The byte ranges and fields of the cw version strings in a text, e.g. a log file. With `loose`
+/// loosely written ones are found as well, see `parse_loose_cwver_str`. Four-digit years are
+/// preferred over two-digit ones.
+pub fn find_cwvers(text: &str, loose: bool) -> Vec<(Range<usize>, CwVersion)> {
+    let mut found = vec![];
+    let mut offset = 0;
+    while let Some((range, version)) = find_cwver(&text[offset..], loose) {
+        found.push((offset + range.start..offset + range.end, version));
+        offset += range.end;
+    }
+    found
+}
+
+fn find_cwver(text: &str, loose: bool) -> Option<(Range<usize>, CwVersion)> {
+    let bytes = text.as_bytes();
+    (0..bytes.len())
+        .filter(|&w| bytes[w] == b'w' || (loose && bytes[w] == b'W'))
+        .find_map(|w| {
+            let year_digits = match bytes[..w].iter().rev().take_while(|b| b.is_ascii_digit()).count() {
+                n if n >= 4 => 4,
+                n if n >= 2 => 2,
+                _ => return None,
+            };
+            let week_digits = bytes[w + 1..].iter().take(2).take_while(|b| b.is_ascii_digit()).count();
+            let separator = w + 1 + week_digits;
+            let separated = match loose {
+                true => matches!(bytes.get(separator), Some(b'.' | b'-' | b'_')),
+                false => bytes.get(separator) == Some(&b'.'),
+            };
+            if week_digits == 0
+                || (!loose && week_digits != 2)
+                || !separated
+                || !bytes.get(separator + 1)?.is_ascii_digit()
+            {
+                return None;
+            }
+            // all matched bytes are ASCII, so these are valid char boundaries
+            let version = CwVersion {
+                year: text[w - year_digits..w].parse().ok()?,
+                week: text[w + 1..separator].parse().ok()?,
+                day: text[separator + 1..separator + 2].parse().ok()?,
+            };
+            Some((w - year_digits..separator + 2, version))
+        })
+}
+
+/// Explains precisely why a cw version string is invalid, e.g. that its week doesn't exist in its
+/// year or that week and day are separated by something else than `.`. Weeks lacking their
+/// leading zero like in `24w7.1` are accepted unless `schema.strict` is set. Custom patterns are
+/// only checked to parse and denote an existing date.
+pub fn validate(cw_ver_str: &str, schema: &Schema) -> Result<NaiveDate, CwverError> {
+    if schema.pattern.is_some() {
+        let strict = Schema {
+            strict: true,
+            ..schema.clone()
+        };
+        return cwver_str_to_date(cw_ver_str, &strict);
+    }
+    let syntax = |reason: String| Err(CwverError::Parse(reason));
+    let out_of_range = |reason: String| Err(CwverError::OutOfRange(reason));
+    let digits = |s: &str| s.bytes().take_while(u8::is_ascii_digit).count();
+    let (year, rest) = match cw_ver_str.split_once('w') {
+        Some(split) => split,
+        None if cw_ver_str.contains('W') => {
+            return syntax("the W separating year and week must be lower case".to_string())
+        }
+        None => return syntax("lacks the w separating year and week".to_string()),
+    };
+    match year.len() {
+        0 => return syntax("lacks the year before w".to_string()),
+        len if digits(year) != len => return syntax(format!("year {} must consist of digits", year)),
+        2 | 4 => {}
+        _ => return syntax(format!("year {} must have two or four digits", year)),
+    }
+    let (week, rest) = rest.split_at(digits(rest));
+    match week.len() {
+        0 => return syntax("lacks the week after w".to_string()),
+        1 if schema.strict => return syntax(format!("week {} must be zero-padded to 0{}", week, week)),
+        1 | 2 => {}
+        _ => return syntax(format!("week {} must have two digits", week)),
+    }
+    let rest = match (rest.strip_prefix('.'), rest.chars().next()) {
+        (Some(rest), _) => rest,
+        (None, Some(separator)) => return syntax(format!("separates week and day with {} instead of .", separator)),
+        (None, None) => return syntax("lacks the . and day of week after the week".to_string()),
+    };
+    let (day, trailing) = rest.split_at(digits(rest));
+    if day.is_empty() {
+        return syntax("lacks the day of week after .".to_string());
+    }
+    if !trailing.is_empty() {
+        return syntax(format!("has the trailing text {} after the day of week", trailing));
+    }
+    let (year, week, day): (i32, u32, u32) = match (year.parse(), week.parse(), day.parse()) {
+        (Ok(year), Ok(week), Ok(day)) => (year, week, day),
+        _ => return syntax(format!("failed to parse {}", cw_ver_str)),
+    };
+    let full_year = schema.year(year);
+    let weeks = weeks_in_year(full_year);
+    if week == 0 {
+        return out_of_range("week 00 does not exist, weeks are counted from 01".to_string());
+    }
+    if week > weeks {
+        return out_of_range(format!(
+            "week {} does not exist in {}, which has {} weeks",
+            week, full_year, weeks
+        ));
+    }
+    if !(1..=7).contains(&day) {
+        return out_of_range(format!(
+            "day of week {} is out of range, expected 1 (Monday) till 7 (Sunday)",
+            day
+        ));
+    }
+    CwVersion { year, week, day }.to_date(schema)
+}
+
+/// Checks that a cw version string parses and round-trips to the identical canonical string.
+pub fn verify(cw_ver_str: &str, schema: &Schema) -> Result<NaiveDate, CwverError> {
+    let date = cwver_str_to_date(cw_ver_str, schema)?;
+    let canonical = CwVersion::from_date(&date, schema).format(schema);
+    match canonical == cw_ver_str {
+        true => Ok(date),
+        false => Err(CwverError::Parse(format!("round-trips to {} instead", canonical))),
+    }
+}
+
+/// The most likely intended valid cw version for one failing `verify`: surrounding text is
+/// dropped, transposed week digits are swapped back (`21w54.1` ➔ `21w45.1`), and weeks or days
+/// out of range are clamped to the nearest valid one.
+pub fn suggest(cw_ver_str: &str, schema: &Schema) -> Option<String> {
+    let CwVersion {
+        year,
+        week,
+        day: day_of_week,
+    } = CwVersion::parse(
+        cw_ver_str,
+        &Schema {
+            strict: false,
+            ..schema.clone()
+        },
+    )
+    .ok()?;
+    let weeks = weeks_in_year(schema.year(year));
+    let week = match week {
+        week if (1..=weeks).contains(&week) => week,
+        // no year has more than 53 weeks, so the digits are likely transposed
+        week if week > 53 && (1..=weeks).contains(&(week % 10 * 10 + week / 10)) => week % 10 * 10 + week / 10,
+        week => week.clamp(1, weeks),
+    };
+    let suggestion = CwVersion {
+        year,
+        week,
+        day: day_of_week.clamp(1, 7),
+    }
+    .format(schema);
+    verify(&suggestion, schema).ok().map(|_| suggestion)
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[cfg(test)]
+    const STRICT: Schema = Schema {
+        strict: true,
+        first_weekday: Weekday::Mon,
+        pivot: DEFAULT_PIVOT,
+        long_year: false,
+        pattern: None,
+    };
+
+    #[test]
+    fn test_parse_cwver() {
+        assert_eq!(parse_cwver_str("21w01.2", false), Some((21, 1, 2)));
+        assert_eq!(parse_cwver_str("00w00.0", false), Some((0, 0, 0)));
+        assert_eq!(parse_cwver_str("99w99.9", false), Some((99, 99, 9)));
+        assert_eq!(parse_cwver_str("21w1.0", false), None);
+    }
+
+    #[test]
+    fn test_parse_cwver_pathological() {
+        assert_eq!(parse_cwver_str("nightly-21w01.2.tar.gz", false), Some((21, 1, 2)));
+        assert_eq!(parse_cwver_str("nightly-21w01.2.tar.gz", true), None);
+        assert_eq!(parse_cwver_str("21w01.2", true), Some((21, 1, 2)));
+        assert_eq!(parse_cwver_str("21w01.23", true), None);
+        assert_eq!(
+            parse_cwver_str("99999999999999999999999w01.1", false),
+            Some((9999, 1, 1))
+        );
+        assert_eq!(parse_cwver_str("2021w01.2", true), Some((2021, 1, 2)));
+        assert_eq!(parse_cwver_str("nightly-2021w01.2", false), Some((2021, 1, 2)));
+        assert_eq!(parse_cwver_str("021w01.2", true), None);
+        assert_eq!(parse_cwver_str("99999999999999999999999w01.1", true), None);
+        assert_eq!(parse_cwver_str("\u{0662}\u{0661}w01.1", false), None);
+        assert_eq!(parse_cwver_str("", true), None);
+        assert_eq!(parse_cwver_str(&"w.".repeat(100_000), false), None);
+    }
+
+    #[test]
+    fn test_parse_loose_cwver() {
+        assert_eq!(parse_loose_cwver_str("24w7.1", true), Some((24, 7, 1)));
+        assert_eq!(parse_loose_cwver_str("24W07-1", true), Some((24, 7, 1)));
+        assert_eq!(parse_loose_cwver_str("2024w07_1", true), Some((2024, 7, 1)));
+        assert_eq!(parse_loose_cwver_str("nightly-24w7.1.tar.gz", false), Some((24, 7, 1)));
+        assert_eq!(parse_loose_cwver_str("nightly-24w7.1.tar.gz", true), None);
+        assert_eq!(parse_loose_cwver_str("024w07.1", true), None);
+        assert_eq!(parse_loose_cwver_str("24w123.1", false), None);
+        assert_eq!(parse_loose_cwver_str("24w07:1", false), None);
+        assert_eq!(parse_loose_cwver_str("w.w", false), None);
+    }
+
+    #[test]
+    fn test_find_cwvers() {
+        let version = |year, week, day| CwVersion { year, week, day };
+        assert_eq!(
+            find_cwvers("fixed in 24W7-1, see 24w08.1 and 2024w09.2", true),
+            vec![
+                (9..15, version(24, 7, 1)),
+                (21..28, version(24, 8, 1)),
+                (33..42, version(2024, 9, 2))
+            ]
+        );
+        assert_eq!(
+            find_cwvers("fixed in 24W7-1, see 24w08.1", false),
+            vec![(21..28, version(24, 8, 1))]
+        );
+        assert_eq!(find_cwvers("24w7.1 24w07-1 w07.1", false), vec![]);
+    }
+
+    #[test]
+    fn test_find_iso_dates() {
+        assert_eq!(
+            find_iso_dates("nightly-2024-02-12_2024-02-13.tar.gz"),
+            vec![
+                (8..18, NaiveDate::from_ymd(2024, 2, 12)),
+                (19..29, NaiveDate::from_ymd(2024, 2, 13))
+            ]
+        );
+        assert_eq!(find_iso_dates("12024-02-12 2024-02-30 2024-2-12"), vec![]);
+        assert_eq!(
+            find_iso_dates("ä2024-02-12"),
+            vec![(2..12, NaiveDate::from_ymd(2024, 2, 12))]
+        );
+    }
+
+    #[test]
+    fn test_loose_date_str() {
+        let monday = NaiveDate::from_ymd(2024, 2, 12);
+        assert_eq!(loose_date_str("24w7.1", &STRICT), Ok(monday));
+        assert_eq!(loose_date_str("24W07-1", &STRICT), Ok(monday));
+        assert_eq!(loose_date_str("2024-02-12", &STRICT), Ok(monday));
+        assert_eq!(
+            loose_date_str("24w7", &STRICT),
+            Err(CwverError::Parse("failed to parse 24w7".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_weights_str() {
+        assert_eq!(weights_str("1=1, 5=0.5"), Ok(hashmap! {1 => 1.0, 5 => 0.5}));
+        assert_eq!(weights_str("8=1"), Err(CwverError::InvalidWorkday(8)));
+        assert_eq!(
+            weights_str("5=0"),
+            Err(CwverError::Parse(
+                "workday weight 0 of day 5 must be greater than 0".to_string()
+            ))
+        );
+        assert_eq!(
+            weights_str("5:0.5"),
+            Err(CwverError::Parse(
+                "failed to parse workday weight 5:0.5, expected day=weight like 5=0.5".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_split_slot() {
+        assert_eq!(split_slot("21w45.7a"), ("21w45.7", Some(0)));
+        assert_eq!(split_slot("2021-11-14b"), ("2021-11-14", Some(1)));
+        assert_eq!(split_slot("21w45.7"), ("21w45.7", None));
+        assert_eq!(split_slot("a"), ("a", None));
+    }
+
+    #[test]
+    fn test_week_of() {
+        let week = DateRange::new(NaiveDate::from_ymd(2021, 11, 8), NaiveDate::from_ymd(2021, 11, 14)).unwrap();
+        assert_eq!(
+            week_of(&NaiveDate::from_ymd(2021, 11, 10), &Schema::default()),
+            Ok(week)
+        );
+        assert_eq!(
+            week_of(&NaiveDate::from_ymd(2021, 11, 14), &Schema::default()),
+            Ok(week)
+        );
+        assert_eq!(week_str("21w45", &Schema::default()), Ok(week));
+        assert_eq!(
+            weeks_between(
+                &NaiveDate::from_ymd(2021, 11, 14),
+                &NaiveDate::from_ymd(2021, 11, 15),
+                &Schema::default()
+            )
+            .map(|weeks| weeks.len()),
+            Ok(2)
+        );
+        assert!(weeks_between(
+            &NaiveDate::from_ymd(2021, 11, 15),
+            &NaiveDate::from_ymd(2021, 11, 14),
+            &Schema::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_year_range_str() {
+        assert_eq!(year_range_str("2021"), Ok(2021..=2021));
+        assert_eq!(year_range_str("2000..2099"), Ok(2000..=2099));
+        assert_eq!(
+            year_range_str("2099..2000"),
+            Err(CwverError::OutOfRange("year range 2099..2000 is empty".to_string()))
+        );
+        assert_eq!(
+            year_range_str("20x1"),
+            Err(CwverError::Parse("failed to parse year 20x1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cwver_str_to_date() {
+        assert_eq!(
+            cwver_str_to_date("21w01.1", &Schema::default()),
+            Ok(NaiveDate::from_ymd(2021, 1, 4))
+        );
+        assert_eq!(
+            cwver_str_to_date("21w10.7", &Schema::default()),
+            Ok(NaiveDate::from_ymd(2021, 3, 14))
+        );
+        assert_eq!(
+            cwver_str_to_date("21w52.7", &Schema::default()),
+            Ok(NaiveDate::from_ymd(2022, 1, 2))
+        );
+        assert_eq!(
+            cwver_str_to_date("21w52.0", &Schema::default()),
+            Err(CwverError::OutOfRange("day of week 0 out-of-range [1-7]".to_string()))
+        );
+        assert_eq!(
+            cwver_str_to_date("21w00.1", &Schema::default()),
+            Err(CwverError::OutOfRange(
+                "failed to calculate date of 21w00.1".to_string()
+            ))
+        );
+        assert_eq!(
+            cwver_str_to_date("21w53.1", &Schema::default()),
+            Err(CwverError::OutOfRange(
+                "failed to calculate date of 21w53.1".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_first_weekday() {
+        let sunday_first = Schema {
+            first_weekday: Weekday::Sun,
+            ..Schema::default()
+        };
+        assert_eq!(
+            cwver_str_to_date("21w01.1", &sunday_first),
+            Ok(NaiveDate::from_ymd(2021, 1, 3))
+        );
+        assert_eq!(
+            cwver_str_to_date("21w01.7", &sunday_first),
+            Ok(NaiveDate::from_ymd(2021, 1, 9))
+        );
+        assert_eq!(
+            CwVersion::from_date(&NaiveDate::from_ymd(2021, 1, 3), &sunday_first).to_string(),
+            "21w01.1".to_string()
+        );
+        assert_eq!(
+            CwVersion::from_date(&NaiveDate::from_ymd(2021, 1, 9), &sunday_first).to_string(),
+            "21w01.7".to_string()
+        );
+        assert_eq!(
+            CwVersion::from_date(&NaiveDate::from_ymd(2021, 1, 10), &sunday_first).to_string(),
+            "21w02.1".to_string()
+        );
+        assert!(!sunday_first.is_iso());
+    }
+
+    #[test]
+    fn test_pivot() {
+        let window = Schema {
+            pivot: 1970,
+            ..Schema::default()
+        };
+        assert_eq!(window.year(70), 1970);
+        assert_eq!(window.year(69), 2069);
+        assert_eq!(Schema::default().year(99), 2099);
+        assert_eq!(window.year(2021), 2021);
+        assert_eq!(
+            cwver_str_to_date("99w01.1", &window),
+            Ok(NaiveDate::from_ymd(1999, 1, 4))
+        );
+    }
+
+    #[test]
+    fn test_offset_str() {
+        assert_eq!(
+            offset_str("+3d"),
+            Ok(Offset {
+                amount: 3,
+                unit: OffsetUnit::Days
+            })
+        );
+        assert_eq!(
+            offset_str("-2w"),
+            Ok(Offset {
+                amount: -2,
+                unit: OffsetUnit::Weeks
+            })
+        );
+        assert_eq!(
+            offset_str("5wd"),
+            Ok(Offset {
+                amount: 5,
+                unit: OffsetUnit::Workdays
+            })
+        );
+        assert_eq!(
+            offset_str("+3"),
+            Err(CwverError::Parse(
+                "offset +3 must be given in days (+3d), weeks (-2w) or workdays (+5wd)".to_string()
+            ))
+        );
+        assert_eq!(
+            offset_str("+-3d"),
+            Err(CwverError::Parse("failed to parse offset +-3d".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_tolerance_str() {
+        assert_eq!(
+            tolerance_str("3wd"),
+            Ok(Tolerance {
+                amount: 3,
+                unit: Unit::Workdays
+            })
+        );
+        assert_eq!(
+            tolerance_str("10d"),
+            Ok(Tolerance {
+                amount: 10,
+                unit: Unit::Days
+            })
+        );
+        assert_eq!(
+            tolerance_str("4h"),
+            Ok(Tolerance {
+                amount: 4,
+                unit: Unit::Hours
+            })
+        );
+        assert_eq!(
+            tolerance_str("3"),
+            Err(CwverError::Parse(
+                "tolerance 3 must be given in workdays (3wd), days (3d) or hours (3h)".to_string()
+            ))
+        );
+        assert_eq!(
+            tolerance_str("wd"),
+            Err(CwverError::Parse("failed to parse tolerance wd".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_range_str() {
+        assert_eq!(
+            range_str("21w40.1..2021-10-08", &STRICT),
+            DateRange::new(NaiveDate::from_ymd(2021, 10, 4), NaiveDate::from_ymd(2021, 10, 8))
+        );
+        assert_eq!(
+            range_str("21w40.1", &STRICT),
+            DateRange::new(NaiveDate::from_ymd(2021, 10, 4), NaiveDate::from_ymd(2021, 10, 4))
+        );
+        assert_eq!(
+            range_str("21w40.2..21w40.1", &STRICT),
+            Err(CwverError::InvertedRange {
+                from: NaiveDate::from_ymd(2021, 10, 5),
+                till: NaiveDate::from_ymd(2021, 10, 4)
+            })
+        );
+    }
+
+    #[test]
+    fn test_weeks_in_year() {
+        assert_eq!(weeks_in_year(2020), 53);
+        assert_eq!(weeks_in_year(2021), 52);
+        assert_eq!(weeks_in_year(2026), 53);
+    }
+
+    #[test]
+    fn test_verify() {
+        assert_eq!(
+            verify("21w45.7", &Schema::default()),
+            Ok(NaiveDate::from_ymd(2021, 11, 14))
+        );
+        assert_eq!(
+            verify("nightly-21w45.7.tar.gz", &Schema::default()),
+            Err(CwverError::Parse("round-trips to 21w45.7 instead".to_string()))
+        );
+        assert_eq!(
+            verify("21w53.1", &Schema::default()),
+            Err(CwverError::OutOfRange(
+                "failed to calculate date of 21w53.1".to_string()
+            ))
+        );
+        assert_eq!(
+            verify("21w45.8", &Schema::default()),
+            Err(CwverError::OutOfRange("day of week 8 out-of-range [1-7]".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let lenient = Schema::default();
+        assert_eq!(validate("24w07.1", &STRICT), Ok(NaiveDate::from_ymd(2024, 2, 12)));
+        assert_eq!(validate("24w7.1", &lenient), Ok(NaiveDate::from_ymd(2024, 2, 12)));
+        assert_eq!(validate("2020w53.1", &STRICT), Ok(NaiveDate::from_ymd(2020, 12, 28)));
+        let reason = |input: &str, schema: &Schema| validate(input, schema).unwrap_err().to_string();
+        assert_eq!(reason("24w7.1", &STRICT), "week 7 must be zero-padded to 07");
+        assert_eq!(
+            reason("24w54.1", &lenient),
+            "week 54 does not exist in 2024, which has 52 weeks"
+        );
+        assert_eq!(
+            reason("24w53.1", &lenient),
+            "week 53 does not exist in 2024, which has 52 weeks"
+        );
+        assert_eq!(
+            reason("24w00.1", &lenient),
+            "week 00 does not exist, weeks are counted from 01"
+        );
+        assert_eq!(
+            reason("24w07.8", &lenient),
+            "day of week 8 is out of range, expected 1 (Monday) till 7 (Sunday)"
+        );
+        assert_eq!(
+            reason("24w07-1", &lenient),
+            "separates week and day with - instead of ."
+        );
+        assert_eq!(
+            reason("24W07.1", &lenient),
+            "the W separating year and week must be lower case"
+        );
+        assert_eq!(reason("2024-02-12", &lenient), "lacks the w separating year and week");
+        assert_eq!(reason("024w07.1", &lenient), "year 024 must have two or four digits");
+        assert_eq!(reason("v24w07.1", &lenient), "year v24 must consist of digits");
+        assert_eq!(reason("24w123.1", &lenient), "week 123 must have two digits");
+        assert_eq!(reason("24w07", &lenient), "lacks the . and day of week after the week");
+        assert_eq!(reason("24w07.", &lenient), "lacks the day of week after .");
+        assert_eq!(
+            reason("24w07.1.tar.gz", &lenient),
+            "has the trailing text .tar.gz after the day of week"
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        let suggest = |cw_ver_str| suggest(cw_ver_str, &Schema::default());
+        assert_eq!(suggest("21w54.1"), Some("21w45.1".to_string()));
+        assert_eq!(suggest("21w53.3"), Some("21w52.3".to_string()));
+        assert_eq!(suggest("21w00.3"), Some("21w01.3".to_string()));
+        assert_eq!(suggest("21w45.0"), Some("21w45.1".to_string()));
+        assert_eq!(suggest("21w45.9"), Some("21w45.7".to_string()));
+        assert_eq!(suggest("nightly-21w45.7.tar.gz"), Some("21w45.7".to_string()));
+        assert_eq!(suggest("21w99.1"), Some("21w52.1".to_string()));
+        assert_eq!(suggest("tomorrow"), None);
+    }
+
+    #[test]
+    fn test_year_range() {
+        assert_eq!(
+            year_range(2021, &Schema::default()),
+            DateRange::new(NaiveDate::from_ymd(2021, 1, 4), NaiveDate::from_ymd(2022, 1, 2))
+        );
+        let sunday_first = Schema {
+            first_weekday: Weekday::Sun,
+            ..Schema::default()
+        };
+        assert_eq!(sunday_first.week_year(&NaiveDate::from_ymd(2022, 1, 2)), 2022);
+        assert_eq!(Schema::default().week_year(&NaiveDate::from_ymd(2022, 1, 2)), 2021);
+        assert_eq!(
+            year_range(2021, &sunday_first),
+            DateRange::new(NaiveDate::from_ymd(2021, 1, 3), NaiveDate::from_ymd(2022, 1, 1))
+        );
+    }
+
+    #[test]
+    fn test_named_range_str() {
+        assert_eq!(
+            named_range_str("suite A  21w40.1..21w40.5", &STRICT),
+            Ok((
+                "suite A".to_string(),
+                DateRange::new(NaiveDate::from_ymd(2021, 10, 4), NaiveDate::from_ymd(2021, 10, 8)).unwrap()
+            ))
+        );
+        assert_eq!(
+            named_range_str("21w40.1..21w40.5", &STRICT),
+            Err(CwverError::Parse(
+                "21w40.1..21w40.5 lacks a name in front of the range".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_month_str() {
+        assert_eq!(
+            month_str("2021-12"),
+            DateRange::new(NaiveDate::from_ymd(2021, 12, 1), NaiveDate::from_ymd(2021, 12, 31))
+        );
+        assert_eq!(
+            month_str("21w45"),
+            Err(CwverError::Parse("failed to parse month 21w45".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_period_str() {
+        assert_eq!(
+            period_str("21w45", &Schema::default()),
+            DateRange::new(NaiveDate::from_ymd(2021, 11, 8), NaiveDate::from_ymd(2021, 11, 14))
+        );
+        assert_eq!(
+            period_str("2021-12", &Schema::default()),
+            DateRange::new(NaiveDate::from_ymd(2021, 12, 1), NaiveDate::from_ymd(2021, 12, 31))
+        );
+        assert_eq!(
+            period_str("2024-02", &Schema::default()),
+            DateRange::new(NaiveDate::from_ymd(2024, 2, 1), NaiveDate::from_ymd(2024, 2, 29))
+        );
+        assert_eq!(
+            period_str("2021-13", &Schema::default()),
+            Err(CwverError::Parse("failed to parse month 2021-13".to_string()))
+        );
+        assert_eq!(
+            period_str("21w45.1", &Schema::default()),
+            Err(CwverError::Parse(
+                "21w45.1 is neither a week like 21w45 nor a month like 2021-11".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_week_str() {
+        assert!(is_week("21w45"));
+        assert!(!is_week("21w45.1"));
+        assert!(!is_week("21w"));
+        assert_eq!(date_str("21w45", &STRICT), Ok(NaiveDate::from_ymd(2021, 11, 8)));
+        assert_eq!(
+            range_str("21w45", &STRICT),
+            DateRange::new(NaiveDate::from_ymd(2021, 11, 8), NaiveDate::from_ymd(2021, 11, 14))
+        );
+        assert_eq!(
+            range_str("21w44.3..21w45", &STRICT),
+            DateRange::new(NaiveDate::from_ymd(2021, 11, 3), NaiveDate::from_ymd(2021, 11, 14))
+        );
+        assert_eq!(
+            week_str("21w53", &STRICT),
+            Err(CwverError::OutOfRange(
+                "failed to calculate date of 21w53.1".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_date_str() {
+        assert_eq!(date_str("21w45.7", &STRICT), Ok(NaiveDate::from_ymd(2021, 11, 14)));
+        assert_eq!(date_str("2021-11-14", &STRICT), Ok(NaiveDate::from_ymd(2021, 11, 14)));
+        assert_eq!(
+            date_str("14.11.2021", &STRICT),
+            Err(CwverError::Parse("Failed to parse ISO date 14.11.2021".to_string()))
+        );
+        let pattern = Schema {
+            pattern: Some("B{yy}W{ww}-{d}".parse().unwrap()),
+            ..Schema::default()
+        };
+        assert_eq!(date_str("B21W45-7", &pattern), Ok(NaiveDate::from_ymd(2021, 11, 14)));
+        assert_eq!(date_str("2021-11-14", &pattern), Ok(NaiveDate::from_ymd(2021, 11, 14)));
+        assert!(date_str("21w45.7", &pattern).is_err());
+    }
+
+    #[test]
+    fn test_datetime_str() {
+        let wednesday = NaiveDate::from_ymd(2021, 11, 10);
+        assert_eq!(
+            datetime_str("21w45.3T14:30", &STRICT),
+            Ok((wednesday, Some(NaiveTime::from_hms(14, 30, 0))))
+        );
+        assert_eq!(
+            datetime_str("2021-11-10T09:05", &STRICT),
+            Ok((wednesday, Some(NaiveTime::from_hms(9, 5, 0))))
+        );
+        assert_eq!(datetime_str("21w45.3", &STRICT), Ok((wednesday, None)));
+        assert_eq!(
+            datetime_str("21w45.3T25:00", &STRICT),
+            Err(CwverError::Parse(
+                "failed to parse time of day 25:00 of 21w45.3T25:00".to_string()
+            ))
+        );
+        assert_eq!(datetime_str("Test-21w45.3", &Schema::default()), Ok((wednesday, None)));
+        assert_eq!(
+            at(&wednesday, Some(&NaiveTime::from_hms(14, 30, 0))),
+            wednesday.and_hms(14, 30, 0)
+        );
+        assert_eq!(at(&wednesday, None), wednesday.and_hms(0, 0, 0));
+        assert_eq!(time_suffix(Some(&NaiveTime::from_hms(9, 5, 0))), "T09:05".to_string());
+        assert_eq!(time_suffix(None), String::new());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::error::CwverError;
+use crate::version::CwVersion;
+use std::fmt;
+use std::str::FromStr;
+
+/// A naming scheme other than `21w45.7`, e.g. `B{yy}W{ww}-{d}` for builds named `B21W45-7`. The
+/// placeholders `{yy}`, `{ww}` and `{d}` stand for year, week and day of the week, everything else
+/// is literal text. Literal text is matched regardless of case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    source: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Part {
+    Literal(usize, usize),
+    Year,
+    Week,
+    Day,
+}
+
+const PLACEHOLDERS: [(&str, Part); 3] = [("{yy}", Part::Year), ("{ww}", Part::Week), ("{d}", Part::Day)];
+
+impl Pattern {
+    /// Unless `strict` is set, the version may be embedded in surrounding text.
+    pub fn parse(&self, cw_ver_str: &str, strict: bool) -> Option<(i32, u32, u32)> {
+        let starts = match strict {
+            true => 0..1,
+            false => 0..cw_ver_str.len() + 1,
+        };
+        starts
+            .filter(|&start| cw_ver_str.is_char_boundary(start))
+            .find_map(|start| {
+                let mut version = (0, 0, 0);
+                let end = self.match_parts(&self.parts, &cw_ver_str.as_bytes()[start..], &mut version)?;
+                Some(version).filter(|_| !strict || end == cw_ver_str.len())
+            })
+    }
+
+    pub fn format(&self, version: &CwVersion) -> String {
+        self.parts
+            .iter()
+            .map(|part| match *part {
+                Part::Literal(from, till) => self.source[from..till].to_string(),
+                Part::Year => format!("{:02}", version.year),
+                Part::Week => format!("{:02}", version.week),
+                Part::Day => version.day.to_string(),
+            })
+            .collect()
+    }
+
+    /// The number of bytes matched by `parts` at the start of `input`. Four-digit years are
+    /// preferred over two-digit ones.
+    fn match_parts(&self, parts: &[Part], input: &[u8], version: &mut (i32, u32, u32)) -> Option<usize> {
+        let (part, rest) = match parts.split_first() {
+            Some(split) => split,
+            None => return Some(0),
+        };
+        let number = |len: usize| match input.get(..len) {
+            Some(digits) if digits.iter().all(u8::is_ascii_digit) => std::str::from_utf8(digits).ok()?.parse().ok(),
+            _ => None,
+        };
+        let lens: &[usize] = match part {
+            Part::Literal(from, till) => {
+                let literal = &self.source.as_bytes()[*from..*till];
+                return match input.get(..literal.len()) {
+                    Some(matched) if matched.eq_ignore_ascii_case(literal) => {
+                        Some(literal.len() + self.match_parts(rest, &input[literal.len()..], version)?)
+                    }
+                    _ => None,
+                };
+            }
+            Part::Year => &[4, 2],
+            Part::Week => &[2],
+            Part::Day => &[1],
+        };
+        lens.iter().find_map(|&len| {
+            let value: u32 = number(len)?;
+            match part {
+                Part::Year => version.0 = value as i32,
+                Part::Week => version.1 = value,
+                _ => version.2 = value,
+            }
+            Some(len + self.match_parts(rest, &input[len..], version)?)
+        })
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = CwverError;
+
+    fn from_str(pattern: &str) -> Result<Pattern, CwverError> {
+        let error = |reason: String| CwverError::Parse(format!("pattern {} {}", pattern, reason));
+        let mut parts = vec![];
+        let mut literal_start = 0;
+        let mut i = 0;
+        while i < pattern.len() {
+            let placeholder = PLACEHOLDERS
+                .iter()
+                .find(|(placeholder, _)| pattern[i..].starts_with(placeholder));
+            match (placeholder, pattern[i..].starts_with('{')) {
+                (Some((placeholder, part)), _) => {
+                    if parts.contains(part) {
+                        return Err(error(format!("contains {} more than once", placeholder)));
+                    }
+                    if literal_start < i {
+                        parts.push(Part::Literal(literal_start, i));
+                    }
+                    parts.push(*part);
+                    i += placeholder.len();
+                    literal_start = i;
+                }
+                (None, true) => {
+                    let unknown = pattern[i..].split_inclusive('}').next().unwrap_or_default();
+                    return Err(error(format!(
+                        "contains the unknown placeholder {}, supported are {{yy}}, {{ww}} and {{d}}",
+                        unknown
+                    )));
+                }
+                (None, false) => i += pattern[i..].chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        if literal_start < pattern.len() {
+            parts.push(Part::Literal(literal_start, pattern.len()));
+        }
+        if let Some((placeholder, _)) = PLACEHOLDERS.iter().find(|(_, part)| !parts.contains(part)) {
+            return Err(error(format!("lacks {}", placeholder)));
+        }
+        Ok(Pattern {
+            source: pattern.to_string(),
+            parts,
+        })
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let pattern: Pattern = "B{yy}W{ww}-{d}".parse().unwrap();
+        assert_eq!(pattern.parse("B21W45-7", true), Some((21, 45, 7)));
+        assert_eq!(pattern.parse("b2021w45-7", true), Some((2021, 45, 7)));
+        assert_eq!(pattern.parse("nightly-B21W45-7.tar.gz", false), Some((21, 45, 7)));
+        assert_eq!(pattern.parse("nightly-B21W45-7.tar.gz", true), None);
+        assert_eq!(pattern.parse("21w45.7", false), None);
+
+        let compact: Pattern = "{yy}{ww}{d}".parse().unwrap();
+        assert_eq!(compact.parse("21457", true), Some((21, 45, 7)));
+        assert_eq!(compact.parse("2021457", true), Some((2021, 45, 7)));
+        assert_eq!(
+            "ü{yy}w{ww}.{d}".parse::<Pattern>().unwrap().parse("äü21w45.7", false),
+            Some((21, 45, 7))
+        );
+    }
+
+    #[test]
+    fn test_format() {
+        let pattern: Pattern = "B{yy}W{ww}-{d}".parse().unwrap();
+        let version = CwVersion {
+            year: 21,
+            week: 5,
+            day: 7,
+        };
+        assert_eq!(pattern.format(&version), "B21W05-7".to_string());
+        assert_eq!(pattern.to_string(), "B{yy}W{ww}-{d}".to_string());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            "{yy}w{ww}".parse::<Pattern>(),
+            Err(CwverError::Parse("pattern {yy}w{ww} lacks {d}".to_string()))
+        );
+        assert_eq!(
+            "{yy}w{ww}.{d}{d}".parse::<Pattern>(),
+            Err(CwverError::Parse(
+                "pattern {yy}w{ww}.{d}{d} contains {d} more than once".to_string()
+            ))
+        );
+        assert_eq!(
+            "{yyyy}w{ww}.{d}".parse::<Pattern>(),
+            Err(CwverError::Parse(
+                "pattern {yyyy}w{ww}.{d} contains the unknown placeholder {yyyy}, supported are {yy}, {ww} and {d}"
+                    .to_string()
+            ))
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use crate::calendar::{next_workday, previous_workday, shift, Calendar, DayKind, Offset, OffsetUnit};
+use crate::error::CwverError;
+use chrono::NaiveDate;
+
+/// An inclusive range of dates, e.g. a regression window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub till: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(from: NaiveDate, till: NaiveDate) -> Result<DateRange, CwverError> {
+        match from <= till {
+            true => Ok(DateRange { from, till }),
+            false => Err(CwverError::InvertedRange { from, till }),
+        }
+    }
+}
+
+/// Merges overlapping ranges and ranges without a workday between them, e.g. two working weeks
+/// separated by a weekend. The result is sorted and free of overlaps.
+pub fn union(calendar: &Calendar, ranges: &[DateRange]) -> Result<Vec<DateRange>, CwverError> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by_key(|range| (range.from, range.till));
+
+    let mut merged: Vec<DateRange> = vec![];
+    for range in sorted {
+        if let Some(last) = merged.last_mut() {
+            let touching = match next_workday(calendar, &last.till) {
+                Ok(next) => range.from <= next,
+                // nothing follows the last representable date
+                Err(_) => true,
+            };
+            if touching {
+                last.till = last.till.max(range.till);
+                continue;
+            }
+        }
+        merged.push(range);
+    }
+    Ok(merged)
+}
+
+/// A part of a split range and the number of workdays in it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chunk {
+    pub range: DateRange,
+    pub workdays: u32,
+}
+
+/// Divides the workdays of a range into `parts` contiguous chunks whose workday counts differ by
+/// one at most, the earlier chunks taking the remainder. Chunks start and end on workdays.
+pub fn split(calendar: &Calendar, range: &DateRange, parts: u32) -> Result<Vec<Chunk>, CwverError> {
+    let workdays = workdays(calendar, range);
+    let count = workdays.len() as u32;
+    if parts == 0 || parts > count {
+        return Err(CwverError::OutOfRange(format!(
+            "can't split {} workday(s) from {} till {} into {} part(s)",
+            count, range.from, range.till, parts
+        )));
+    }
+
+    let mut chunks = vec![];
+    let mut remaining = &workdays[..];
+    for part in 0..parts {
+        let size = count / parts + u32::from(part < count % parts);
+        let (chunk, rest) = remaining.split_at(size as usize);
+        chunks.push(Chunk {
+            range: DateRange::new(chunk[0], chunk[chunk.len() - 1])?,
+            workdays: size,
+        });
+        remaining = rest;
+    }
+    Ok(chunks)
+}
+
+/// The n-th workday of a range counting from 1, negative n count back from the last workday.
+pub fn nth_workday(calendar: &Calendar, range: &DateRange, n: i32) -> Result<NaiveDate, CwverError> {
+    let workdays = workdays(calendar, range);
+    let index = match n {
+        n if n > 0 => Some(n as usize - 1),
+        n if n < 0 => workdays.len().checked_sub(n.unsigned_abs() as usize),
+        _ => {
+            return Err(CwverError::OutOfRange(
+                "workdays are counted from 1, or from -1 for the last one".to_string(),
+            ))
+        }
+    };
+    index.and_then(|index| workdays.get(index).copied()).ok_or_else(|| {
+        CwverError::OutOfRange(format!(
+            "there are only {} workday(s) from {} till {}",
+            workdays.len(),
+            range.from,
+            range.till
+        ))
+    })
+}
+
+/// What's left of a range from a given day on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Remaining {
+    /// Weeks including the one `from` is in.
+    pub weeks: u32,
+    /// Workdays including `from`.
+    pub workdays: u32,
+}
+
+/// Counts the weeks and workdays left of a range starting at the beginning of a week, e.g. a
+/// year. Nothing is left once the range has passed, and all of it before it has begun.
+pub fn remaining(calendar: &Calendar, range: &DateRange, from: &NaiveDate) -> Remaining {
+    let from = *from.max(&range.from);
+    if from > range.till {
+        return Remaining { weeks: 0, workdays: 0 };
+    }
+    let left = DateRange { from, till: range.till };
+    let days = range.till.signed_duration_since(range.from).num_days() + 1;
+    let days_passed = from.signed_duration_since(range.from).num_days();
+    Remaining {
+        weeks: ((days + 6) / 7 - days_passed / 7) as u32,
+        workdays: workdays(calendar, &left).len() as u32,
+    }
+}
+
+/// Every `step` through a range, e.g. every workday for `1wd`. Steps in workdays start at the
+/// first workday of the range.
+pub fn seq(calendar: &Calendar, range: &DateRange, step: &Offset) -> Result<Vec<NaiveDate>, CwverError> {
+    if step.amount <= 0 {
+        return Err(CwverError::OutOfRange(format!("step {} must be positive", step)));
+    }
+    let mut date = match step.unit {
+        OffsetUnit::Workdays if !calendar.is_workday(&range.from) => next_workday(calendar, &range.from)?,
+        _ => range.from,
+    };
+    let mut dates = vec![];
+    while date <= range.till {
+        dates.push(date);
+        date = match shift(calendar, &date, step) {
+            Ok(next) => next,
+            // the range ends before the last representable date at the latest
+            Err(_) => break,
+        };
+    }
+    Ok(dates)
+}
+
+/// The days of a range not worked on and why.
+pub fn free_days(calendar: &Calendar, range: &DateRange) -> Vec<(NaiveDate, DayKind)> {
+    range
+        .from
+        .iter_days()
+        .take_while(|date| date <= &range.till)
+        .map(|date| (date, calendar.day_kind(&date)))
+        .filter(|(_, kind)| kind != &DayKind::Workday)
+        .collect()
+}
+
+/// The workdays missing between sorted dates, e.g. of nightly builds, as ranges.
+pub fn gaps(calendar: &Calendar, dates: &[NaiveDate]) -> Result<Vec<DateRange>, CwverError> {
+    let mut gaps = vec![];
+    for pair in dates.windows(2) {
+        let first = next_workday(calendar, &pair[0])?;
+        if first < pair[1] {
+            gaps.push(DateRange::new(first, previous_workday(calendar, &pair[1])?)?);
+        }
+    }
+    Ok(gaps)
+}
+
+pub fn workdays(calendar: &Calendar, range: &DateRange) -> Vec<NaiveDate> {
+    range
+        .from
+        .iter_days()
+        .take_while(|date| date <= &range.till)
+        .filter(|date| calendar.is_workday(date))
+        .collect()
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[cfg(test)]
+    fn range(from: (i32, u32, u32), till: (i32, u32, u32)) -> DateRange {
+        DateRange::new(
+            NaiveDate::from_ymd(from.0, from.1, from.2),
+            NaiveDate::from_ymd(till.0, till.1, till.2),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_union() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        assert_eq!(
+            union(
+                commercial_workdays,
+                &[
+                    range((2021, 10, 11), (2021, 10, 15)),
+                    range((2021, 10, 4), (2021, 10, 6)),
+                    range((2021, 10, 5), (2021, 10, 8)),
+                    range((2021, 10, 19), (2021, 10, 20)),
+                ]
+            ),
+            Ok(vec![
+                range((2021, 10, 4), (2021, 10, 15)),
+                range((2021, 10, 19), (2021, 10, 20))
+            ])
+        );
+        assert_eq!(union(commercial_workdays, &[]), Ok(vec![]));
+
+        assert_eq!(
+            DateRange::new(NaiveDate::from_ymd(2021, 10, 5), NaiveDate::from_ymd(2021, 10, 4)),
+            Err(CwverError::InvertedRange {
+                from: NaiveDate::from_ymd(2021, 10, 5),
+                till: NaiveDate::from_ymd(2021, 10, 4)
+            })
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        // 11 workdays from monday till monday two weeks later
+        let two_weeks = range((2021, 10, 4), (2021, 10, 18));
+        let chunks = split(commercial_workdays, &two_weeks, 3).unwrap();
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.range).collect::<Vec<_>>(),
+            vec![
+                range((2021, 10, 4), (2021, 10, 7)),
+                range((2021, 10, 8), (2021, 10, 13)),
+                range((2021, 10, 14), (2021, 10, 18))
+            ]
+        );
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.workdays).collect::<Vec<_>>(),
+            vec![4, 4, 3]
+        );
+        assert_eq!(split(commercial_workdays, &two_weeks, 1).unwrap()[0].range, two_weeks);
+        assert_eq!(
+            split(commercial_workdays, &range((2021, 10, 9), (2021, 10, 10)), 1),
+            Err(CwverError::OutOfRange(
+                "can't split 0 workday(s) from 2021-10-09 till 2021-10-10 into 1 part(s)".to_string()
+            ))
+        );
+        assert!(split(commercial_workdays, &two_weeks, 0).is_err());
+    }
+
+    #[test]
+    fn test_nth_workday() {
+        let calendar = &Calendar {
+            holidays: hashmap! {NaiveDate::from_ymd(2021, 11, 1) => None},
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        let november = range((2021, 11, 1), (2021, 11, 30));
+        assert_eq!(
+            nth_workday(calendar, &november, 1),
+            Ok(NaiveDate::from_ymd(2021, 11, 2))
+        );
+        assert_eq!(
+            nth_workday(calendar, &november, 3),
+            Ok(NaiveDate::from_ymd(2021, 11, 4))
+        );
+        assert_eq!(
+            nth_workday(calendar, &november, -1),
+            Ok(NaiveDate::from_ymd(2021, 11, 30))
+        );
+        assert_eq!(
+            nth_workday(calendar, &november, -3),
+            Ok(NaiveDate::from_ymd(2021, 11, 26))
+        );
+        assert_eq!(
+            nth_workday(calendar, &november, 22),
+            Err(CwverError::OutOfRange(
+                "there are only 21 workday(s) from 2021-11-01 till 2021-11-30".to_string()
+            ))
+        );
+        assert!(nth_workday(calendar, &november, -22).is_err());
+        assert!(nth_workday(calendar, &november, 0).is_err());
+    }
+
+    #[test]
+    fn test_seq() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let step = |amount, unit| Offset { amount, unit };
+        let saturday_till_thursday = range((2021, 11, 6), (2021, 11, 11));
+        assert_eq!(
+            seq(
+                commercial_workdays,
+                &saturday_till_thursday,
+                &step(2, OffsetUnit::Workdays)
+            ),
+            Ok(vec![
+                NaiveDate::from_ymd(2021, 11, 8),
+                NaiveDate::from_ymd(2021, 11, 10)
+            ])
+        );
+        assert_eq!(
+            seq(commercial_workdays, &saturday_till_thursday, &step(3, OffsetUnit::Days)),
+            Ok(vec![NaiveDate::from_ymd(2021, 11, 6), NaiveDate::from_ymd(2021, 11, 9)])
+        );
+        assert_eq!(
+            seq(
+                commercial_workdays,
+                &saturday_till_thursday,
+                &step(1, OffsetUnit::Weeks)
+            )
+            .unwrap()
+            .len(),
+            1
+        );
+        assert_eq!(
+            seq(
+                commercial_workdays,
+                &saturday_till_thursday,
+                &step(-1, OffsetUnit::Days)
+            ),
+            Err(CwverError::OutOfRange("step -1d must be positive".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_free_days() {
+        let calendar = &Calendar {
+            holidays: hashmap! {NaiveDate::from_ymd(2021, 12, 24) => Some("Christmas Eve".to_string())},
+            ..Calendar::new(hashset![1, 2, 3, 4, 5])
+        };
+        assert_eq!(
+            free_days(calendar, &range((2021, 12, 23), (2021, 12, 27))),
+            vec![
+                (
+                    NaiveDate::from_ymd(2021, 12, 24),
+                    DayKind::Holiday(Some("Christmas Eve".to_string()))
+                ),
+                (NaiveDate::from_ymd(2021, 12, 25), DayKind::DayOff),
+                (NaiveDate::from_ymd(2021, 12, 26), DayKind::DayOff)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let date = |day| NaiveDate::from_ymd(2021, 10, day);
+        // a Friday and the following Monday leave no gap, nor do duplicates
+        assert_eq!(
+            gaps(
+                commercial_workdays,
+                &[date(1), date(4), date(4), date(5), date(8), date(13)]
+            ),
+            Ok(vec![
+                range((2021, 10, 6), (2021, 10, 7)),
+                range((2021, 10, 11), (2021, 10, 12))
+            ])
+        );
+        assert_eq!(gaps(commercial_workdays, &[date(1)]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_remaining() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        // ISO year 2021 with 52 weeks
+        let year = range((2021, 1, 4), (2022, 1, 2));
+        assert_eq!(
+            remaining(commercial_workdays, &year, &NaiveDate::from_ymd(2021, 12, 22)),
+            Remaining { weeks: 2, workdays: 8 }
+        );
+        assert_eq!(
+            remaining(commercial_workdays, &year, &NaiveDate::from_ymd(2021, 12, 27)),
+            Remaining { weeks: 1, workdays: 5 }
+        );
+        assert_eq!(
+            remaining(commercial_workdays, &year, &NaiveDate::from_ymd(2020, 6, 1)),
+            Remaining {
+                weeks: 52,
+                workdays: 260
+            }
+        );
+        assert_eq!(
+            remaining(commercial_workdays, &year, &NaiveDate::from_ymd(2022, 1, 3)),
+            Remaining { weeks: 0, workdays: 0 }
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::calendar::{count_workdays, jump_n_workdays, Calendar};
+use crate::error::CwverError;
+use crate::parse::{cwver_str_to_date, Schema};
+use crate::version::CwVersion;
+use chrono::{NaiveDate, Weekday};
+use std::ops::RangeInclusive;
+
+/// Outcome of a self-test run. Failures are human readable descriptions, one per checked date.
+#[derive(Debug, PartialEq)]
+pub struct SelfTestReport {
+    pub from: NaiveDate,
+    pub till: NaiveDate,
+    pub round_trip_failures: Vec<String>,
+    pub symmetry_failures: Vec<String>,
+}
+
+impl SelfTestReport {
+    pub fn failures(&self) -> usize {
+        self.round_trip_failures.len() + self.symmetry_failures.len()
+    }
+}
+
+/// Checks every date of the given ISO week-based years: formatting and parsing must round-trip,
+/// and counting the workdays back from a date n workdays ahead must yield n again.
+pub fn selftest(
+    calendar: &Calendar,
+    years: &RangeInclusive<i32>,
+    schema: &Schema,
+) -> Result<SelfTestReport, CwverError> {
+    let first_day_of_year = |year: i32| {
+        NaiveDate::from_isoywd_opt(year, 1, Weekday::Mon)
+            .ok_or_else(|| CwverError::OutOfRange(format!("year {} out of range", year)))
+    };
+    let out_of_range = || CwverError::OutOfRange("year range out of range".to_string());
+    let from = first_day_of_year(*years.start())?;
+    let till = first_day_of_year(years.end().checked_add(1).ok_or_else(out_of_range)?)?
+        .pred_opt()
+        .ok_or_else(out_of_range)?;
+
+    let mut report = SelfTestReport {
+        from,
+        till,
+        round_trip_failures: vec![],
+        symmetry_failures: vec![],
+    };
+    for date in from.iter_days().take_while(|date| date <= &till) {
+        if let Err(e) = round_trip(&date, schema) {
+            report.round_trip_failures.push(e);
+        }
+        if let Err(e) = workday_symmetry(calendar, &date) {
+            report.symmetry_failures.push(e);
+        }
+    }
+    Ok(report)
+}
+
+fn round_trip(date: &NaiveDate, schema: &Schema) -> Result<(), String> {
+    let strict = Schema {
+        strict: true,
+        ..schema.clone()
+    };
+    let formatted = CwVersion::from_date(date, schema).format(schema);
+    let parsed = cwver_str_to_date(&formatted, &strict).map_err(|e| format!("{} ➔ {}: {}", date, formatted, e))?;
+    let reformatted = CwVersion::from_date(&parsed, schema).format(schema);
+    match &parsed == date && reformatted == formatted {
+        true => Ok(()),
+        false => Err(format!("{} ➔ {} ➔ {} ➔ {}", date, formatted, parsed, reformatted)),
+    }
+}
+
+fn workday_symmetry(calendar: &Calendar, date: &NaiveDate) -> Result<(), String> {
+    for n in [1, calendar.workdays.len() as u32] {
+        let target = jump_n_workdays(date, n, calendar)?;
+        let counted = count_workdays(calendar, date, &target)?;
+        if counted != n {
+            return Err(format!(
+                "{}: jumping {} workday(s) ahead ends at {}, but {} workday(s) are counted in between",
+                date, n, target, counted
+            ));
+        }
+    }
+    Ok(())
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_selftest() {
+        let commercial_workdays = &Calendar::new(hashset![1, 2, 3, 4, 5]);
+        let report = selftest(commercial_workdays, &(2020..=2021), &Schema::default()).unwrap();
+        assert_eq!(report.from, NaiveDate::from_ymd(2019, 12, 30));
+        assert_eq!(report.till, NaiveDate::from_ymd(2022, 1, 2));
+        assert_eq!(report.failures(), 0);
+
+        let report = selftest(&Calendar::new(hashset![3]), &(2099..=2100), &Schema::default()).unwrap();
+        // 2100 is outside the pivot window and written as 2100w01.1 and so on
+        assert_eq!(report.failures(), 0);
+
+        let sunday_first = Schema {
+            first_weekday: Weekday::Sun,
+            ..Schema::default()
+        };
+        assert_eq!(
+            selftest(commercial_workdays, &(2020..=2021), &sunday_first)
+                .unwrap()
+                .failures(),
+            0
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use crate::error::CwverError;
+use chrono::NaiveDate;
+use std::collections::BTreeSet;
+
+/// How two lists of dates are combined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    /// Dates of the first list missing in the second one.
+    Diff,
+    /// Dates contained in both lists.
+    Intersect,
+    /// Dates contained in either list.
+    Union,
+}
+
+impl Operation {
+    pub const NAMES: [&'static str; 3] = ["diff", "intersect", "union"];
+
+    pub fn parse(name: &str) -> Result<Operation, CwverError> {
+        match name {
+            "diff" => Ok(Operation::Diff),
+            "intersect" => Ok(Operation::Intersect),
+            "union" => Ok(Operation::Union),
+            _ => Err(CwverError::Unknown(format!(
+                "unknown set operation {}, supported are {}",
+                name,
+                Operation::NAMES.join(", ")
+            ))),
+        }
+    }
+
+    /// The resulting dates, sorted and without duplicates.
+    pub fn apply(&self, a: &[NaiveDate], b: &[NaiveDate]) -> Vec<NaiveDate> {
+        let (a, b): (BTreeSet<_>, BTreeSet<_>) = (a.iter().copied().collect(), b.iter().copied().collect());
+        match self {
+            Operation::Diff => a.difference(&b).copied().collect(),
+            Operation::Intersect => a.intersection(&b).copied().collect(),
+            Operation::Union => a.union(&b).copied().collect(),
+        }
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let (mon, tue, wed) = (
+            NaiveDate::from_ymd(2021, 10, 4),
+            NaiveDate::from_ymd(2021, 10, 5),
+            NaiveDate::from_ymd(2021, 10, 6),
+        );
+        let (a, b) = (vec![wed, mon, tue, mon], vec![tue, wed]);
+        assert_eq!(Operation::Diff.apply(&a, &b), vec![mon]);
+        assert_eq!(Operation::Diff.apply(&b, &a), vec![]);
+        assert_eq!(Operation::Intersect.apply(&a, &b), vec![tue, wed]);
+        assert_eq!(Operation::Union.apply(&b, &[mon]), vec![mon, tue, wed]);
+        assert_eq!(
+            Operation::parse("xor"),
+            Err(CwverError::Unknown(
+                "unknown set operation xor, supported are diff, intersect, union".to_string()
+            ))
+        );
+    }
+}
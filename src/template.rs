@@ -0,0 +1,152 @@
+use crate::locale::Locale;
+use crate::parse::{at, Schema};
+use crate::version::CwVersion;
+use chrono::{Datelike, NaiveDate, NaiveTime};
+
+/// A line of output like `{yy}w{ww}.{d} built on {iso} ({weekday})`, replacing the regular output
+/// of a date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placeholder {
+    /// The cw version string in the naming scheme of the schema.
+    Cwver,
+    /// Two-digit week-based year.
+    Year,
+    /// Four-digit week-based year.
+    LongYear,
+    Week,
+    Day,
+    /// Name of the day of the week in the locale.
+    Weekday,
+    /// ISO date.
+    Iso,
+    /// Seconds since 1970-01-01 at the start of the day, or at the time of day if given, in UTC.
+    Epoch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+const PLACEHOLDERS: &[(&str, Placeholder)] = &[
+    ("cwver", Placeholder::Cwver),
+    ("yy", Placeholder::Year),
+    ("yyyy", Placeholder::LongYear),
+    ("ww", Placeholder::Week),
+    ("d", Placeholder::Day),
+    ("weekday", Placeholder::Weekday),
+    ("iso", Placeholder::Iso),
+    ("epoch", Placeholder::Epoch),
+];
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Template, String> {
+        let mut parts = vec![];
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let end = rest[start..]
+                .find('}')
+                .map(|end| start + end)
+                .ok_or_else(|| format!("unclosed placeholder {} in template {}", &rest[start..], template))?;
+            let name = &rest[start + 1..end];
+            let placeholder = PLACEHOLDERS
+                .iter()
+                .find(|(known, _)| *known == name)
+                .map(|(_, placeholder)| *placeholder)
+                .ok_or_else(|| {
+                    let names: Vec<String> = PLACEHOLDERS.iter().map(|(name, _)| format!("{{{}}}", name)).collect();
+                    format!("unknown placeholder {{{}}}, supported are {}", name, names.join(", "))
+                })?;
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            parts.push(Part::Placeholder(placeholder));
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        Ok(Template { parts })
+    }
+
+    pub fn render(&self, date: &NaiveDate, time: Option<&NaiveTime>, schema: &Schema, locale: Locale) -> String {
+        let version = CwVersion::from_date(date, schema);
+        let week_year = schema.week_year(date);
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => text.clone(),
+                Part::Placeholder(placeholder) => match placeholder {
+                    Placeholder::Cwver => version.format(schema),
+                    Placeholder::Year => format!("{:02}", week_year.rem_euclid(100)),
+                    Placeholder::LongYear => format!("{:04}", week_year),
+                    Placeholder::Week => format!("{:02}", version.week),
+                    Placeholder::Day => version.day.to_string(),
+                    Placeholder::Weekday => locale.weekday(date.weekday()).to_string(),
+                    Placeholder::Iso => date.to_string(),
+                    Placeholder::Epoch => at(date, time).timestamp().to_string(),
+                },
+            })
+            .collect()
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template = Template::parse("{yy}w{ww}.{d} built on {iso} ({weekday})").unwrap();
+        let sunday = NaiveDate::from_ymd(2021, 11, 14);
+        assert_eq!(
+            template.render(&sunday, None, &Schema::default(), Locale::En),
+            "21w45.7 built on 2021-11-14 (Sunday)".to_string()
+        );
+        assert_eq!(
+            Template::parse("{yyyy}-{ww} {epoch}").unwrap().render(
+                &NaiveDate::from_ymd(2022, 1, 2),
+                None,
+                &Schema::default(),
+                Locale::De
+            ),
+            "2021-52 1641081600".to_string()
+        );
+        assert_eq!(
+            Template::parse("{cwver}").unwrap().render(
+                &sunday,
+                Some(&NaiveTime::from_hms(14, 30, 0)),
+                &Schema::default(),
+                Locale::En
+            ),
+            "21w45.7".to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Template::parse("{week}"),
+            Err(
+                "unknown placeholder {week}, supported are {cwver}, {yy}, {yyyy}, {ww}, {d}, {weekday}, {iso}, {epoch}"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            Template::parse("v{yy"),
+            Err("unclosed placeholder {yy in template v{yy".to_string())
+        );
+        assert_eq!(
+            Template::parse("plain text"),
+            Ok(Template {
+                parts: vec![Part::Literal("plain text".to_string())]
+            })
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::error::CwverError;
+use crate::parse::{parse_cwver_str, Schema};
+use chrono::{Datelike, NaiveDate, Weekday};
+use num::traits::FromPrimitive;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// A calendar week version like `21w45.3`: the two-digit year, the week and the day of the week.
+/// Which date it refers to depends on the `Schema`. The year may also be a four-digit one like in
+/// `2021w45.3`, which is not subject to the pivot.
+///
+/// Versions are compared by calendar position, with two-digit years taken within the default pivot
+/// window: `21w45.3` equals `2021w45.3`, and `2021w01.1` sorts before `22w01.1`.
+#[derive(Debug, Clone, Copy)]
+pub struct CwVersion {
+    pub year: i32,
+    pub week: u32,
+    pub day: u32,
+}
+
+impl CwVersion {
+    /// Unless `schema.strict` is set, the cw version string may be embedded in surrounding text
+    /// (e.g. `nightly-21w45.7.tar.gz`). See `FromStr` for parsing the bare string.
+    pub fn parse(cw_ver_str: &str, schema: &Schema) -> Result<CwVersion, CwverError> {
+        let parsed = match &schema.pattern {
+            Some(pattern) => pattern.parse(cw_ver_str, schema.strict),
+            None => parse_cwver_str(cw_ver_str, schema.strict),
+        };
+        let (year, week, day) = parsed.ok_or_else(|| CwverError::Parse(format!("failed to parse {}", cw_ver_str)))?;
+        Ok(CwVersion { year, week, day })
+    }
+
+    /// Dates whose shifted week is not representable are formatted as ISO week date, cw version
+    /// strings can't express such years anyway. Years outside the pivot window are written with
+    /// four digits, so that they read back as the same date.
+    pub fn from_date(date: &NaiveDate, schema: &Schema) -> CwVersion {
+        let shifted = date.checked_add_signed(schema.shift()).unwrap_or(*date);
+        let iso_week = shifted.iso_week();
+        let two_digit_year = iso_week.year().rem_euclid(100);
+        CwVersion {
+            year: match schema.long_year || schema.year(two_digit_year) != iso_week.year() {
+                true => iso_week.year(),
+                false => two_digit_year,
+            },
+            week: iso_week.week(),
+            day: shifted.weekday().number_from_monday(),
+        }
+    }
+
+    /// The cw version string in the naming scheme of the schema.
+    pub fn format(&self, schema: &Schema) -> String {
+        match &schema.pattern {
+            Some(pattern) => pattern.format(self),
+            None => self.to_string(),
+        }
+    }
+
+    /// The fields compared, hashed and ordered by, with the year normalised.
+    fn key(&self) -> (i32, u32, u32) {
+        (Schema::default().year(self.year), self.week, self.day)
+    }
+
+    pub fn to_date(&self, schema: &Schema) -> Result<NaiveDate, CwverError> {
+        if !(1..=7).contains(&self.day) {
+            return Err(CwverError::OutOfRange(format!(
+                "day of week {} out-of-range [1-7]",
+                self.day
+            )));
+        }
+        let weekday = Weekday::from_u32(self.day - 1)
+            .ok_or_else(|| CwverError::OutOfRange(format!("{} is not a valid day of week", self.day)))?;
+        NaiveDate::from_isoywd_opt(schema.year(self.year), self.week, weekday)
+            .and_then(|date| date.checked_sub_signed(schema.shift()))
+            .ok_or_else(|| CwverError::OutOfRange(format!("failed to calculate date of {}", self)))
+    }
+}
+
+/// Parses exactly one cw version string like `21w45.3`, without surrounding text.
+impl FromStr for CwVersion {
+    type Err = CwverError;
+
+    fn from_str(cw_ver_str: &str) -> Result<CwVersion, CwverError> {
+        let strict = Schema {
+            strict: true,
+            ..Schema::default()
+        };
+        CwVersion::parse(cw_ver_str, &strict)
+    }
+}
+
+impl PartialEq for CwVersion {
+    fn eq(&self, other: &CwVersion) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for CwVersion {}
+
+impl PartialOrd for CwVersion {
+    fn partial_cmp(&self, other: &CwVersion) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CwVersion {
+    fn cmp(&self, other: &CwVersion) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for CwVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// The canonical form like `21w45.3`.
+impl fmt::Display for CwVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}w{:02}.{:01}", self.year, self.week, self.day)
+    }
+}
+
+/// Serialized as the canonical string like `21w45.3`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for CwVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Only accepts versions denoting an existing day with ISO weeks and the default pivot, e.g. no
+/// `21w53.1`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CwVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<CwVersion, D::Error> {
+        let cw_ver_str = String::deserialize(deserializer)?;
+        let version: CwVersion = cw_ver_str.parse().map_err(serde::de::Error::custom)?;
+        version.to_date(&Schema::default()).map_err(serde::de::Error::custom)?;
+        Ok(version)
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+
+    #[cfg(test)]
+    fn version(year: i32, week: u32, day: u32) -> CwVersion {
+        CwVersion { year, week, day }
+    }
+
+    #[test]
+    fn test_cw_version() {
+        assert_eq!(
+            CwVersion::parse("nightly-21w45.3.tar.gz", &Schema::default()),
+            Ok(version(21, 45, 3))
+        );
+        assert_eq!(
+            CwVersion::parse("21w45", &Schema::default()),
+            Err(CwverError::Parse("failed to parse 21w45".to_string()))
+        );
+        assert_eq!(
+            version(21, 45, 3).to_date(&Schema::default()),
+            Ok(NaiveDate::from_ymd(2021, 11, 10))
+        );
+        assert_eq!(
+            CwVersion::from_date(&NaiveDate::from_ymd(2021, 11, 10), &Schema::default()),
+            version(21, 45, 3)
+        );
+        assert_eq!(
+            CwVersion::parse("2021w45.3", &Schema::default()),
+            Ok(version(2021, 45, 3))
+        );
+        assert_eq!(
+            version(2121, 45, 3).to_date(&Schema::default()),
+            Ok(NaiveDate::from_ymd(2121, 11, 5))
+        );
+        assert_eq!(
+            version(21, 45, 8).to_date(&Schema::default()),
+            Err(CwverError::OutOfRange("day of week 8 out-of-range [1-7]".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("21w45.3".parse(), Ok(version(21, 45, 3)));
+        assert_eq!(
+            "nightly-21w45.3".parse::<CwVersion>(),
+            Err(CwverError::Parse("failed to parse nightly-21w45.3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(version(21, 1, 1).to_string(), "21w01.1".to_string());
+        let from_date = |date| CwVersion::from_date(&date, &Schema::default()).to_string();
+        assert_eq!(from_date(NaiveDate::from_ymd(2021, 3, 14)), "21w10.7".to_string());
+        assert_eq!(from_date(NaiveDate::from_ymd(2022, 1, 2)), "21w52.7".to_string());
+        let long_year = Schema {
+            long_year: true,
+            ..Schema::default()
+        };
+        assert_eq!(
+            CwVersion::from_date(&NaiveDate::from_ymd(2022, 1, 2), &long_year).to_string(),
+            "2021w52.7".to_string()
+        );
+        let seventies = Schema {
+            pivot: 1970,
+            ..Schema::default()
+        };
+        assert_eq!(from_date(NaiveDate::from_ymd(1999, 3, 14)), "1999w10.7".to_string());
+        assert_eq!(
+            CwVersion::from_date(&NaiveDate::from_ymd(1999, 3, 14), &seventies).to_string(),
+            "99w10.7".to_string()
+        );
+    }
+
+    #[test]
+    fn test_ord_and_hash() {
+        let mut versions = vec![
+            version(21, 45, 3),
+            version(20, 53, 7),
+            version(21, 45, 1),
+            version(21, 45, 3),
+        ];
+        versions.sort();
+        assert_eq!(
+            versions,
+            vec![
+                version(20, 53, 7),
+                version(21, 45, 1),
+                version(21, 45, 3),
+                version(21, 45, 3)
+            ]
+        );
+        let unique: std::collections::HashSet<_> = versions.into_iter().collect();
+        assert_eq!(unique.len(), 3);
+    }
+
+    #[test]
+    fn test_ord_and_hash_mixed_years() {
+        assert_eq!(version(21, 45, 3), version(2021, 45, 3));
+        assert_ne!(version(21, 45, 3), version(2121, 45, 3));
+        let mut versions = vec![
+            version(22, 1, 1),
+            version(2021, 1, 1),
+            version(2099, 52, 7),
+            version(0, 1, 1),
+        ];
+        versions.sort();
+        assert_eq!(
+            versions,
+            vec![
+                version(0, 1, 1),
+                version(2021, 1, 1),
+                version(22, 1, 1),
+                version(2099, 52, 7)
+            ]
+        );
+        let unique: std::collections::HashSet<_> = vec![version(21, 45, 3), version(2021, 45, 3), version(2021, 45, 4)]
+            .into_iter()
+            .collect();
+        assert_eq!(unique.len(), 2);
+        assert!(unique.contains(&version(21, 45, 3)));
+        assert!(unique.contains(&version(2021, 45, 4)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        assert_eq!(serde_json::to_string(&version(21, 45, 7)).unwrap(), "\"21w45.7\"");
+        assert_eq!(
+            serde_json::from_str::<CwVersion>("\"21w45.7\"").unwrap(),
+            version(21, 45, 7)
+        );
+        assert_eq!(
+            serde_json::from_str::<CwVersion>("\"21w53.1\"")
+                .unwrap_err()
+                .to_string(),
+            "failed to calculate date of 21w53.1"
+        );
+        assert_eq!(
+            serde_json::from_str::<CwVersion>("\" 21w45.7\"")
+                .unwrap_err()
+                .to_string(),
+            "failed to parse  21w45.7"
+        );
+    }
+}
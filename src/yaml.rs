@@ -0,0 +1,105 @@
+use serde_json::Value;
+
+/// Renders a JSON document as YAML document. Strings are always double-quoted, JSON string
+/// escapes are valid in YAML as well, so that dates or versions like `21w45` are never read as
+/// other types.
+pub fn to_string(document: &Value) -> String {
+    let mut lines = vec!["---".to_string()];
+    block(document, 0, &mut lines);
+    lines.join("\n")
+}
+
+fn block(value: &Value, indent: usize, lines: &mut Vec<String>) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                match inline(value) {
+                    Some(value) => lines.push(format!("{}{}: {}", pad, key_str(key), value)),
+                    None => {
+                        lines.push(format!("{}{}:", pad, key_str(key)));
+                        block(value, indent + 2, lines);
+                    }
+                }
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                match inline(item) {
+                    Some(item) => lines.push(format!("{}- {}", pad, item)),
+                    None => {
+                        let mut nested = vec![];
+                        block(item, indent + 2, &mut nested);
+                        // the first line of the nested block starts right after the dash
+                        nested[0].replace_range(..indent + 2, &format!("{}- ", pad));
+                        lines.extend(nested);
+                    }
+                }
+            }
+        }
+        value => lines.push(format!("{}{}", pad, inline(value).unwrap_or_default())),
+    }
+}
+
+/// Scalars and empty collections, which are written on the line of their key.
+fn inline(value: &Value) -> Option<String> {
+    match value {
+        Value::Object(map) if map.is_empty() => Some("{}".to_string()),
+        Value::Array(items) if items.is_empty() => Some("[]".to_string()),
+        Value::Object(_) | Value::Array(_) => None,
+        scalar => Some(scalar.to_string()),
+    }
+}
+
+/// Identifier-like keys are written plain, all others quoted.
+fn key_str(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !["yes", "no", "on", "off", "true", "false", "null", "y", "n"].contains(&key.to_ascii_lowercase().as_str());
+    match plain {
+        true => key.to_string(),
+        false => Value::from(key).to_string(),
+    }
+}
+
+mod tests {
+    #[cfg(test)]
+    use super::*;
+    #[cfg(test)]
+    use serde_json::json;
+
+    #[test]
+    fn test_to_string() {
+        let document = json!({
+            "from": { "cwver": "21w40.1", "date": "2021-10-04" },
+            "workdays": 44,
+            "candidates": [{ "cwver": "21w44.4" }, { "cwver": "21w44.5" }],
+            "years": [2004, 2009],
+            "intervals": { "5": 7 },
+            "none": [],
+            "on": true,
+        });
+        assert_eq!(
+            to_string(&document),
+            [
+                "---",
+                "candidates:",
+                "  - cwver: \"21w44.4\"",
+                "  - cwver: \"21w44.5\"",
+                "from:",
+                "  cwver: \"21w40.1\"",
+                "  date: \"2021-10-04\"",
+                "intervals:",
+                "  \"5\": 7",
+                "none: []",
+                "\"on\": true",
+                "workdays: 44",
+                "years:",
+                "  - 2004",
+                "  - 2009",
+            ]
+            .join("\n")
+        );
+        assert_eq!(to_string(&json!([[1, 2], "a"])), "---\n- - 1\n  - 2\n- \"a\"");
+    }
+}